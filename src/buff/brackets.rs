@@ -0,0 +1,265 @@
+use std::{ops::Range, rc::Rc};
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Parser, QueryCursor, Tree};
+
+use super::Buffer;
+
+const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+// return the bracket we are looking for and if we have to search forward
+fn bracket_pair(c: char) -> Option<(char, bool)> {
+    BRACKETS.iter().find_map(|&(open, close)| match c {
+        _ if c == open => Some((close, true)),
+        _ if c == close => Some((open, false)),
+        _ => None,
+    })
+}
+
+// strings and comments are skipped when we look for the matching bracket
+fn is_skipped_kind(kind: &str) -> bool {
+    kind.contains("string") || kind.contains("comment") || kind == "char_literal"
+}
+
+// the tree of the buffer and the byte ranges of its strings and comments at some changes
+#[derive(Debug, Clone)]
+pub struct Syntax {
+    changes: usize,
+    pub tree: Tree,
+    pub skipped: Rc<Vec<Range<usize>>>,
+}
+
+impl Buffer {
+    // parse the whole buffer with the grammar of its query_language, the tree is kept until
+    // the lines change so the redraws and the keys which dont edit dont parse it again
+    pub fn syntax(&self) -> Option<Syntax> {
+        let (_, language) = self.query_language.as_ref()?;
+        let mut syntax = self.syntax.borrow_mut();
+        if let Some(syntax) = syntax.as_ref().filter(|s| s.changes == self.changes) {
+            return Some(syntax.clone());
+        }
+        let mut parser = Parser::new();
+        parser.set_language(language).ok()?;
+        let tree = parser.parse(self.lines.join("\n"), None)?;
+        let parsed = Syntax {
+            changes: self.changes,
+            skipped: Rc::new(Buffer::skipped_ranges(&tree)),
+            tree,
+        };
+        *syntax = Some(parsed.clone());
+        Some(parsed)
+    }
+
+    // byte offset of a (char x, y) position in the joined buffer
+    pub fn byte_offset(&self, pos: (u16, u16)) -> usize {
+        let line_start: usize = self.lines[..(pos.1 as usize).min(self.lines.len())]
+            .iter()
+            .map(|line| line.len() + 1)
+            .sum();
+        let x = match self.lines.get(pos.1 as usize) {
+            Some(line) => line
+                .char_indices()
+                .nth(pos.0 as usize)
+                .map(|(i, _)| i)
+                .unwrap_or(line.len()),
+            None => 0,
+        };
+        line_start + x
+    }
+
+    // every byte range covered by a string or a comment node
+    fn skipped_ranges(tree: &Tree) -> Vec<Range<usize>> {
+        let mut ranges = vec![];
+        let mut nodes = vec![tree.root_node()];
+        while let Some(node) = nodes.pop() {
+            if is_skipped_kind(node.kind()) {
+                ranges.push(node.byte_range());
+                continue;
+            }
+            let mut cursor = node.walk();
+            nodes.extend(node.children(&mut cursor));
+        }
+        ranges
+    }
+
+    // use the highlight captures to know if the cursor is inside a string or a comment,
+    // a line comment still contains the cursor at its end
+    pub fn is_in_string_or_comment(&self, pos: (u16, u16)) -> bool {
+        let (Some((query, _)), Some(syntax)) = (&self.query_language, self.syntax()) else {
+            return false;
+        };
+        let code = self.lines.join("\n");
+        let offset = self.byte_offset(pos);
+        let mut query_cursor = QueryCursor::new();
+        query_cursor.set_byte_range(offset.saturating_sub(1)..offset + 1);
+
+        let mut captures = query_cursor.captures(query, syntax.tree.root_node(), code.as_bytes());
+        while let Some((m, i)) = captures.next() {
+            let capture = m.captures[*i];
+            let range = capture.node.byte_range();
+            let inside = match query.capture_names()[capture.index as usize] {
+                name if name.contains("string") => range.start < offset && offset < range.end,
+                name if name.contains("comment") => range.start < offset && offset <= range.end,
+                _ => false,
+            };
+            if inside {
+                return true;
+            }
+        }
+        false
+    }
+
+    // return the position of the bracket matching the one under the cursor
+    pub fn matching_bracket(&self, pos: (u16, u16)) -> Option<(u16, u16)> {
+        let c = self._get_char(&pos)?;
+        let (target, forward) = bracket_pair(c)?;
+        let skipped = self.syntax().map(|s| s.skipped).unwrap_or_default();
+        // a bracket inside a string or a comment is matched with the raw text
+        let start_byte = self.byte_offset(pos);
+        let skipped: &[Range<usize>] = match skipped.iter().any(|r| r.contains(&start_byte)) {
+            true => &[],
+            false => &skipped,
+        };
+
+        let mut depth: usize = 0;
+        let mut y = pos.1 as usize;
+        let mut line_start = self.byte_offset((0, pos.1));
+
+        loop {
+            let line = self.lines.get(y)?;
+            let chars: Vec<(usize, char)> = line.char_indices().collect();
+            let indexes: Vec<usize> = match (forward, y == pos.1 as usize) {
+                (true, true) => (pos.0 as usize..chars.len()).collect(),
+                (true, false) => (0..chars.len()).collect(),
+                (false, true) => (0..=pos.0 as usize).rev().collect(),
+                (false, false) => (0..chars.len()).rev().collect(),
+            };
+
+            for i in indexes {
+                let (byte, ch) = chars[i];
+                if skipped.iter().any(|r| r.contains(&(line_start + byte))) {
+                    continue;
+                }
+                if ch == c {
+                    depth += 1;
+                } else if ch == target {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((i as u16, y as u16));
+                    }
+                }
+            }
+
+            match forward {
+                true => {
+                    line_start += line.len() + 1;
+                    y += 1;
+                }
+                false => {
+                    y = y.checked_sub(1)?;
+                    line_start -= self.lines[y].len() + 1;
+                }
+            }
+        }
+    }
+
+    // like matching_bracket but if the cursor isnt on a bracket we take the first one after it
+    // on the line, the ones of the strings and comments are skipped unless the cursor is in it
+    pub fn next_matching_bracket(&self, pos: (u16, u16)) -> Option<(u16, u16)> {
+        let line = self.lines.get(pos.1 as usize)?;
+        let skipped = self.syntax().map(|s| s.skipped).unwrap_or_default();
+        let cursor_byte = self.byte_offset(pos);
+        let line_start = self.byte_offset((0, pos.1));
+        let is_skipped = |byte: usize| {
+            skipped
+                .iter()
+                .any(|r| r.contains(&byte) && !r.contains(&cursor_byte))
+        };
+        let x = line
+            .char_indices()
+            .enumerate()
+            .skip(pos.0 as usize)
+            .find(|(_, (byte, c))| bracket_pair(*c).is_some() && !is_skipped(line_start + byte))
+            .map(|(x, _)| x as u16)?;
+        self.matching_bracket((x, pos.1))
+    }
+}
+
+#[cfg(test)]
+mod tests_brackets {
+    use tree_sitter::{Language, Query};
+
+    use super::*;
+
+    fn rust_buffer(lines: Vec<&str>) -> Buffer {
+        let language: Language = tree_sitter_rust::LANGUAGE.into();
+        let mut buffer = Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            "test.rs".to_string(),
+        );
+        buffer.query_language = Some((
+            Query::new(&language, tree_sitter_rust::HIGHLIGHTS_QUERY).expect("Query Error"),
+            language,
+        ));
+        buffer
+    }
+
+    #[test]
+    fn test_matching_bracket_across_lines() {
+        let buffer = rust_buffer(vec!["fn main() {", "    let x = (1, [2]);", "}"]);
+        assert_eq!(buffer.matching_bracket((10, 0)), Some((0, 2)));
+        assert_eq!(buffer.matching_bracket((0, 2)), Some((10, 0)));
+        assert_eq!(buffer.matching_bracket((16, 1)), Some((18, 1)));
+        assert_eq!(buffer.matching_bracket((1, 1)), None);
+    }
+
+    #[test]
+    fn test_matching_bracket_skip_strings_and_comments() {
+        let buffer = rust_buffer(vec![
+            "fn main() {",
+            "    let s = \"}\"; // }",
+            "    let c = '{';",
+            "}",
+        ]);
+        assert_eq!(buffer.matching_bracket((10, 0)), Some((0, 3)));
+        assert_eq!(buffer.matching_bracket((0, 3)), Some((10, 0)));
+    }
+
+    #[test]
+    fn test_next_matching_bracket() {
+        let buffer = rust_buffer(vec!["let v = vec![1, 2];"]);
+        assert_eq!(buffer.next_matching_bracket((0, 0)), Some((17, 0)));
+        assert_eq!(buffer.next_matching_bracket((18, 0)), None);
+        // the brackets of the strings and comments are skipped
+        let buffer = rust_buffer(vec!["let s = \"(\"; // [", "f(\"{\")"]);
+        assert_eq!(buffer.next_matching_bracket((0, 0)), None);
+        assert_eq!(buffer.next_matching_bracket((0, 1)), Some((5, 1)));
+    }
+
+    #[test]
+    fn test_is_in_string_or_comment() {
+        let buffer = rust_buffer(vec!["let s = \"ab\"; // c", "let c = 1;"]);
+        assert!(buffer.is_in_string_or_comment((10, 0)));
+        assert!(!buffer.is_in_string_or_comment((8, 0)));
+        assert!(!buffer.is_in_string_or_comment((12, 0)));
+        assert!(buffer.is_in_string_or_comment((18, 0)));
+        assert!(!buffer.is_in_string_or_comment((4, 1)));
+    }
+
+    #[test]
+    fn test_syntax_tree_follows_the_edits() {
+        let mut buffer = rust_buffer(vec!["let s = 1;"]);
+        assert!(!buffer.is_in_string_or_comment((9, 0)));
+        buffer.replace_lines(0..1, vec!["let s = \"1\";".to_string()]);
+        assert!(buffer.is_in_string_or_comment((9, 0)));
+        // the tree of the last changes is kept for the next calls
+        let syntax = buffer.syntax.borrow();
+        assert_eq!(syntax.as_ref().map(|s| s.changes), Some(buffer.changes));
+    }
+
+    #[test]
+    fn test_matching_bracket_without_language() {
+        let buffer = Buffer::new_tmp(vec!["(a \")\")".to_string()], "a.txt".to_string());
+        assert_eq!(buffer.matching_bracket((0, 0)), Some((4, 0)));
+    }
+}
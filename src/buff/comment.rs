@@ -0,0 +1,190 @@
+use crate::languages::CommentTokens;
+
+use super::Buffer;
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn indent_len(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_commented(line: &str, open: &str, close: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.len() >= open.len() + close.len()
+        && trimmed.starts_with(open)
+        && trimmed.ends_with(close)
+}
+
+// remove the open token at the start of the text and the close one at the end
+// with the space we add when commenting
+fn strip_tokens(text: &str, open: &str, close: &str) -> String {
+    let text = text.strip_prefix(open).unwrap_or(text);
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    if close.is_empty() {
+        return text.to_string();
+    }
+    let text = text.trim_end();
+    let text = text.strip_suffix(close).unwrap_or(text);
+    text.strip_suffix(' ').unwrap_or(text).to_string()
+}
+
+// comment every lines at the same indentation or uncomment them if they are all commented,
+// blank lines are kept as they are
+fn toggle_each_line(lines: &[String], open: &str, close: &str) -> Vec<String> {
+    let mut non_blank = lines.iter().filter(|line| !is_blank(line)).peekable();
+    let has_content = non_blank.peek().is_some();
+    let uncomment = has_content && non_blank.all(|line| is_commented(line, open, close));
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| indent_len(line))
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| match line {
+            _ if is_blank(line) => line.clone(),
+            _ if uncomment => {
+                let (spaces, text) = line.split_at(indent_len(line));
+                format!("{spaces}{}", strip_tokens(text, open, close))
+            }
+            _ => {
+                let (spaces, text) = line.split_at(indent);
+                match close.is_empty() {
+                    true => format!("{spaces}{open} {text}"),
+                    false => format!("{spaces}{open} {text} {close}"),
+                }
+            }
+        })
+        .collect()
+}
+
+// wrap all the lines in a single block comment or remove it if they already are
+fn toggle_block(lines: &[String], open: &str, close: &str) -> Option<Vec<String>> {
+    let first = lines.iter().position(|line| !is_blank(line))?;
+    let last = lines.iter().rposition(|line| !is_blank(line))?;
+    let mut lines = lines.to_vec();
+
+    let is_block = match first == last {
+        true => is_commented(&lines[first], open, close),
+        false => {
+            lines[first].trim_start().starts_with(open) && lines[last].trim_end().ends_with(close)
+        }
+    };
+
+    match is_block {
+        true => {
+            let (spaces, text) = lines[first].split_at(indent_len(&lines[first]));
+            lines[first] = format!("{spaces}{}", strip_tokens(text, open, ""));
+            let text = lines[last].trim_end();
+            let text = text.strip_suffix(close).unwrap_or(text);
+            lines[last] = text.strip_suffix(' ').unwrap_or(text).to_string();
+        }
+        false => {
+            let indent = indent_len(&lines[first]);
+            lines[first].insert_str(indent, &format!("{open} "));
+            lines[last].push_str(&format!(" {close}"));
+        }
+    }
+    Some(lines)
+}
+
+impl Buffer {
+    // return the lines between start and end (included) with their comment toggled,
+    // the language block comment is used on each line if it has no line comment
+    pub fn toggle_line_comment(
+        &self,
+        start: u16,
+        end: u16,
+        tokens: &CommentTokens,
+    ) -> Option<Vec<String>> {
+        let lines = self.lines.get(start as usize..=end as usize)?;
+        match (&tokens.line, &tokens.block) {
+            (Some(line), _) => Some(toggle_each_line(lines, line, "")),
+            (None, Some((open, close))) => Some(toggle_each_line(lines, open, close)),
+            (None, None) => None,
+        }
+    }
+
+    // same as toggle_line_comment but all the lines are in a single block comment
+    pub fn toggle_block_comment(
+        &self,
+        start: u16,
+        end: u16,
+        tokens: &CommentTokens,
+    ) -> Option<Vec<String>> {
+        let lines = self.lines.get(start as usize..=end as usize)?;
+        let (open, close) = tokens.block.as_ref()?;
+        toggle_block(lines, open, close)
+    }
+}
+
+#[cfg(test)]
+mod tests_comment {
+    use super::*;
+
+    fn buffer(lines: Vec<&str>) -> Buffer {
+        Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            "test.rs".to_string(),
+        )
+    }
+
+    fn tokens(line: Option<&str>, block: Option<(&str, &str)>) -> CommentTokens {
+        CommentTokens {
+            line: line.map(|l| l.to_string()),
+            block: block.map(|(o, c)| (o.to_string(), c.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_toggle_line_comment_keep_indentation() {
+        let rust = tokens(Some("//"), Some(("/*", "*/")));
+        let buffer = buffer(vec!["fn main() {", "    let a = 1;", "", "        a", "}"]);
+
+        let commented = buffer.toggle_line_comment(1, 3, &rust).unwrap();
+        assert_eq!(commented, vec!["    // let a = 1;", "", "    //     a"]);
+
+        let buffer = Buffer::new_tmp(commented, "test.rs".to_string());
+        let uncommented = buffer.toggle_line_comment(0, 2, &rust).unwrap();
+        assert_eq!(uncommented, vec!["    let a = 1;", "", "        a"]);
+    }
+
+    #[test]
+    fn test_toggle_line_comment_partially_commented() {
+        let lua = tokens(Some("--"), None);
+        let buffer = buffer(vec!["-- a", "b"]);
+        let commented = buffer.toggle_line_comment(0, 1, &lua).unwrap();
+        assert_eq!(commented, vec!["-- -- a", "-- b"]);
+    }
+
+    #[test]
+    fn test_toggle_line_comment_with_block_tokens() {
+        let css = tokens(None, Some(("/*", "*/")));
+        let buffer = buffer(vec!["  color: red;", "  /* margin: 0; */"]);
+        let commented = buffer.toggle_line_comment(0, 0, &css).unwrap();
+        assert_eq!(commented, vec!["  /* color: red; */"]);
+        let uncommented = buffer.toggle_line_comment(1, 1, &css).unwrap();
+        assert_eq!(uncommented, vec!["  margin: 0;"]);
+        assert_eq!(buffer.toggle_line_comment(0, 0, &tokens(None, None)), None);
+    }
+
+    #[test]
+    fn test_toggle_block_comment() {
+        let html = tokens(None, Some(("<!--", "-->")));
+        let buffer = buffer(vec!["  <div>", "  </div>"]);
+        let commented = buffer.toggle_block_comment(0, 1, &html).unwrap();
+        assert_eq!(commented, vec!["  <!-- <div>", "  </div> -->"]);
+
+        let buffer = Buffer::new_tmp(commented, "test.html".to_string());
+        let uncommented = buffer.toggle_block_comment(0, 1, &html).unwrap();
+        assert_eq!(uncommented, vec!["  <div>", "  </div>"]);
+        assert_eq!(
+            buffer.toggle_block_comment(0, 0, &tokens(Some("#"), None)),
+            None
+        );
+    }
+}
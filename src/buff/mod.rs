@@ -1,33 +1,66 @@
 use std::{
+    cell::RefCell,
+    collections::HashMap,
     fs::{self, File, OpenOptions},
     io::{Read, Write},
     ops::Range,
-    path::PathBuf,
+    path::{Path, PathBuf},
+    rc::Rc,
     str::FromStr,
 };
 
 use anyhow::Result;
 use tree_sitter::{Language, Query};
 
-use crate::languages::Languages;
+use crate::{
+    editor::core::{ex::ExError, options::Options},
+    languages::Languages,
+};
+
+mod brackets;
+mod comment;
+mod motions;
+mod search;
+mod text_object;
+
+pub use brackets::Syntax;
+pub use search::{search_regex, CaseOptions};
+pub use text_object::{is_delimiter_object, Pos};
+
+// a buffer shown by several viewports, like the windows of a split
+pub type SharedBuffer = Rc<RefCell<Buffer>>;
 
 #[derive(Debug)]
 pub struct Buffer {
     pub file: Option<File>,
     pub is_directory: bool,
-    pub path: String,
+    pub path: Option<String>, // none until a buffer opened without a path is saved somewhere
     pub lines: Vec<String>,
     pub query_language: Option<(Query, Language)>,
+    pub options: Options,                // the values set with :setlocal
+    pub marks: HashMap<char, usize>,     // the lines of the marks a to z
+    pub syntax: RefCell<Option<Syntax>>, // the tree of the last changes
+    pub changes: usize,                  // bumped by each edit of the lines
+    pub saved_changes: usize,            // the changes when the file was read or written
 }
 
 impl Buffer {
+    pub fn shared(self) -> SharedBuffer {
+        Rc::new(RefCell::new(self))
+    }
+
     pub fn new_tmp(lines: Vec<String>, path: String) -> Buffer {
         Buffer {
             file: None,
             is_directory: false,
             lines,
-            path,
+            path: Some(path),
             query_language: None,
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         }
     }
 
@@ -40,6 +73,8 @@ impl Buffer {
                 Ok(metadata) if metadata.is_dir() => {
                     return Buffer::from_dir(&f_path);
                 }
+                // a new file is named by its path and created when it is saved
+                Err(_) => return Buffer::new_tmp(vec![String::new()], f_path),
                 _ => (),
             }
         }
@@ -48,21 +83,26 @@ impl Buffer {
             file: None,
             is_directory: false,
             lines: vec![String::new()],
-            path: "Empty".to_string(),
+            path: None,
             query_language: None,
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         }
     }
 
     fn from_file(f_path: &str) -> Buffer {
         let mut file = None;
         let mut lines: Vec<String> = Vec::new();
-        let mut path = String::from("Empty");
+        let mut path = None;
 
         if let Ok(mut c_file) = File::open(f_path) {
             let mut buf = String::new();
             match c_file.read_to_string(&mut buf) {
                 Ok(u) => u,
-                Err(_) => panic!("Couldnt open file: {}", path),
+                Err(_) => panic!("Couldnt open file: {}", f_path),
             };
             file = Some(c_file);
             lines = buf.lines().map(|s| s.to_string()).collect();
@@ -70,7 +110,7 @@ impl Buffer {
                 lines.push("".to_string());
             }
 
-            path = f_path.to_string();
+            path = Some(f_path.to_string());
         }
 
         Buffer {
@@ -79,6 +119,11 @@ impl Buffer {
             lines,
             path,
             query_language: None,
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         }
     }
 
@@ -98,13 +143,18 @@ impl Buffer {
             file: None,
             is_directory: true,
             lines,
-            path: d_path,
+            path: Some(d_path),
             query_language: None,
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         }
     }
 
     pub fn parent_dir(&mut self) -> Option<Buffer> {
-        match PathBuf::from_str(&self.path) {
+        match PathBuf::from_str(self.name()) {
             Ok(path_buf) => match path_buf.parent() {
                 Some(parent_path) => {
                     let parent_path = parent_path.to_str().unwrap().to_string();
@@ -193,19 +243,22 @@ impl Buffer {
     }
 
     pub fn new_line_with_text(&mut self, cursor: (u16, u16)) -> u16 {
+        self.changes += 1;
         let y_pos: usize = cursor.1 as usize + 1;
-        let mut new_line = self.get_line_indentation(cursor.1 as usize);
-        let len = new_line.len();
+        let mut next_line_content = String::new();
 
         // slice the part of the string from cursor into the end;
         if let Some(line) = self.lines.get_mut(cursor.1 as usize) {
             let x = cursor.0 as usize;
-            let clone_line = line.clone();
-            let next_line_content = &clone_line[x..];
+            next_line_content = line[x..].to_string();
             line.replace_range(x.., "");
-            new_line.push_str(next_line_content);
         }
+        // the indentation only depend of what stay before the cursor like the { of {}
+        let mut new_line = self.get_line_indentation(cursor.1 as usize);
+        let len = new_line.len();
+        new_line.push_str(&next_line_content);
 
+        self.shift_marks(y_pos, 0, 1);
         match y_pos > self.lines.len() {
             true => {
                 self.lines.push(new_line);
@@ -219,10 +272,12 @@ impl Buffer {
 
     // return the indentation to place the cursor
     pub fn new_line(&mut self, cursor: (u16, u16)) -> u16 {
+        self.changes += 1;
         let y_pos: usize = cursor.1 as usize;
         let new_line = self.get_line_indentation(y_pos.saturating_sub(1));
         let len = new_line.len();
 
+        self.shift_marks(y_pos, 0, 1);
         match y_pos > self.lines.len() {
             true => {
                 self.lines.push(new_line);
@@ -235,6 +290,7 @@ impl Buffer {
     }
 
     pub fn add_char(&mut self, c: char, cursor: (u16, u16)) {
+        self.changes += 1;
         if let Some(line) = self.lines.get_mut(cursor.1 as usize) {
             let char_indices: Vec<_> = line.char_indices().collect();
 
@@ -247,15 +303,24 @@ impl Buffer {
         }
     }
     pub fn add_str(&mut self, s: String, cursor: (u16, u16)) {
+        self.changes += 1;
         if let Some(line) = self.lines.get_mut(cursor.1 as usize) {
-            line.insert_str(cursor.0 as usize, &s);
+            // the cursor is a char column
+            let byte = line
+                .char_indices()
+                .nth(cursor.0 as usize)
+                .map(|(i, _)| i)
+                .unwrap_or(line.len());
+            line.insert_str(byte, &s);
         }
     }
 
     pub fn remove(&mut self, y: usize) -> String {
+        self.changes += 1;
         let mut removed = String::new();
         if self.lines.get_mut(y).is_some() {
             removed = self.lines.remove(y);
+            self.shift_marks(y, 1, 0);
         }
         removed
     }
@@ -267,6 +332,7 @@ impl Buffer {
         range: Range<usize>,
         is_last_line: bool,
     ) -> (Option<String>, bool) {
+        self.changes += 1;
         // copy line
         let mut line = line[range.clone()].to_string();
         // get mutable line vec of lines
@@ -333,6 +399,7 @@ impl Buffer {
         block
     }
     pub fn remove_char(&mut self, cursor: (u16, u16)) -> Option<char> {
+        self.changes += 1;
         if let Some(line) = self.lines.get_mut(cursor.1 as usize) {
             let char_indices: Vec<_> = line.char_indices().collect();
 
@@ -350,59 +417,68 @@ impl Buffer {
     }
 
     pub fn remove_char_line(&mut self, cursor: (u16, u16)) {
+        self.changes += 1;
         let mut buf = String::new();
         if let Some(line) = self.get(cursor.1 as usize) {
             buf = line.clone();
             self.lines.remove(cursor.1 as usize);
+            self.shift_marks(cursor.1 as usize, 1, 0);
         }
         if let Some(prev_line) = self.lines.get_mut(cursor.1 as usize - 1) {
             prev_line.push_str(buf.as_str());
         }
     }
 
+    // the path of the buffer, or how vim shows a buffer without one
+    pub fn name(&self) -> &str {
+        self.path.as_deref().unwrap_or("[No Name]")
+    }
+
     pub fn save(&mut self) -> anyhow::Result<()> {
-        if let Some(_c_file) = &self.file {
-            let mut open_file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(self.path.clone())?;
-            for line in self.lines.iter() {
-                writeln!(open_file, "{line}")?;
-            }
+        if self.is_directory {
+            return Ok(());
         }
-
+        let Some(path) = &self.path else {
+            return Err(ExError::NoFileName.into());
+        };
+        self.write_to(path, false)?;
+        self.file = Some(File::open(path)?);
+        self.mark_saved();
         Ok(())
     }
 
-    // return a bool to know if the file is save
-    // only compare file and not the file_explorer
-    pub fn compare_file(&mut self) -> anyhow::Result<bool> {
-        if self.is_directory {
-            return Ok(false);
+    // write the lines to another file, its missing directories are created with `parents`
+    pub fn write_to(&self, path: &str, parents: bool) -> anyhow::Result<()> {
+        let parent = Path::new(path)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty());
+        if let Some(parent) = parent.filter(|_| parents) {
+            fs::create_dir_all(parent)?;
         }
+        let mut open_file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        for line in self.lines.iter() {
+            writeln!(open_file, "{line}")?;
+        }
+        Ok(())
+    }
 
-        if let Ok(mut c_file) = File::open(&self.path) {
-            let mut buf = String::new();
-            c_file.read_to_string(&mut buf).unwrap();
-
-            let lines = buf.lines().map(|s| s.to_string()).collect::<Vec<String>>();
+    // the lines were edited since the file was read or written, the directories never are
+    pub fn is_modified(&self) -> bool {
+        !self.is_directory && self.changes != self.saved_changes
+    }
 
-            // let matching = lines.iter().zip(&self.lines).filter(|&(a, b)| a == b);
-            for (a, b) in lines.iter().zip(&self.lines) {
-                if a != b {
-                    // not saved
-                    return Ok(true);
-                }
-            }
-        }
-        // if let Some(c_file) = &mut self.file {}
-        // no diff between file
-        Ok(false)
+    // the lines are the ones of the file
+    pub fn mark_saved(&mut self) {
+        self.saved_changes = self.changes;
     }
 
     pub fn push_or_insert(&mut self, line: String, y: usize) {
+        self.changes += 1;
+        self.shift_marks(y, 0, 1);
         match y >= self.lines.len() {
             true => self.lines.push(line),
             false => self.lines.insert(y, line),
@@ -410,23 +486,49 @@ impl Buffer {
     }
 
     pub fn insert_str(&mut self, y: usize, x: usize, content: &str) {
+        self.changes += 1;
         if let Some(buffer_line) = self.lines.get_mut(y) {
             buffer_line.insert_str(x, content);
         }
     }
 
+    // replace the lines in range by the new ones and return the old lines
+    pub fn replace_lines(&mut self, range: Range<usize>, lines: Vec<String>) -> Vec<String> {
+        self.changes += 1;
+        let end = range.end.min(self.lines.len());
+        let start = range.start.min(end);
+        self.shift_marks(start, end - start, lines.len());
+        let old = self.lines.splice(start..end, lines).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        old
+    }
+
+    // the lines y..y + removed are replaced by added lines, the marks below move with their
+    // line and the ones on a removed line are deleted
+    fn shift_marks(&mut self, y: usize, removed: usize, added: usize) {
+        self.marks
+            .retain(|_, line| !(y + added..y + removed).contains(line));
+        for line in self.marks.values_mut() {
+            if *line >= y + removed {
+                *line = *line + added - removed;
+            }
+        }
+    }
+
     pub fn create_files_or_directories(&mut self, filename: &String) -> Result<bool> {
         let mut is_created = true;
         match filename.contains('.') {
             true => {
-                let full_path = format!("{}/{}", self.path, filename);
+                let full_path = format!("{}/{}", self.name(), filename);
                 self.create_file(&full_path)?;
                 self.lines.push(full_path);
             }
             false if filename.chars().last().unwrap().eq(&'/') => {
                 let mut filename = filename.clone();
                 filename.pop();
-                let full_path = format!("{}/{}", self.path, filename);
+                let full_path = format!("{}/{}", self.name(), filename);
                 self.create_directory(&full_path)?;
                 self.lines.push(full_path);
             }
@@ -467,7 +569,9 @@ impl Buffer {
     }
 
     pub fn set_query_language(&mut self, languages: &Languages) {
-        if let Some((language, query_highlight)) = languages.get(&self.path) {
+        // the tree of another language is parsed again
+        *self.syntax.get_mut() = None;
+        if let Some((language, query_highlight, _)) = languages.get(self.name()) {
             self.query_language = Some((
                 Query::new(language, query_highlight).expect("Query_error"),
                 language.clone(),
@@ -506,7 +610,7 @@ mod tests_buffer {
         let file1_path = file1.path().to_str().unwrap().to_string();
         let buffer = Buffer::new(Some(file1_path.clone()));
 
-        assert_eq!(buffer.path, file1_path);
+        assert_eq!(buffer.name(), file1_path);
         assert!(!buffer.is_directory);
         assert!(!buffer.lines.is_empty());
     }
@@ -518,7 +622,7 @@ mod tests_buffer {
         let dir_path = dir.path().to_str().unwrap().to_string();
         let buffer = Buffer::new(Some(dir_path.clone()));
 
-        assert_eq!(buffer.path, dir_path);
+        assert_eq!(buffer.name(), dir_path);
         assert!(buffer.is_directory);
         assert!(!buffer.lines.is_empty());
     }
@@ -560,11 +664,14 @@ mod tests_buffer {
 
         let file1_path = file1.path().to_str().unwrap().to_string();
         let mut buffer = Buffer::new(Some(file1_path.clone()));
+        assert!(!buffer.is_modified());
 
         buffer.add_str("New content".to_string(), (0, 0));
+        assert!(buffer.is_modified());
 
         // Save the buffer back to the file
         buffer.save().unwrap();
+        assert!(!buffer.is_modified());
 
         // Check the file content to ensure it was saved correctly
         let mut file_content = String::new();
@@ -575,4 +682,12 @@ mod tests_buffer {
 
         assert!(file_content.contains("New content"));
     }
+
+    #[test]
+    fn test_save_without_name() {
+        let mut buffer = Buffer::new(None);
+        assert_eq!(buffer.name(), "[No Name]");
+        let err = buffer.save().unwrap_err();
+        assert_eq!(err.downcast::<ExError>().unwrap(), ExError::NoFileName);
+    }
 }
@@ -0,0 +1,133 @@
+use super::Buffer;
+
+// chars that can close a sentence after the punctuation like in: (end.) or "end."
+const SENTENCE_CLOSERS: [char; 4] = [')', ']', '"', '\''];
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+impl Buffer {
+    // return the first blank line after the current paragraph or the last line
+    pub fn next_paragraph(&self, y: u16) -> u16 {
+        let last = self.lines.len().saturating_sub(1);
+        let mut i = y as usize;
+        // we skip the blank lines we are on before searching the end of the paragraph
+        while i < last && is_blank(&self.lines[i]) {
+            i += 1;
+        }
+        while i < last && !is_blank(&self.lines[i]) {
+            i += 1;
+        }
+        i.min(last) as u16
+    }
+
+    // return the first blank line before the current paragraph or the first line
+    pub fn prev_paragraph(&self, y: u16) -> u16 {
+        let mut i = (y as usize).min(self.lines.len().saturating_sub(1));
+        while i > 0 && is_blank(&self.lines[i]) {
+            i -= 1;
+        }
+        while i > 0 && !is_blank(&self.lines[i]) {
+            i -= 1;
+        }
+        i as u16
+    }
+
+    // a sentence start after a . ! or ? followed by a whitespace, blank lines are also
+    // considered as a sentence
+    fn sentence_starts(&self) -> Vec<(u16, u16)> {
+        let mut starts = vec![];
+        let mut at_start = true;
+
+        for (y, line) in self.lines.iter().enumerate() {
+            if is_blank(line) {
+                starts.push((0, y as u16));
+                at_start = true;
+                continue;
+            }
+
+            // is_ending is true after a punctuation until we find a whitespace
+            let mut is_ending = false;
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    _ if at_start && !c.is_whitespace() => {
+                        starts.push((x as u16, y as u16));
+                        at_start = false;
+                    }
+                    '.' | '!' | '?' => is_ending = true,
+                    _ if c.is_whitespace() && is_ending => at_start = true,
+                    _ if !SENTENCE_CLOSERS.contains(&c) => is_ending = false,
+                    _ => (),
+                }
+            }
+            // the end of the line is like a whitespace
+            if is_ending {
+                at_start = true;
+            }
+        }
+        starts
+    }
+
+    pub fn next_sentence(&self, pos: (u16, u16)) -> (u16, u16) {
+        let (x, y) = pos;
+        self.sentence_starts()
+            .into_iter()
+            .find(|&(sx, sy)| sy > y || (sy == y && sx > x))
+            .unwrap_or_else(|| {
+                let last = self.lines.len().saturating_sub(1);
+                let len = self.lines.get(last).map(|l| l.chars().count()).unwrap_or(0);
+                (len.saturating_sub(1) as u16, last as u16)
+            })
+    }
+
+    pub fn prev_sentence(&self, pos: (u16, u16)) -> (u16, u16) {
+        let (x, y) = pos;
+        self.sentence_starts()
+            .into_iter()
+            .rev()
+            .find(|&(sx, sy)| sy < y || (sy == y && sx < x))
+            .unwrap_or((0, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests_motions {
+    use super::*;
+
+    fn buffer(lines: Vec<&str>) -> Buffer {
+        Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            "test.txt".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let buffer = buffer(vec!["a", "b", "", "", "c", "d", "", "e"]);
+        assert_eq!(buffer.next_paragraph(0), 2);
+        assert_eq!(buffer.next_paragraph(2), 6);
+        assert_eq!(buffer.next_paragraph(6), 7);
+        assert_eq!(buffer.prev_paragraph(7), 6);
+        assert_eq!(buffer.prev_paragraph(5), 3);
+        assert_eq!(buffer.prev_paragraph(1), 0);
+    }
+
+    #[test]
+    fn test_sentences() {
+        let buffer = buffer(vec![
+            "First one. Second (one.) Third",
+            "still third? e.g. fourth",
+            "",
+            "Last",
+        ]);
+        assert_eq!(buffer.next_sentence((0, 0)), (11, 0));
+        assert_eq!(buffer.next_sentence((11, 0)), (25, 0));
+        assert_eq!(buffer.next_sentence((25, 0)), (13, 1));
+        assert_eq!(buffer.next_sentence((13, 1)), (18, 1));
+        assert_eq!(buffer.next_sentence((18, 1)), (0, 2));
+        assert_eq!(buffer.next_sentence((0, 3)), (3, 3));
+        assert_eq!(buffer.prev_sentence((13, 1)), (25, 0));
+        assert_eq!(buffer.prev_sentence((11, 0)), (0, 0));
+    }
+}
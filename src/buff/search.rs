@@ -0,0 +1,120 @@
+use regex::{Regex, RegexBuilder};
+
+use super::Buffer;
+
+// how the case of the search is handled when the pattern has no \c or \C
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaseOptions {
+    pub ignorecase: bool,
+    pub smartcase: bool, // only ignore the case if the pattern is in lowercase
+}
+
+// build the regex of a search pattern in the rust syntax, some vim escapes are supported:
+// \c \C to force the case, \< \> for the word boundaries and \v which is a no-op because
+// the rust syntax is already "very magic". an invalid regex is searched as a literal string
+pub fn search_regex(pattern: &str, case: CaseOptions) -> Regex {
+    let mut forced_case = None;
+    let mut has_upper = false;
+    let mut translated = String::new();
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            has_upper |= c.is_uppercase();
+            translated.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('c') => forced_case = Some(true),
+            Some('C') => forced_case = Some(false),
+            Some('v') => {}
+            Some('<' | '>') => translated.push_str("\\b"),
+            Some(c) => {
+                translated.push('\\');
+                translated.push(c);
+            }
+            None => translated.push_str("\\\\"),
+        }
+    }
+
+    let ignore_case = forced_case.unwrap_or(case.ignorecase && !(case.smartcase && has_upper));
+    let build = |pattern: &str| {
+        RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .multi_line(true)
+            .build()
+    };
+    build(&translated).unwrap_or_else(|_| build(&regex::escape(pattern)).unwrap())
+}
+
+impl Buffer {
+    // all the matches as (x, y, len) in chars, a match on multiple lines is cut at the end
+    // of its first line
+    pub fn search(&self, regex: &Regex) -> Vec<(u16, u16, u16)> {
+        let text = self.lines.join("\n");
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+
+        regex
+            .find_iter(&text)
+            .map(|m| {
+                let y = line_starts.partition_point(|start| *start <= m.start()) - 1;
+                let line_start = line_starts[y];
+                let line_end = line_start + self.lines[y].len();
+                let x = text[line_start..m.start()].chars().count();
+                let len = text[m.start()..m.end().min(line_end)].chars().count();
+                (x as u16, y as u16, len as u16)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_search {
+    use super::*;
+
+    const SMARTCASE: CaseOptions = CaseOptions {
+        ignorecase: true,
+        smartcase: true,
+    };
+
+    fn buffer(lines: Vec<&str>) -> Buffer {
+        Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            "a.txt".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_search_smartcase() {
+        let buffer = buffer(vec!["Foo foo FOO"]);
+        let search = |pattern| buffer.search(&search_regex(pattern, SMARTCASE)).len();
+        assert_eq!(search("foo"), 3);
+        assert_eq!(search("Foo"), 1);
+        assert_eq!(search("Foo\\c"), 3);
+        assert_eq!(search("\\Cfoo"), 1);
+        assert_eq!(search("\\<fo"), 3);
+        // \W is not an uppercase letter of the pattern
+        assert_eq!(search("o\\Wf"), 2);
+    }
+
+    #[test]
+    fn test_search_regex_and_char_columns() {
+        let buffer = buffer(vec!["éà let x1 = 2;", "let y22 = 3;"]);
+        let regex = search_regex("\\v[a-z]\\d+", SMARTCASE);
+        assert_eq!(buffer.search(&regex), vec![(7, 0, 2), (4, 1, 3)]);
+
+        // invalid regex are literal
+        let regex = search_regex("x1 = (", SMARTCASE);
+        assert!(buffer.search(&regex).is_empty());
+    }
+
+    #[test]
+    fn test_search_multi_line() {
+        let buffer = buffer(vec!["fn a() {", "}", "fn b() {", "    x", "}"]);
+        let regex = search_regex("\\{\\n}", SMARTCASE);
+        assert_eq!(buffer.search(&regex), vec![(7, 0, 1)]);
+        let regex = search_regex("^fn", SMARTCASE);
+        assert_eq!(buffer.search(&regex).len(), 2);
+    }
+}
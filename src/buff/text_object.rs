@@ -0,0 +1,390 @@
+use std::ops::Range;
+
+use super::Buffer;
+
+// a position in the buffer, the end of a range is excluded
+pub type Pos = (u16, u16);
+
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+// the open and close char of a bracket object like i( or a}
+fn bracket_object(obj: char) -> Option<(char, char)> {
+    match obj {
+        '(' | ')' | 'b' => Some(('(', ')')),
+        '[' | ']' => Some(('[', ']')),
+        '{' | '}' | 'B' => Some(('{', '}')),
+        '<' | '>' => Some(('<', '>')),
+        _ => None,
+    }
+}
+
+fn is_word_char(c: char, big_word: bool) -> bool {
+    match big_word {
+        true => !c.is_whitespace(),
+        false => c.is_alphanumeric() || c == '_',
+    }
+}
+
+// the objects that have an open and a close delimiter like quotes, brackets or tags
+pub fn is_delimiter_object(obj: char) -> bool {
+    obj == 't' || QUOTES.contains(&obj) || bracket_object(obj).is_some()
+}
+
+// a tag found in the text: name, char range and if it is a closing one
+struct Tag {
+    name: String,
+    start: usize,
+    end: usize,
+    closing: bool,
+}
+
+fn parse_tags(chars: &[char]) -> Vec<Tag> {
+    let mut tags = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '<' {
+            i += 1;
+            continue;
+        }
+        let Some(len) = chars[i..].iter().position(|c| *c == '>') else {
+            break;
+        };
+        let content: String = chars[i + 1..i + len].iter().collect();
+        let closing = content.starts_with('/');
+        let name: String = content
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        // comments, doctype and self closing tags cannot surround anything
+        if !name.is_empty() && !content.ends_with('/') {
+            tags.push(Tag {
+                name,
+                start: i,
+                end: i + len + 1,
+                closing,
+            });
+        }
+        i += len + 1;
+    }
+    tags
+}
+
+impl Buffer {
+    // the whole buffer as chars with a \n between lines and the index of pos in it
+    fn flat_chars(&self, pos: Pos) -> (Vec<char>, usize) {
+        let mut chars = vec![];
+        let mut cursor = 0;
+        for (y, line) in self.lines.iter().enumerate() {
+            if y == pos.1 as usize {
+                cursor = chars.len() + (pos.0 as usize).min(line.chars().count());
+            }
+            chars.extend(line.chars());
+            chars.push('\n');
+        }
+        chars.pop();
+        (chars, cursor)
+    }
+
+    // convert an index of flat_chars into a buffer position
+    fn flat_pos(&self, mut index: usize) -> Pos {
+        for (y, line) in self.lines.iter().enumerate() {
+            let len = line.chars().count();
+            if index <= len {
+                return (index as u16, y as u16);
+            }
+            index -= len + 1;
+        }
+        let last = self.lines.len().saturating_sub(1);
+        let len = self.lines.get(last).map(|l| l.chars().count()).unwrap_or(0);
+        (len as u16, last as u16)
+    }
+
+    // return the range of a text object like iw, a", i( or at
+    // the start is included and the end excluded
+    pub fn text_object(&self, pos: Pos, around: bool, obj: char) -> Option<(Pos, Pos)> {
+        let (chars, cursor) = self.flat_chars(pos);
+        let (start, end) = match obj {
+            'w' | 'W' => word_object(&chars, cursor, around, obj == 'W')?,
+            'p' => return None, // paragraphs are handled as lines by the operators
+            't' => tag_object(&chars, cursor, around)?,
+            _ if QUOTES.contains(&obj) => quote_object(&chars, cursor, around, obj)?,
+            _ => {
+                let (open, close) = bracket_object(obj)?;
+                let (start, end) = enclosing_pair(&chars, cursor, open, close)?;
+                match around {
+                    true => (start, end + 1),
+                    false => (start + 1, end),
+                }
+            }
+        };
+        Some((self.flat_pos(start), self.flat_pos(end)))
+    }
+
+    // the ranges of the open and close delimiters surrounding the position
+    pub fn surrounding(&self, pos: Pos, obj: char) -> Option<((Pos, Pos), (Pos, Pos))> {
+        let (outer_start, outer_end) = self.text_object(pos, true, obj)?;
+        let (inner_start, inner_end) = self.text_object(pos, false, obj)?;
+        Some(((outer_start, inner_start), (inner_end, outer_end)))
+    }
+
+    // apply the replacements (start, end, text) and return the range of lines touched with
+    // their new content
+    pub fn replaced_lines(
+        &self,
+        replacements: &[(Pos, Pos, String)],
+    ) -> (Range<usize>, Vec<String>) {
+        let first_y = replacements.iter().map(|(s, _, _)| s.1).min().unwrap_or(0);
+        let last_y = replacements.iter().map(|(_, e, _)| e.1).max().unwrap_or(0);
+        let mut text: Vec<char> = self.lines[first_y as usize..=last_y as usize]
+            .join("\n")
+            .chars()
+            .collect();
+
+        let offset = |pos: Pos| -> usize {
+            let line_start: usize = self.lines[first_y as usize..pos.1 as usize]
+                .iter()
+                .map(|line| line.chars().count() + 1)
+                .sum();
+            let len = self.lines[pos.1 as usize].chars().count();
+            line_start + (pos.0 as usize).min(len)
+        };
+
+        let mut sorted: Vec<&(Pos, Pos, String)> = replacements.iter().collect();
+        sorted.sort_by_key(|(start, _, _)| std::cmp::Reverse((start.1, start.0)));
+        for (start, end, content) in sorted {
+            let (start, end) = (offset(*start).min(text.len()), offset(*end).min(text.len()));
+            text.splice(start..end, content.chars());
+        }
+
+        let text: String = text.into_iter().collect();
+        let lines = text.split('\n').map(|l| l.to_string()).collect();
+        (first_y as usize..last_y as usize + 1, lines)
+    }
+}
+
+fn word_object(chars: &[char], cursor: usize, around: bool, big: bool) -> Option<(usize, usize)> {
+    let c = *chars.get(cursor)?;
+    // on a whitespace iw select the whitespaces, on a punctuation only the punctuations
+    let same_kind = |ch: char| match () {
+        _ if c.is_whitespace() => ch.is_whitespace() && ch != '\n',
+        _ if is_word_char(c, big) => is_word_char(ch, big),
+        _ => !ch.is_whitespace() && !is_word_char(ch, big),
+    };
+    let mut start = cursor;
+    while start > 0 && same_kind(chars[start - 1]) {
+        start -= 1;
+    }
+    let mut end = cursor + 1;
+    while end < chars.len() && same_kind(chars[end]) {
+        end += 1;
+    }
+    if around && !c.is_whitespace() {
+        let is_space = |ch: char| ch == ' ' || ch == '\t';
+        match chars.get(end).is_some_and(|ch| is_space(*ch)) {
+            true => {
+                while end < chars.len() && is_space(chars[end]) {
+                    end += 1;
+                }
+            }
+            false => {
+                while start > 0 && is_space(chars[start - 1]) {
+                    start -= 1;
+                }
+            }
+        }
+    }
+    Some((start, end))
+}
+
+// quotes are only searched on the line of the cursor, they are paired from the start of the
+// line and if the cursor isnt inside a pair we take the next one
+fn quote_object(
+    chars: &[char],
+    cursor: usize,
+    around: bool,
+    quote: char,
+) -> Option<(usize, usize)> {
+    let line_start = chars[..cursor]
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |i| i + 1);
+    let line_end = chars[cursor..]
+        .iter()
+        .position(|c| *c == '\n')
+        .map_or(chars.len(), |i| cursor + i);
+
+    let quotes: Vec<usize> = (line_start..line_end)
+        .filter(|&i| chars[i] == quote && (i == line_start || chars[i - 1] != '\\'))
+        .collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| cursor <= close)?;
+
+    match around {
+        true => Some((open, close + 1)),
+        false => Some((open + 1, close)),
+    }
+}
+
+// find the open bracket before the cursor that isnt closed and its matching close bracket
+fn enclosing_pair(
+    chars: &[char],
+    cursor: usize,
+    open: char,
+    close: char,
+) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut start = None;
+    for i in (0..=cursor.min(chars.len().checked_sub(1)?)).rev() {
+        match chars[i] {
+            c if c == close && i != cursor => depth += 1,
+            c if c == open && depth == 0 => {
+                start = Some(i);
+                break;
+            }
+            c if c == open => depth -= 1,
+            _ => (),
+        }
+    }
+    let start = start?;
+
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(start + 1) {
+        match *c {
+            c if c == open => depth += 1,
+            c if c == close && depth == 0 => return Some((start, i)),
+            c if c == close => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+// the innermost pair of tags surrounding the cursor
+fn tag_object(chars: &[char], cursor: usize, around: bool) -> Option<(usize, usize)> {
+    let mut stack: Vec<&Tag> = vec![];
+    let mut pairs = vec![];
+    let tags = parse_tags(chars);
+    for tag in &tags {
+        match tag.closing {
+            false => stack.push(tag),
+            true => {
+                // close the last open tag with the same name and forget the unclosed ones
+                if let Some(i) = stack.iter().rposition(|open| open.name == tag.name) {
+                    pairs.push((stack[i], tag));
+                    stack.truncate(i);
+                }
+            }
+        }
+    }
+
+    let (open, close) = pairs
+        .into_iter()
+        .filter(|(open, close)| open.start <= cursor && cursor < close.end)
+        .min_by_key(|(open, close)| close.end - open.start)?;
+    match around {
+        true => Some((open.start, close.end)),
+        false => Some((open.end, close.start)),
+    }
+}
+
+#[cfg(test)]
+mod tests_text_object {
+    use super::*;
+
+    fn buffer(lines: Vec<&str>) -> Buffer {
+        Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            "test.txt".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_word_object() {
+        let buffer = buffer(vec!["let foo_bar = 1;"]);
+        assert_eq!(
+            buffer.text_object((5, 0), false, 'w'),
+            Some(((4, 0), (11, 0)))
+        );
+        assert_eq!(
+            buffer.text_object((5, 0), true, 'w'),
+            Some(((4, 0), (12, 0)))
+        );
+        assert_eq!(
+            buffer.text_object((12, 0), true, 'w'),
+            Some(((12, 0), (14, 0)))
+        );
+        assert_eq!(
+            buffer.text_object((15, 0), true, 'w'),
+            Some(((15, 0), (16, 0)))
+        );
+    }
+
+    #[test]
+    fn test_quote_object() {
+        let buffer = buffer(vec![r#"a("b", "c\"d")"#]);
+        assert_eq!(
+            buffer.text_object((4, 0), false, '"'),
+            Some(((3, 0), (4, 0)))
+        );
+        assert_eq!(
+            buffer.text_object((0, 0), true, '"'),
+            Some(((2, 0), (5, 0)))
+        );
+        assert_eq!(
+            buffer.text_object((9, 0), false, '"'),
+            Some(((8, 0), (12, 0)))
+        );
+        assert_eq!(buffer.text_object((0, 0), false, '\''), None);
+    }
+
+    #[test]
+    fn test_bracket_object_across_lines() {
+        let buffer = buffer(vec!["fn a() {", "    (b)", "}"]);
+        assert_eq!(
+            buffer.text_object((4, 1), false, '{'),
+            Some(((8, 0), (0, 2)))
+        );
+        assert_eq!(
+            buffer.text_object((5, 1), true, 'b'),
+            Some(((4, 1), (7, 1)))
+        );
+        assert_eq!(
+            buffer.text_object((7, 0), true, 'B'),
+            Some(((7, 0), (1, 2)))
+        );
+        assert_eq!(buffer.text_object((0, 0), true, '['), None);
+    }
+
+    #[test]
+    fn test_tag_object() {
+        let buffer = buffer(vec!["<div class=\"a\">", "  <p>hi<br/></p>", "</div>"]);
+        assert_eq!(
+            buffer.text_object((6, 1), false, 't'),
+            Some(((5, 1), (12, 1)))
+        );
+        assert_eq!(
+            buffer.text_object((6, 1), true, 't'),
+            Some(((2, 1), (16, 1)))
+        );
+        assert_eq!(
+            buffer.text_object((0, 1), true, 't'),
+            Some(((0, 0), (6, 2)))
+        );
+    }
+
+    #[test]
+    fn test_replaced_lines() {
+        let buffer = buffer(vec!["a (b", "c) d"]);
+        let replacements = vec![
+            ((2, 0), (3, 0), "[".to_string()),
+            ((1, 1), (2, 1), "]".to_string()),
+        ];
+        assert_eq!(
+            buffer.replaced_lines(&replacements),
+            (0..2, vec!["a [b".to_string(), "c] d".to_string()])
+        );
+    }
+}
@@ -2,6 +2,92 @@
 -- Shift Control Option Command Hyper Meta
 
 return {
+	-- they can be changed with :set name=value, :set name, :set noname and :setlocal for the
+	-- current viewport or buffer only
+	options = {
+		-- number of lines kept visible above and below the cursor
+		scrolloff = 3,
+		-- ignorecase makes the search ignore the case, with smartcase only when the pattern has no
+		-- uppercase letter, \c and \C force it
+		ignorecase = false,
+		smartcase = false,
+		-- milliseconds to type the next key of a sequence like gg
+		timeoutlen = 1000,
+		-- spaces of a shift with > < and of the indentation after a brace
+		shiftwidth = 4,
+		-- spaces added by Tab when there is no snippet to expand
+		softtabstop = 2,
+		-- width of the line numbers
+		numberwidth = 5,
+		-- percentage of the screen left around the popups
+		popupmargin = 30,
+		-- the cursor can go after the last char of the line in normal mode
+		onemore = false,
+	},
+	-- :make runs the command in the background with its arguments, cargo gives its errors as json
+	-- and the output of the other commands is read with the patterns, also used by :cfile
+	-- their groups are file, line, col, type and message, a message without file takes the
+	-- location of the next lines like rustc and a location without message the next message like
+	-- the python tracebacks
+	make = {
+		command = "cargo build",
+		patterns = {
+			-- rustc
+			[[^(?P<type>error|warning)(\[\w+\])?: (?P<message>.*)$]],
+			[[^\s*--> (?P<file>.+):(?P<line>\d+):(?P<col>\d+)$]],
+			-- gcc and clang
+			[[^(?P<file>[^\s:][^:]*):(?P<line>\d+):(?P<col>\d+): (?:fatal )?(?P<type>error|warning): (?P<message>.*)$]],
+			-- tsc
+			[[^(?P<file>[^\s(]+)\((?P<line>\d+),(?P<col>\d+)\): (?P<type>error|warning) (?P<message>.*)$]],
+			-- python tracebacks
+			[[^\s*File "(?P<file>[^"]+)", line (?P<line>\d+)]],
+			[[^(?P<message>[A-Z]\w*(Error|Exception|Warning|Interrupt)(: .*)?)$]],
+			-- path:line:col: message
+			[[^(?P<file>[^\s:][^:]*):(?P<line>\d+):(?:(?P<col>\d+):)? (?P<message>.*)$]],
+		},
+	},
+	-- the commands run by :Name, run receives the opts with name, args, fargs, bang, range, line1
+	-- and line2 and the editor with line_count(), path(), get_lines(first, last),
+	-- set_lines(first, last, lines), get_cursor(), set_cursor(line, col), toast(msg), error(msg),
+	-- setqflist(entries, title), getqflist(), setloclist(entries, title) and getloclist() where
+	-- the entries are { filename, lnum, col, text } tables or "path:line:col: message" strings
+	-- the lines start at 1 and a set_lines with last at first - 1 inserts the lines
+	-- nargs is "0", "1", "?", "*" or "+", range true uses the current line by default and "%" the
+	-- whole file, bang allows a ! and complete is "file", "option" or "buffer"
+	commands = {
+		-- remove the trailing whitespaces
+		Trim = {
+			range = "%",
+			run = function(opts, editor)
+				local lines = editor.get_lines(opts.line1, opts.line2)
+				for i, line in ipairs(lines) do
+					lines[i] = line:gsub("%s+$", "")
+				end
+				editor.set_lines(opts.line1, opts.line2, lines)
+			end,
+		},
+	},
+	-- override the comment tokens of a language by its extension
+	-- ex: rs = { line = "//", block = { "/*", "*/" } }
+	comments = {},
+	-- pairs closed automatically in insert mode by extension, default is used for the others
+	autopairs = {
+		default = { "()", "[]", "{}", '""', "''" },
+		rs = { "()", "[]", "{}", '""' },
+		html = { "()", "[]", "{}", '""', "''", "<>" },
+	},
+	-- snippets by extension expanded with Tab in insert mode, default is used for all files
+	-- $1 $2 are tab stops, ${1:text} a placeholder, the same number is mirrored and $0 is the end
+	-- they can also be defined in ~/.rusty/snippets/rs.lua returning { trigger = body }
+	snippets = {
+		rs = {
+			fn = "fn ${1:name}(${2}) {\n    $0\n}",
+			test = "#[test]\nfn ${1:name}() {\n    $0\n}",
+		},
+		lua = {
+			fn = "local function ${1:name}(${2})\n    $0\nend",
+		},
+	},
 	keybinds = {
 		normal = {
 			{
@@ -22,17 +108,18 @@ return {
 				description = "Delete other viewport",
 				modifiers = "",
 			},
+			-- the viewports were switched with H and L before, those are the screen motions now
 			{
-				key = "H",
+				key = "<leader>bp",
 				action = "PrevViewport",
 				description = "Switch to the previous viewport",
-				modifiers = "Shift",
+				modifiers = "",
 			},
 			{
-				key = "L",
+				key = "<leader>bn",
 				action = "NextViewport",
 				description = "Switch to the next viewport",
-				modifiers = "Shift",
+				modifiers = "",
 			},
 			{ key = "v", action = "EnterMode Visual", description = "Switches to Visual mode.", modifiers = "" },
 			{ key = "h", action = "MoveLeft", description = "Move left by 1", modifiers = "" },
@@ -47,6 +134,54 @@ return {
 
 			{ key = "w", action = "MoveNext", description = "Move to the next different char", modifiers = "" },
 			{ key = "b", action = "MovePrev", description = "Move to the prev different char", modifiers = "" },
+			{
+				key = "%",
+				action = "JumpMatchingBracket",
+				description = "Jump to the matching bracket",
+				modifiers = "",
+			},
+			{
+				key = "gc",
+				action = "OperatorPending comment",
+				description = "Toggle line comments on a motion, gcc for the current line",
+				modifiers = "",
+			},
+			{
+				key = "m",
+				action = "OperatorPending mark",
+				description = "Sets a mark on the line, jump to it with :'a",
+				modifiers = "",
+			},
+			{
+				key = "w",
+				action = "OperatorPending window",
+				description = "Window commands like s, v to split, hjkl to move and + - < > to resize",
+				modifiers = "Control",
+			},
+			{
+				key = "gb",
+				action = "OperatorPending block_comment",
+				description = "Toggle a block comment on a motion, gbc for the current line",
+				modifiers = "",
+			},
+			{
+				key = "ys",
+				action = "OperatorPending surround_add",
+				description = "Surround a motion with a char or a tag, yss for the current line",
+				modifiers = "",
+			},
+			{
+				key = "cs",
+				action = "OperatorPending surround_change",
+				description = "Change the surrounding quotes, brackets or tag",
+				modifiers = "",
+			},
+			{
+				key = "ds",
+				action = "OperatorPending surround_delete",
+				description = "Delete the surrounding quotes, brackets or tag",
+				modifiers = "",
+			},
 			{
 				key = "zz",
 				action = "CenterLine",
@@ -77,13 +212,33 @@ return {
 			},
 
 			-- Search Actions
-			{ key = "/", action = "EnterMode Search", description = "Switches to Search mode.", modifiers = "" },
+			{ key = "/", action = "StartSearch", description = "Switches to Search mode.", modifiers = "" },
+			{ key = "?", action = "StartSearch backward", description = "Search backward.", modifiers = "" },
+			{ key = "Return", action = "OpenResult", description = "Opens the entry of a results list.", modifiers = "" },
 			{
 				key = "n",
 				action = "IterNextSearch",
 				description = "Jumps to the next search occurrence.",
 				modifiers = "",
 			},
+			{
+				key = "N",
+				action = "IterPrevSearch",
+				description = "Jumps to the previous search occurrence.",
+				modifiers = "Shift",
+			},
+			{
+				key = "*",
+				action = "SearchWordUnderCursor",
+				description = "Search forward the word under the cursor.",
+				modifiers = "",
+			},
+			{
+				key = "#",
+				action = "SearchWordUnderCursor backward",
+				description = "Search backward the word under the cursor.",
+				modifiers = "",
+			},
 
 			-- Insert Actions
 			{ key = "i", action = "EnterInsertMode", description = "Switches to Insert mode.", modifiers = "" },
@@ -128,6 +283,25 @@ return {
 			-- Movement Actions
 			{ key = "Page Up", action = "PageUp", description = "Scrolls up by one page.", modifiers = "" },
 			{ key = "Page Down", action = "PageDown", description = "Scrolls down by one page.", modifiers = "" },
+			{ key = "}", action = "NextParagraph", description = "Move to the next blank line", modifiers = "" },
+			{ key = "{", action = "PrevParagraph", description = "Move to the prev blank line", modifiers = "" },
+			{ key = ")", action = "NextSentence", description = "Move to the next sentence", modifiers = "" },
+			{ key = "(", action = "PrevSentence", description = "Move to the prev sentence", modifiers = "" },
+			{ key = "H", action = "ScreenTop", description = "Move to the top of the screen", modifiers = "Shift" },
+			{ key = "M", action = "ScreenMiddle", description = "Move to the middle of the screen", modifiers = "Shift" },
+			{ key = "L", action = "ScreenBottom", description = "Move to the bottom of the screen", modifiers = "Shift" },
+			{ key = "d", action = "HalfPageDown", description = "Scrolls down by half a page.", modifiers = "Control" },
+			{ key = "u", action = "HalfPageUp", description = "Scrolls up by half a page.", modifiers = "Control" },
+			{ key = "e", action = "ScrollLineDown", description = "Scrolls down by one line.", modifiers = "Control" },
+			{ key = "y", action = "ScrollLineUp", description = "Scrolls up by one line.", modifiers = "Control" },
+			{
+				key = "n",
+				action = "AddCursorNextMatch",
+				description = "Add a cursor on the next match of the word",
+				modifiers = "Control",
+			},
+			{ key = "Down", action = "AddCursorBelow", description = "Add a cursor below", modifiers = "Control" },
+			{ key = "Up", action = "AddCursorAbove", description = "Add a cursor above", modifiers = "Control" },
 			{ key = "[", action = "PageUp", description = "Scrolls up by one page.", modifiers = "" },
 			{ key = "]", action = "PageDown", description = "Scrolls down by one page.", modifiers = "" },
 
@@ -176,9 +350,45 @@ return {
 
 			{ key = "w", action = "MoveNext", description = "Move to the next different char", modifiers = "" },
 			{ key = "b", action = "MovePrev", description = "Move to the prev different char", modifiers = "" },
+			{
+				key = "%",
+				action = "JumpMatchingBracket",
+				description = "Jump to the matching bracket",
+				modifiers = "",
+			},
+			{
+				key = "gc",
+				action = "CommentVisual",
+				description = "Toggle line comments on the selected lines",
+				modifiers = "",
+			},
+			{
+				key = "gb",
+				action = "BlockCommentVisual",
+				description = "Toggle a block comment around the selected lines",
+				modifiers = "",
+			},
+			{
+				key = "S",
+				action = "OperatorPending surround_visual",
+				description = "Surround the selection with a char or a tag",
+				modifiers = "Shift",
+			},
+			{
+				key = "I",
+				action = "CursorsFromVisual",
+				description = "Add a cursor on each selected line",
+				modifiers = "Shift",
+			},
 
 			{ key = "Esc", action = "EnterMode Normal", description = "Switches to Normal mode.", modifiers = "" },
 			{ key = ":", action = "EnterMode Command", description = "Switches to Command mode.", modifiers = "" },
+			{
+				key = "!",
+				action = "FilterSelection",
+				description = "Filters the selected lines through a shell command.",
+				modifiers = "",
+			},
 
 			-- Actions
 			{ key = "d", action = "DeleteBlock", description = "Deletes a selected block of text.", modifiers = "" },
@@ -187,6 +397,17 @@ return {
 			-- Movement Actions
 			{ key = "Page Up", action = "PageUp", description = "Scrolls up by one page.", modifiers = "" },
 			{ key = "Page Down", action = "PageDown", description = "Scrolls down by one page.", modifiers = "" },
+			{ key = "}", action = "NextParagraph", description = "Move to the next blank line", modifiers = "" },
+			{ key = "{", action = "PrevParagraph", description = "Move to the prev blank line", modifiers = "" },
+			{ key = ")", action = "NextSentence", description = "Move to the next sentence", modifiers = "" },
+			{ key = "(", action = "PrevSentence", description = "Move to the prev sentence", modifiers = "" },
+			{ key = "H", action = "ScreenTop", description = "Move to the top of the screen", modifiers = "Shift" },
+			{ key = "M", action = "ScreenMiddle", description = "Move to the middle of the screen", modifiers = "Shift" },
+			{ key = "L", action = "ScreenBottom", description = "Move to the bottom of the screen", modifiers = "Shift" },
+			{ key = "d", action = "HalfPageDown", description = "Scrolls down by half a page.", modifiers = "Control" },
+			{ key = "u", action = "HalfPageUp", description = "Scrolls up by half a page.", modifiers = "Control" },
+			{ key = "e", action = "ScrollLineDown", description = "Scrolls down by one line.", modifiers = "Control" },
+			{ key = "y", action = "ScrollLineUp", description = "Scrolls up by one line.", modifiers = "Control" },
 			{
 				key = "G",
 				action = "EndOfFile",
@@ -247,10 +468,22 @@ return {
 			},
 			{
 				key = "Tab",
-				action = "AddStr /space/space",
-				description = "Adds a string of text at the cursor position.",
+				action = "ExpandSnippet",
+				description = "Expands the snippet before the cursor or adds a string of text.",
 				modifiers = "",
 			},
+			{
+				key = "n",
+				action = "CompleteNext",
+				description = "Completes the word with the words of the open buffers.",
+				modifiers = "Control",
+			},
+			{
+				key = "p",
+				action = "CompletePrev",
+				description = "Completes the word starting from the last match.",
+				modifiers = "Control",
+			},
 		},
 
 		command = {
@@ -273,6 +506,31 @@ return {
 				description = "Executes the entered command.",
 				modifiers = "",
 			},
+			{ key = "Left", action = "CmdlineLeft", description = "Moves the cursor left.", modifiers = "" },
+			{ key = "Right", action = "CmdlineRight", description = "Moves the cursor right.", modifiers = "" },
+			{ key = "Home", action = "CmdlineHome", description = "Moves the cursor to the start.", modifiers = "" },
+			{ key = "End", action = "CmdlineEnd", description = "Moves the cursor to the end.", modifiers = "" },
+			{ key = "Up", action = "HistoryPrev", description = "Older entry of the history.", modifiers = "" },
+			{ key = "Down", action = "HistoryNext", description = "Newer entry of the history.", modifiers = "" },
+			{ key = "Tab", action = "CmdlineComplete", description = "Completes the command or argument.", modifiers = "" },
+			{
+				key = "Back Tab",
+				action = "CmdlineComplete backward",
+				description = "Previous completion of the command or argument.",
+				modifiers = "Shift",
+			},
+			{
+				key = "w",
+				action = "CmdlineDeleteWord",
+				description = "Deletes the word before the cursor.",
+				modifiers = "Control",
+			},
+			{
+				key = "u",
+				action = "CmdlineDeleteToStart",
+				description = "Deletes everything before the cursor.",
+				modifiers = "Control",
+			},
 		},
 
 		search = {
@@ -289,6 +547,24 @@ return {
 				modifiers = "",
 			},
 			{ key = "Return", action = "EnterMode Normal", description = "Switches to Normal mode.", modifiers = "" },
+			{ key = "Left", action = "CmdlineLeft", description = "Moves the cursor left.", modifiers = "" },
+			{ key = "Right", action = "CmdlineRight", description = "Moves the cursor right.", modifiers = "" },
+			{ key = "Home", action = "CmdlineHome", description = "Moves the cursor to the start.", modifiers = "" },
+			{ key = "End", action = "CmdlineEnd", description = "Moves the cursor to the end.", modifiers = "" },
+			{ key = "Up", action = "HistoryPrev", description = "Older entry of the history.", modifiers = "" },
+			{ key = "Down", action = "HistoryNext", description = "Newer entry of the history.", modifiers = "" },
+			{
+				key = "w",
+				action = "CmdlineDeleteWord",
+				description = "Deletes the word before the cursor.",
+				modifiers = "Control",
+			},
+			{
+				key = "u",
+				action = "CmdlineDeleteToStart",
+				description = "Deletes everything before the cursor.",
+				modifiers = "Control",
+			},
 		},
 
 		file_explorer = {
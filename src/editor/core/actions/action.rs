@@ -1,4 +1,16 @@
-use crate::editor::{core::mode::Mode, CursorBlock};
+use crate::editor::{
+    core::{
+        ex::{Ex, Global},
+        file::FileWrite,
+        mode::Mode,
+        operator::Operator,
+        quickfix::{ListKind, ListMove},
+        substitute::Substitute,
+        user_command::UserCommand,
+    },
+    CursorBlock,
+};
+use crate::viewports::layout::Direction;
 
 #[derive(Debug, Clone)]
 pub struct OldCursorPosition {
@@ -90,6 +102,81 @@ pub enum Action {
     UndoRemoveCharAt(OldCursorPosition, char),
     AppendInsertMode,
     EnterInsertMode,
+    JumpMatchingBracket,
+    NextParagraph,
+    PrevParagraph,
+    NextSentence,
+    PrevSentence,
+    ScreenTop,
+    ScreenMiddle,
+    ScreenBottom,
+    HalfPageDown,
+    HalfPageUp,
+    ScrollLineDown,
+    ScrollLineUp,
+    OperatorPending(Operator),
+    ToggleComment(u16, u16), // first and last buffer line
+    ToggleBlockComment(u16, u16),
+    CommentVisual,
+    BlockCommentVisual,
+    UndoLines(OldCursorPosition, usize, Vec<String>, usize), // start y, old lines, new lines len
+    SurroundAdd((u16, u16), (u16, u16), String, String),     // start, end excluded, open, close
+    SurroundChange(char, String, String),
+    SurroundDelete(char),
+    CompleteNext,
+    CompletePrev,
+    AcceptCompletion,
+    CancelCompletion,
+    ExpandSnippet(String), // text added when there is no snippet to expand, empty for softtabstop
+    JumpSnippetStop(bool), // forward
+    AddCursorNextMatch,
+    AddCursorBelow,
+    AddCursorAbove,
+    CursorsFromVisual,
+    Substitute(Substitute),
+    SubstituteAnswer(char), // y n a q or l
+    StartSearch(bool),      // backward
+    IterPrevSearch,
+    ClearSearchHighlight,
+    CmdlineLeft,
+    CmdlineRight,
+    CmdlineHome,
+    CmdlineEnd,
+    CmdlineDeleteWord,
+    CmdlineDeleteToStart,
+    CmdlineComplete(bool), // backward
+    HistoryPrev,
+    HistoryNext,
+    Grep(String),
+    OpenResult,
+    SearchWordUnderCursor(bool), // backward
+    ListOpen(ListKind),
+    ListClose(ListKind),
+    ListGoto(ListKind, ListMove),
+    ListExpr(ListKind, String), // lua expression returning the entries
+    ListFile(ListKind, String), // compiler output
+    Ex(Ex),
+    Global(Global),
+    SetMark(char),
+    WriteFile(FileWrite),
+    SaveAll,
+    QuitAll(bool),               // :qa quits even with several windows, force
+    Edit(Option<String>, bool),  // force
+    Set(String, bool),           // the arguments of :set, :setlocal
+    Shell(String),               // :!cmd
+    Make(String),                // the arguments added to the make command
+    FilterSelection,             // visual ! starts a :'<,'>!
+    UserCommand(UserCommand),    // a command of config.lua
+    Split(bool, Option<String>), // vertical, the file shown in the new window
+    FocusWindow(Direction),
+    NextWindow,
+    CloseWindow,
+    OnlyWindow,
+    EqualizeWindows,
+    ResizeWindow(bool, i32), // vertical changes the width
+    ListBuffers,
+    GotoBuffer(String),         // the id or a part of the path
+    DeleteBuffer(String, bool), // the current one when it is empty, force
 }
 
 impl PartialEq for Action {
@@ -122,6 +209,49 @@ impl PartialEq for Action {
             (Self::RenameFileOrDirectory(l0), Self::RenameFileOrDirectory(r0)) => l0 == r0,
             (Self::AddModalChar(l0), Self::AddModalChar(r0)) => l0 == r0,
             (Self::HelpKeybinds(l0), Self::HelpKeybinds(r0)) => l0 == r0,
+            (Self::OperatorPending(l0), Self::OperatorPending(r0)) => l0 == r0,
+            (Self::ToggleComment(l0, l1), Self::ToggleComment(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::ToggleBlockComment(l0, l1), Self::ToggleBlockComment(r0, r1)) => {
+                l0 == r0 && l1 == r1
+            }
+            (Self::UndoLines(l0, l1, l2, l3), Self::UndoLines(r0, r1, r2, r3)) => {
+                l0 == r0 && l1 == r1 && l2 == r2 && l3 == r3
+            }
+            (Self::SurroundAdd(l0, l1, l2, l3), Self::SurroundAdd(r0, r1, r2, r3)) => {
+                l0 == r0 && l1 == r1 && l2 == r2 && l3 == r3
+            }
+            (Self::SurroundChange(l0, l1, l2), Self::SurroundChange(r0, r1, r2)) => {
+                l0 == r0 && l1 == r1 && l2 == r2
+            }
+            (Self::SurroundDelete(l0), Self::SurroundDelete(r0)) => l0 == r0,
+            (Self::ExpandSnippet(l0), Self::ExpandSnippet(r0)) => l0 == r0,
+            (Self::JumpSnippetStop(l0), Self::JumpSnippetStop(r0)) => l0 == r0,
+            (Self::Substitute(l0), Self::Substitute(r0)) => l0 == r0,
+            (Self::SubstituteAnswer(l0), Self::SubstituteAnswer(r0)) => l0 == r0,
+            (Self::StartSearch(l0), Self::StartSearch(r0)) => l0 == r0,
+            (Self::SearchWordUnderCursor(l0), Self::SearchWordUnderCursor(r0)) => l0 == r0,
+            (Self::Grep(l0), Self::Grep(r0)) => l0 == r0,
+            (Self::ListOpen(l0), Self::ListOpen(r0)) => l0 == r0,
+            (Self::ListClose(l0), Self::ListClose(r0)) => l0 == r0,
+            (Self::ListGoto(l0, l1), Self::ListGoto(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::ListExpr(l0, l1), Self::ListExpr(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::ListFile(l0, l1), Self::ListFile(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::Ex(l0), Self::Ex(r0)) => l0 == r0,
+            (Self::Global(l0), Self::Global(r0)) => l0 == r0,
+            (Self::SetMark(l0), Self::SetMark(r0)) => l0 == r0,
+            (Self::WriteFile(l0), Self::WriteFile(r0)) => l0 == r0,
+            (Self::Edit(l0, l1), Self::Edit(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::Set(l0, l1), Self::Set(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::Shell(l0), Self::Shell(r0)) => l0 == r0,
+            (Self::Make(l0), Self::Make(r0)) => l0 == r0,
+            (Self::UserCommand(l0), Self::UserCommand(r0)) => l0 == r0,
+            (Self::Split(l0, l1), Self::Split(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::FocusWindow(l0), Self::FocusWindow(r0)) => l0 == r0,
+            (Self::ResizeWindow(l0, l1), Self::ResizeWindow(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::GotoBuffer(l0), Self::GotoBuffer(r0)) => l0 == r0,
+            (Self::DeleteBuffer(l0, l1), Self::DeleteBuffer(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::QuitAll(l0), Self::QuitAll(r0)) => l0 == r0,
+            (Self::CmdlineComplete(l0), Self::CmdlineComplete(r0)) => l0 == r0,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
@@ -157,6 +287,7 @@ impl From<String> for Action {
             ["EndOfLine"] => Action::EndOfLine,
             ["StartOfLine"] => Action::StartOfLine,
             ["Save"] => Action::Save,
+            ["SaveAll"] => Action::SaveAll,
             ["CreateFileOrDirectory", path] => Action::CreateFileOrDirectory(path.to_string()),
             ["EndOfFile"] => Action::EndOfFile,
             ["StartOfFile"] => Action::StartOfFile,
@@ -182,6 +313,23 @@ impl From<String> for Action {
             ["FindSearchValue"] => Action::FindSearchValue,
             ["GotoPos"] => panic!("GotoPos requires a cursor position"),
             ["IterNextSearch"] => Action::IterNextSearch,
+            ["IterPrevSearch"] => Action::IterPrevSearch,
+            ["ClearSearchHighlight"] => Action::ClearSearchHighlight,
+            ["CmdlineLeft"] => Action::CmdlineLeft,
+            ["CmdlineRight"] => Action::CmdlineRight,
+            ["CmdlineHome"] => Action::CmdlineHome,
+            ["CmdlineEnd"] => Action::CmdlineEnd,
+            ["CmdlineDeleteWord"] => Action::CmdlineDeleteWord,
+            ["CmdlineDeleteToStart"] => Action::CmdlineDeleteToStart,
+            ["CmdlineComplete"] => Action::CmdlineComplete(false),
+            ["CmdlineComplete", "backward"] => Action::CmdlineComplete(true),
+            ["HistoryPrev"] => Action::HistoryPrev,
+            ["HistoryNext"] => Action::HistoryNext,
+            ["OpenResult"] => Action::OpenResult,
+            ["StartSearch"] => Action::StartSearch(false),
+            ["StartSearch", "backward"] => Action::StartSearch(true),
+            ["SearchWordUnderCursor"] => Action::SearchWordUnderCursor(false),
+            ["SearchWordUnderCursor", "backward"] => Action::SearchWordUnderCursor(true),
             ["GotoParentDirectory"] => Action::GotoParentDirectory,
             ["AddStr", s] => Action::AddStr(s.to_string()),
             ["RenameFileOrDirectory", name] => Action::RenameFileOrDirectory(name.to_string()),
@@ -201,6 +349,47 @@ impl From<String> for Action {
             ["PushEmptyViewport"] => Action::PushEmptyViewport,
             ["AppendInsertMode"] => Action::AppendInsertMode,
             ["EnterInsertMode"] => Action::EnterInsertMode,
+            ["JumpMatchingBracket"] => Action::JumpMatchingBracket,
+            ["NextParagraph"] => Action::NextParagraph,
+            ["PrevParagraph"] => Action::PrevParagraph,
+            ["NextSentence"] => Action::NextSentence,
+            ["PrevSentence"] => Action::PrevSentence,
+            ["ScreenTop"] => Action::ScreenTop,
+            ["ScreenMiddle"] => Action::ScreenMiddle,
+            ["ScreenBottom"] => Action::ScreenBottom,
+            ["HalfPageDown"] => Action::HalfPageDown,
+            ["HalfPageUp"] => Action::HalfPageUp,
+            ["ScrollLineDown"] => Action::ScrollLineDown,
+            ["ScrollLineUp"] => Action::ScrollLineUp,
+            ["OperatorPending", operator] => Action::OperatorPending(Operator::from(*operator)),
+            ["CommentVisual"] => Action::CommentVisual,
+            ["BlockCommentVisual"] => Action::BlockCommentVisual,
+            ["CompleteNext"] => Action::CompleteNext,
+            ["CompletePrev"] => Action::CompletePrev,
+            ["AcceptCompletion"] => Action::AcceptCompletion,
+            ["CancelCompletion"] => Action::CancelCompletion,
+            ["ExpandSnippet", fallback] => Action::ExpandSnippet(fallback.to_string()),
+            ["ExpandSnippet"] => Action::ExpandSnippet(String::new()),
+            ["AddCursorNextMatch"] => Action::AddCursorNextMatch,
+            ["AddCursorBelow"] => Action::AddCursorBelow,
+            ["AddCursorAbove"] => Action::AddCursorAbove,
+            ["CursorsFromVisual"] => Action::CursorsFromVisual,
+            ["FilterSelection"] => Action::FilterSelection,
+            ["Make", args @ ..] => Action::Make(args.join(" ")),
+            ["Set", args @ ..] if !args.is_empty() => Action::Set(args.join(" "), false),
+            ["Split"] => Action::Split(false, None),
+            ["Vsplit"] => Action::Split(true, None),
+            ["FocusWindow", direction] => Action::FocusWindow(Direction::from(*direction)),
+            ["NextWindow"] => Action::NextWindow,
+            ["CloseWindow"] => Action::CloseWindow,
+            ["OnlyWindow"] => Action::OnlyWindow,
+            ["EqualizeWindows"] => Action::EqualizeWindows,
+            ["ListBuffers"] => Action::ListBuffers,
+            ["ResizeWindow", side, delta] => match (*side, delta.parse::<i32>()) {
+                ("width", Ok(delta)) => Action::ResizeWindow(true, delta),
+                ("height", Ok(delta)) => Action::ResizeWindow(false, delta),
+                _ => panic!("Invalid Action string: {}", value),
+            },
             _ => panic!("Invalid Action string: {}", value),
         }
     }
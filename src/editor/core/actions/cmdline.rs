@@ -0,0 +1,38 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{core::mode::Mode, Editor};
+
+impl Action {
+    pub fn cmdline<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        // the completion menu is closed by any other action
+        if !matches!(self, Action::CmdlineComplete(_)) {
+            editor.wildmenu = None;
+        }
+        match self {
+            Action::CmdlineComplete(backward) => {
+                editor.cmdline_complete(*backward);
+                return Ok(());
+            }
+            Action::CmdlineLeft => editor.cmdline_move(-1),
+            Action::CmdlineRight => editor.cmdline_move(1),
+            Action::CmdlineHome => editor.cmdline_move(isize::MIN / 2),
+            Action::CmdlineEnd => editor.cmdline_move(isize::MAX / 2),
+            Action::CmdlineDeleteWord => editor.cmdline_delete_before(true),
+            Action::CmdlineDeleteToStart => editor.cmdline_delete_before(false),
+            Action::HistoryPrev => editor.cmdline_history(true),
+            Action::HistoryNext => editor.cmdline_history(false),
+            _ => return Ok(()),
+        }
+
+        // the search follows what is typed
+        let is_edit = !matches!(
+            self,
+            Action::CmdlineLeft | Action::CmdlineRight | Action::CmdlineHome | Action::CmdlineEnd
+        );
+        if is_edit && matches!(editor.mode, Mode::Search) {
+            editor.buffer_actions.push(Action::FindSearchValue);
+        }
+        Ok(())
+    }
+}
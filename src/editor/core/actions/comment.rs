@@ -0,0 +1,120 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{
+    core::{mode::Mode, operator::PendingOperator},
+    Editor,
+};
+
+impl Action {
+    pub fn comment<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::OperatorPending(operator) => {
+                editor.pending_operator = Some(PendingOperator::new(*operator));
+            }
+
+            Action::ToggleComment(start, end) | Action::ToggleBlockComment(start, end) => {
+                if !editor.is_viewport_modifiable() {
+                    return Ok(());
+                }
+                let viewport = editor.viewports.c_viewport();
+                let Some(tokens) = viewport
+                    .languages
+                    .comment_tokens(viewport.buffer.borrow().name())
+                else {
+                    editor
+                        .toast
+                        .error("no comment defined for this file".to_string());
+                    return Ok(());
+                };
+
+                let buffer = viewport.buffer.borrow();
+                let lines = match self {
+                    Action::ToggleComment(..) => buffer.toggle_line_comment(*start, *end, tokens),
+                    _ => buffer.toggle_block_comment(*start, *end, tokens),
+                };
+                drop(buffer);
+                match lines {
+                    Some(lines) => {
+                        let old_len = lines.len();
+                        editor.replace_buffer_lines(*start as usize, old_len, lines);
+                    }
+                    None => editor
+                        .toast
+                        .error("no block comment defined for this file".to_string()),
+                }
+            }
+
+            Action::CommentVisual | Action::BlockCommentVisual => {
+                if let Some(v_block) = editor.get_visual_block_pos() {
+                    let viewport = editor.viewports.c_viewport();
+                    let start = viewport.viewport_cursor(&v_block.start).1;
+                    let end = viewport.viewport_cursor(&v_block.end).1;
+                    editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
+                    editor.buffer_actions.push(match self {
+                        Action::CommentVisual => Action::ToggleComment(start, end),
+                        _ => Action::ToggleBlockComment(start, end),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_comment {
+    use super::*;
+    use crate::editor::{
+        core::operator::Operator,
+        test_helpers::{editor_with_path, lines},
+    };
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn test_gcc_then_undo() -> anyhow::Result<()> {
+        let mut editor = editor_with_path(&["fn main() {", "    a();", "    b();", "}"], "a.rs");
+        editor.cursor = (0, 1);
+
+        Action::OperatorPending(Operator::Comment).execute(&mut editor)?;
+        assert_eq!(
+            editor.handle_pending_operator(KeyCode::Char('j')),
+            Some(Action::ToggleComment(1, 2))
+        );
+        Action::ToggleComment(1, 2).execute(&mut editor)?;
+        assert_eq!(lines(&editor)[1..3], ["    // a();", "    // b();"]);
+
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(lines(&editor)[1..3], ["    a();", "    b();"]);
+        assert!(editor.undo_actions.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pending_operator_cancel() -> anyhow::Result<()> {
+        let mut editor = editor_with_path(&["a"], "a.py");
+        Action::OperatorPending(Operator::Comment).execute(&mut editor)?;
+        assert_eq!(editor.handle_pending_operator(KeyCode::Esc), None);
+        assert!(editor.pending_operator.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_without_tokens() -> anyhow::Result<()> {
+        let mut editor = editor_with_path(&["a"], "a.txt");
+        Action::ToggleComment(0, 0).execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["a"]);
+        assert!(editor.undo_actions.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_comment_css() -> anyhow::Result<()> {
+        let mut editor = editor_with_path(&["a {", "  color: red;", "}"], "a.css");
+        Action::ToggleBlockComment(0, 2).execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["/* a {", "  color: red;", "} */"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::Editor;
+
+impl Action {
+    pub fn completion<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::CompleteNext | Action::CompletePrev => {
+                let forward = matches!(self, Action::CompleteNext);
+                match editor.completion.as_mut() {
+                    Some(completion) if forward => completion.select_next(),
+                    Some(completion) => completion.select_prev(),
+                    None => {
+                        if editor.is_viewport_modifiable() {
+                            editor.open_completion(forward);
+                        }
+                    }
+                }
+            }
+
+            Action::AcceptCompletion => {
+                let Some(completion) = editor.completion.take() else {
+                    return Ok(());
+                };
+                let Some(word) = completion.selected() else {
+                    return Ok(());
+                };
+                let prefix_len = completion.prefix.chars().count();
+                // the word can match with another case so we fix the prefix before inserting
+                // the rest of the word
+                if !word.starts_with(&completion.prefix) {
+                    let (x, y) = completion.start;
+                    let mut line: Vec<char> = editor.viewports.c_viewport().buffer.borrow().lines
+                        [y as usize]
+                        .chars()
+                        .collect();
+                    line.splice(
+                        x as usize..x as usize + prefix_len,
+                        word.chars().take(prefix_len),
+                    );
+                    editor.replace_buffer_lines(y as usize, 1, vec![line.into_iter().collect()]);
+                }
+                let rest: String = word.chars().skip(prefix_len).collect();
+                if !rest.is_empty() {
+                    editor.buffer_actions.push(Action::AddStr(rest));
+                }
+            }
+
+            Action::CancelCompletion => editor.completion = None,
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -26,6 +26,7 @@ impl Action {
                         .viewports
                         .c_mut_viewport()
                         .buffer
+                        .borrow_mut()
                         .remove_char(v_cursor);
 
                     if let Some(char) = char {
@@ -43,7 +44,10 @@ impl Action {
                 }
             }
             Action::RemoveChar => {
-                if !editor.is_viewport_modifiable() {
+                if !editor.is_viewport_modifiable()
+                    || editor.clear_snippet_placeholder()
+                    || editor.autopair_backspace()
+                {
                     return Ok(());
                 }
                 let cursor_viewport = editor.v_cursor();
@@ -53,6 +57,7 @@ impl Action {
                         editor.cursor.0 -= 1;
                         current_viewport
                             .buffer
+                            .borrow_mut()
                             .remove_char((cursor_viewport.0 - 1, cursor_viewport.1));
                     }
                     false if cursor_viewport.1 > 0 => {
@@ -60,7 +65,10 @@ impl Action {
                         // because we want the text that will be added behind the cursor
                         let new_x_pos = current_viewport
                             .get_line_len_no_v_cursor(&(editor.cursor.0, editor.cursor.1 - 1));
-                        current_viewport.buffer.remove_char_line(cursor_viewport);
+                        current_viewport
+                            .buffer
+                            .borrow_mut()
+                            .remove_char_line(cursor_viewport);
                         editor.move_prev_line();
                         editor.cursor.0 = new_x_pos;
                     }
@@ -73,8 +81,8 @@ impl Action {
                 }
                 let (_, y) = editor.v_cursor();
                 let current_viewport = editor.viewports.c_mut_viewport();
-                let content = current_viewport.buffer.get(y as usize).clone();
-                current_viewport.buffer.remove(y as usize);
+                let content = current_viewport.buffer.borrow().get(y as usize).clone();
+                current_viewport.buffer.borrow_mut().remove(y as usize);
 
                 if let Some(text) = &content {
                     copy_to_clipboard(text);
@@ -95,27 +103,20 @@ impl Action {
                     .viewports
                     .c_mut_viewport()
                     .buffer
+                    .borrow_mut()
                     .remove_word(v_cursor)
             }
 
-            Action::RemoveCharFrom(is_search) => {
-                let content = match is_search {
-                    true => &mut editor.search,
-                    false => &mut editor.command,
-                };
-
-                match content.is_empty() {
-                    true => {
-                        editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
-                    }
-                    false => {
-                        content.pop();
-                        if *is_search {
-                            editor.buffer_actions.push(Action::FindSearchValue)
-                        }
+            Action::RemoveCharFrom(is_search) => match editor.cmdline_remove(*is_search) {
+                false => {
+                    editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
+                }
+                true => {
+                    if *is_search {
+                        editor.buffer_actions.push(Action::FindSearchValue)
                     }
                 }
-            }
+            },
 
             Action::DeleteBlock => {
                 if !editor.is_viewport_modifiable() {
@@ -128,6 +129,7 @@ impl Action {
 
                     if let Some(str) = c_mut_viewport
                         .buffer
+                        .borrow()
                         .get_block(v_cursor_start, v_cursor_end)
                     {
                         clipboard::copy_to_clipboard(&str);
@@ -135,6 +137,7 @@ impl Action {
 
                     let block_content: Vec<Option<String>> = c_mut_viewport
                         .buffer
+                        .borrow_mut()
                         .remove_block(v_cursor_start, v_cursor_end);
 
                     editor.cursor = v_block.start;
@@ -181,7 +184,7 @@ mod tests_deletion {
         let tmp_file = setup_temp_file();
         let path = tmp_file.path().to_str().unwrap().to_string();
         let mut editor = Editor::default();
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(path));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new(Some(path));
         editor
     }
 
@@ -191,7 +194,13 @@ mod tests_deletion {
         editor.cursor = (2, 0); // Set cursor to 'n' in "Line1"
         Action::RemoveCharAt.execute(&mut editor).unwrap();
 
-        let line = editor.viewports.c_viewport().buffer.get(0).unwrap();
+        let line = editor
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .get(0)
+            .unwrap();
         assert_eq!(line, "Lie1"); // 'n' should be removed
     }
 
@@ -200,7 +209,13 @@ mod tests_deletion {
         let mut editor = mock_file_editor();
         editor.cursor = (5, 0); // Cursor at the end of "Line1"
         Action::RemoveChar.execute(&mut editor).unwrap();
-        let line = editor.viewports.c_viewport().buffer.get(0).unwrap();
+        let line = editor
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .get(0)
+            .unwrap();
         assert_eq!(line, "Line"); // '1' should be removed
     }
 
@@ -209,7 +224,7 @@ mod tests_deletion {
         let mut editor = mock_file_editor();
         editor.cursor.1 = 1; // Delete "Line2"
         Action::DeleteLine.execute(&mut editor).unwrap();
-        let buffer = &editor.viewports.c_viewport().buffer.lines;
+        let buffer = &editor.viewports.c_viewport().buffer.borrow().lines;
         assert_eq!(buffer.len(), 2);
         assert_eq!(buffer[0], "Line1");
         assert_eq!(buffer[1], "Line3");
@@ -237,7 +252,7 @@ mod tests_deletion {
         editor.mode = Mode::Visual;
         editor.visual_cursor = Some((4, 1));
         Action::DeleteBlock.execute(&mut editor).unwrap();
-        let buffer = &editor.viewports.c_viewport().buffer.lines;
+        let buffer = &editor.viewports.c_viewport().buffer.borrow().lines;
         assert_eq!(buffer.len(), 1); // Only one line remains
         assert_eq!(buffer[0], "Line3"); // The selected block was deleted
     }
@@ -0,0 +1,42 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{core::mode::Mode, Editor};
+
+impl Action {
+    pub fn ex<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::Ex(command) => editor.run_ex(command),
+            Action::Global(global) => editor.run_global(global),
+            Action::SetMark(mark) => {
+                editor.set_mark(*mark);
+                Ok(())
+            }
+            Action::Set(args, local) => {
+                if let Err(err) = editor.set_options(args, *local) {
+                    editor.toast.error(err.to_string());
+                }
+                Ok(())
+            }
+            Action::Shell(cmd) => {
+                if let Err(err) = editor.run_shell(cmd) {
+                    editor.toast.error(err.to_string());
+                }
+                Ok(())
+            }
+            Action::UserCommand(command) => {
+                if let Err(err) = editor.run_user_command(command) {
+                    editor.toast.error(err.to_string());
+                }
+                Ok(())
+            }
+            // the command line starts with the range of the selection and the !
+            Action::FilterSelection => {
+                editor.buffer_actions.push(Action::AddCommandChar('!'));
+                editor.buffer_actions.push(Action::EnterMode(Mode::Command));
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{core::file::FileWrite, Editor};
+
+impl Action {
+    pub fn file<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        let result = match self {
+            Action::Save => editor.write_file(&FileWrite::default()),
+            Action::WriteFile(write) => editor.write_file(write),
+            Action::SaveAll => editor.save_all(),
+            Action::Edit(path, force) => editor.edit_file(path.as_deref(), *force),
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            editor.toast.error(err.to_string());
+        }
+        Ok(())
+    }
+}
@@ -8,12 +8,14 @@ impl Action {
     pub fn insertion<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
         match self {
             Action::AddStr(s) => {
-                let len = s.len();
+                editor.clear_snippet_placeholder();
+                let len = s.chars().count();
                 let cursor_viewport = editor.v_cursor();
                 editor
                     .viewports
                     .c_mut_viewport()
                     .buffer
+                    .borrow_mut()
                     .add_str(s.clone(), cursor_viewport);
                 editor.undo_insert_actions.push(Action::UndoStrAt(
                     OldCursorPosition::new(editor.cursor, editor.viewports.c_viewport().top),
@@ -23,6 +25,10 @@ impl Action {
                 editor.cursor.0 += len as u16;
             }
             Action::AddChar(c) => {
+                editor.clear_snippet_placeholder();
+                if editor.autopair_char(*c) {
+                    return Ok(());
+                }
                 let cursor_viewport = editor.v_cursor();
                 editor
                     .undo_insert_actions
@@ -35,11 +41,12 @@ impl Action {
                     .viewports
                     .c_mut_viewport()
                     .buffer
+                    .borrow_mut()
                     .add_char(*c, cursor_viewport);
                 editor.clear_buffer_x_cursor();
                 editor.cursor.0 += 1;
             }
-            Action::AddCommandChar(c) => editor.command.push(*c),
+            Action::AddCommandChar(c) => editor.cmdline_insert(false, *c),
             // the modal should have a push fn
             Action::AddModalChar(c) => {
                 if let Some(ref mut modal) = editor.modal {
@@ -48,7 +55,7 @@ impl Action {
             }
 
             Action::AddSearchChar(c) => {
-                editor.search.push(*c);
+                editor.cmdline_insert(true, *c);
                 editor.buffer_actions.push(Action::FindSearchValue)
             }
 
@@ -59,7 +66,7 @@ impl Action {
                 let v_cursor = editor.v_cursor();
                 let current_viewport = editor.viewports.c_mut_viewport();
 
-                editor.cursor.0 = current_viewport.buffer.new_line(v_cursor);
+                editor.cursor.0 = current_viewport.buffer.borrow_mut().new_line(v_cursor);
                 editor.buffer_actions.push(Action::EnterMode(Mode::Insert));
 
                 editor
@@ -77,7 +84,10 @@ impl Action {
                 let (v_x, v_y) = editor.v_cursor();
                 let current_viewport = editor.viewports.c_mut_viewport();
 
-                editor.cursor.0 = current_viewport.buffer.new_line((v_x, v_y + 1));
+                editor.cursor.0 = current_viewport
+                    .buffer
+                    .borrow_mut()
+                    .new_line((v_x, v_y + 1));
                 editor.move_next_line();
 
                 editor.buffer_actions.push(Action::EnterMode(Mode::Insert));
@@ -91,7 +101,7 @@ impl Action {
             }
 
             Action::NewLine => {
-                if !editor.is_viewport_modifiable() {
+                if !editor.is_viewport_modifiable() || editor.autopair_newline() {
                     return Ok(());
                 }
                 let (v_x, v_y) = editor.v_cursor();
@@ -99,6 +109,7 @@ impl Action {
                     .viewports
                     .c_mut_viewport()
                     .buffer
+                    .borrow_mut()
                     .new_line_with_text((v_x, v_y));
 
                 editor.cursor.0 = indentation;
@@ -148,7 +159,7 @@ mod tests_insertion {
         let tmp_file = setup_temp_file();
         let path = tmp_file.path().to_str().unwrap().to_string();
         let mut editor = Editor::default();
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(path));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new(Some(path));
         editor
     }
 
@@ -160,7 +171,13 @@ mod tests_insertion {
             .execute(&mut editor)
             .unwrap();
 
-        let line = editor.viewports.c_viewport().buffer.get(0).unwrap();
+        let line = editor
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .get(0)
+            .unwrap();
         assert_eq!(line, "HelloLine1");
     }
 
@@ -177,6 +194,7 @@ mod tests_insertion {
                 .viewports
                 .c_viewport()
                 .buffer
+                .borrow()
                 ._get_char(&initial_cursor),
             Some('X')
         );
@@ -1,28 +1,44 @@
 pub mod action;
+pub mod cmdline;
+pub mod comment;
+pub mod completion;
 pub mod deletion;
+pub mod ex;
+pub mod file;
 pub mod insertion;
 pub mod movement;
+pub mod multi_cursor;
+pub mod quickfix;
 pub mod search;
+pub mod snippet;
+pub mod substitute;
+pub mod surround;
 pub mod undo;
 pub mod viewport;
+pub mod window;
 pub mod yank_past;
 use std::fs::metadata;
 use std::io::Write;
+use std::path::PathBuf;
 
 use action::Action;
 use anyhow::Ok;
 use crossterm::{cursor, ExecutableCommand, QueueableCommand};
 
 use super::super::Editor;
+use super::autopair::InsertedClosers;
 use super::command::Command;
+use super::ex::ExError;
+use super::grep::{parse_result, spawn_grep};
+use super::history::HistoryKind;
+use super::job::parse_results;
 use super::mode::Mode;
-use crate::buff::Buffer;
+use crate::buff::{search_regex, Buffer};
 use crate::editor::ui::clear::ClearDraw;
 use crate::editor::ui::modal::{
     create::ModalCreateFD, delete::ModalDeleteFD, rename::ModalRenameFD,
 };
 use crate::editor::TERMINAL_SIZE_MINUS;
-use crate::log_message;
 use crate::viewport::Viewport;
 
 impl ClearDraw for Viewport {}
@@ -40,6 +56,13 @@ impl Action {
 
         // remove visual_cursor if we leave Visual Mode
         if matches!(editor.mode, Mode::Visual) && !matches!(mode, Mode::Visual) {
+            if let Some(v_block) = editor.get_visual_block_pos() {
+                let viewport = editor.viewports.c_viewport();
+                editor.visual_lines = Some((
+                    viewport.viewport_cursor(&v_block.start).1,
+                    viewport.viewport_cursor(&v_block.end).1,
+                ));
+            }
             editor.visual_cursor = None;
         }
         Ok(())
@@ -60,6 +83,9 @@ impl Action {
         // if we leave insert mode
         if matches!(editor.mode, Mode::Insert) && !matches!(mode, Mode::Insert) {
             editor.stdout.execute(cursor::SetCursorStyle::SteadyBlock)?;
+            editor.completion = None;
+            editor.snippet = None;
+            editor.closers = InsertedClosers::default();
             if !editor.undo_insert_actions.is_empty() {
                 let actions = std::mem::take(&mut editor.undo_insert_actions);
                 editor.undo_actions.push(Action::UndoMultiple(actions));
@@ -78,6 +104,19 @@ impl Action {
         if matches!(editor.mode, Mode::Command) && !matches!(mode, Mode::Command) {
             editor.command = String::new();
         }
+        // the command is about the lines of the selection
+        if matches!(editor.mode, Mode::Visual) && matches!(mode, Mode::Command) {
+            editor.command = String::from("'<,'>");
+        }
+        // the search is in the history once it is validated
+        if matches!(editor.mode, Mode::Search) && !matches!(mode, Mode::Search) {
+            editor.history.push(HistoryKind::Search, &editor.search);
+        }
+        // the cursor of the command line start at the end
+        if matches!(mode, Mode::Command | Mode::Search) && editor.mode != *mode {
+            editor.cmdline_back = 0;
+            editor.history.reset();
+        }
         Ok(())
     }
 
@@ -85,12 +124,32 @@ impl Action {
         // i could use a tree pattern like in movement i call delete in delete i call for find ...
         // but i prefer to call all of them in a single file
         self.movement(editor)?;
-        self.deletion(editor)?;
+        // the edits are made at every cursor when there is more than one
+        match editor.has_multiple_cursors() && self.is_multi_cursor_edit() {
+            true => editor.at_each_cursor(|editor| {
+                self.deletion(editor)?;
+                self.insertion(editor)
+            })?,
+            false => {
+                self.deletion(editor)?;
+                self.insertion(editor)?;
+            }
+        }
         self.search(editor)?;
-        self.insertion(editor)?;
         self.undo(editor)?;
         self.yank_past(editor)?;
         self.viewport(editor)?;
+        self.comment(editor)?;
+        self.surround(editor)?;
+        self.completion(editor)?;
+        self.snippet(editor)?;
+        self.multi_cursor(editor)?;
+        self.substitute(editor)?;
+        self.quickfix(editor)?;
+        self.ex(editor)?;
+        self.file(editor)?;
+        self.cmdline(editor)?;
+        self.window(editor)?;
 
         // other that dont really need a file for themselve
         match self {
@@ -131,19 +190,11 @@ impl Action {
                 editor.clear_buffer_x_cursor();
                 editor.buffer_actions.push(Action::EnterMode(Mode::Insert));
             }
-            Action::Save => {
-                if !editor.viewports.c_viewport().is_file_explorer() {
-                    let current_viewport = editor.viewports.c_mut_viewport();
-                    current_viewport.buffer.save()?;
-                    editor
-                        .toast
-                        .indication(format!("file: {} is saved", current_viewport.buffer.path));
-                }
-            }
             Action::CreateFileOrDirectory(filename) => {
                 let current_viewport = editor.viewports.c_mut_viewport();
                 let is_created = current_viewport
                     .buffer
+                    .borrow_mut()
                     .create_files_or_directories(filename)?;
                 editor.buffer_actions.push(Action::LeaveModal);
                 match is_created {
@@ -162,9 +213,13 @@ impl Action {
             Action::RenameFileOrDirectory(filename) => {
                 let y = editor.v_cursor().1 as usize;
                 let current_viewport = editor.viewports.c_mut_viewport();
-                if let Some(file) = current_viewport.buffer.lines.get_mut(y) {
-                    std::fs::rename(file.clone(), filename)?;
-                    *file = filename.clone();
+                let file = current_viewport.buffer.borrow().get(y);
+                if let Some(file) = file {
+                    std::fs::rename(file, filename)?;
+                    current_viewport
+                        .buffer
+                        .borrow_mut()
+                        .replace_lines(y..y + 1, vec![filename.clone()]);
                     editor
                         .toast
                         .indication(format!("successfull rename too {filename}"));
@@ -174,7 +229,8 @@ impl Action {
             Action::DeleteFileOrDirectory => {
                 let y = editor.v_cursor().1 as usize;
                 let current_viewport = editor.viewports.c_mut_viewport();
-                if let Some(path) = current_viewport.buffer.lines.get_mut(y) {
+                let path = current_viewport.buffer.borrow().get(y);
+                if let Some(path) = path {
                     match std::fs::metadata(&path) {
                         std::io::Result::Ok(meta) => {
                             match meta.is_file() {
@@ -186,7 +242,7 @@ impl Action {
                                 }
                             }
                             editor.toast.indication(format!("{path} has been deleted"));
-                            current_viewport.buffer.remove(y);
+                            current_viewport.buffer.borrow_mut().remove(y);
                         }
                         std::io::Result::Err(_) => {
                             editor.toast.error(format!("Couldnt Delete {path}"));
@@ -202,31 +258,47 @@ impl Action {
                 editor.waiting_command = Some(*c);
             }
             Action::ExecuteCommand => {
-                let cmd = editor.command.as_str();
-                if let Some(action) = Command::execute(cmd) {
-                    editor.buffer_actions.push(action);
+                editor.history.push(HistoryKind::Command, &editor.command);
+                let cmd = editor.command.trim();
+                if !cmd.is_empty() {
+                    match Command::execute(cmd, &editor.user_commands) {
+                        Result::Ok(action) => editor.buffer_actions.push(action),
+                        Result::Err(err) => editor.toast.error(err.to_string()),
+                    }
                 }
                 editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
             }
-            // if it enter this Action::Quit its because file arent save so we need to leave the
-            // Mode::Command
-            Action::Quit => {
-                editor.toast.error(format!(
-                    "file: {} is not saved",
-                    editor.viewports.c_viewport().buffer.path
-                ));
-                editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
-            }
+            // :q closes the window when the screen is split
+            Action::Quit | Action::ForceQuit if editor.window_count() > 1 => editor.close_window(),
+            Action::Quit | Action::QuitAll(false) => match editor.viewports.first_modified() {
+                None => editor.quit = true,
+                Some(id) => {
+                    let name = editor
+                        .viewports
+                        .buffer(id)
+                        .map(|b| b.borrow().name().to_string());
+                    let err = ExError::BufferNotSaved(id, name.unwrap_or_default());
+                    editor.toast.error(err.to_string());
+                }
+            },
+            Action::ForceQuit | Action::QuitAll(true) => editor.quit = true,
             Action::GotoParentDirectory => {
                 let current_viewport = editor.viewports.c_mut_viewport();
-                if let Some(parent_buffer) = current_viewport.buffer.parent_dir() {
+                let parent_buffer = current_viewport.buffer.borrow_mut().parent_dir();
+                if let Some(parent_buffer) = parent_buffer {
                     current_viewport.modifiable = true;
-                    current_viewport.buffer = parent_buffer;
+                    *current_viewport.buffer.borrow_mut() = parent_buffer;
                 }
             }
             Action::EnterFileOrDirectory => {
                 let (_, y) = editor.v_cursor();
-                if let Some(path) = editor.viewports.c_viewport().buffer.get(y as usize) {
+                let path = editor
+                    .viewports
+                    .c_viewport()
+                    .buffer
+                    .borrow()
+                    .get(y as usize);
+                if let Some(path) = path {
                     editor.reset_cursor();
                     // if this is a directory we only change the content of it to the new dir
                     // if its a file we swap to the viewport of file
@@ -237,33 +309,45 @@ impl Action {
                         true => {
                             let viewport = editor.viewports.c_mut_viewport();
                             viewport.modifiable = true;
-                            viewport.buffer = Buffer::new(Some(path));
+                            *viewport.buffer.borrow_mut() = Buffer::new(Some(path));
                         }
                         false => {
-                            let mut viewport_exists = false;
-                            for (i, v) in editor.viewports.values.iter().enumerate() {
-                                if v.buffer.path == path {
-                                    editor.viewports.index = i;
-                                    viewport_exists = true;
-                                }
-                            }
-
-                            if !viewport_exists {
-                                let mut viewport = Viewport::new(
-                                    Buffer::new(Some(path)),
-                                    editor.size.0,
-                                    editor.size.1 - TERMINAL_SIZE_MINUS,
-                                    0,
-                                    true,
-                                );
-                                viewport.buffer.set_query_language(&viewport.languages);
-                                editor.viewports.index = editor.viewports.push(viewport);
-                            }
+                            editor.open_file(path.clone());
                             editor.buffer_actions.push(Action::SwapViewportToExplorer);
                         }
                     }
                 }
             }
+            Action::Grep(pattern) => {
+                let regex = search_regex(pattern, editor.search_case());
+                let root = PathBuf::from(editor.viewports.explorer.buffer.borrow().name());
+                let receiver = spawn_grep(root, regex);
+                editor.start_results_job(
+                    format!("[grep] {pattern}"),
+                    receiver,
+                    Some(parse_results),
+                );
+            }
+            Action::Make(args) => editor.make(args),
+            // open the path:line:col: entry of a results list
+            Action::OpenResult => {
+                if editor.viewports.c_viewport().modifiable {
+                    return Ok(());
+                }
+                let y = editor.v_cursor().1 as usize;
+                if let Some(kind) = editor.list_kind() {
+                    editor.goto_list_entry(kind, None, y);
+                    return Ok(());
+                }
+                let entry = editor.viewports.c_viewport().buffer.borrow().get(y);
+                if let Some((path, pos)) = entry.as_deref().and_then(parse_result) {
+                    if metadata(&path).is_ok_and(|m| m.is_file()) {
+                        editor.open_file(path);
+                        editor.reset_cursor();
+                        editor.goto_buffer_pos(pos);
+                    }
+                }
+            }
             Action::SwapViewportToExplorer => {
                 let c_mut_viewport = editor.viewports.c_mut_viewport();
                 let vwidth = c_mut_viewport.vwidth;
@@ -291,17 +375,20 @@ impl Action {
                 editor.set_modal(Box::new(modal_input));
             }
             Action::RenameInputModal => {
-                let current_viewport = editor.viewports.c_viewport();
-                if let Some(line) = current_viewport.buffer.get(editor.v_cursor().1 as usize) {
+                let line = (editor.viewports.c_viewport().buffer.borrow())
+                    .get(editor.v_cursor().1 as usize);
+                if let Some(line) = line {
                     let modal_input =
                         ModalRenameFD::new(format!("Enter the name for {line}"), line.clone());
                     editor.set_modal(Box::new(modal_input));
                 }
             }
             Action::DeleteInputModal => {
-                let current_viewport = editor.viewports.c_viewport();
-                if let Some(line) = current_viewport.buffer.get(editor.v_cursor().1 as usize) {
-                    let line = line.replace(&current_viewport.buffer.path, "");
+                let buffer = editor.viewports.c_viewport().buffer.borrow();
+                let line = (buffer.get(editor.v_cursor().1 as usize))
+                    .map(|line| line.replace(buffer.name(), ""));
+                drop(buffer);
+                if let Some(line) = line {
                     let modal_input =
                         ModalDeleteFD::new(format!("Are you you wan to delete {line} Y/N"));
                     editor.set_modal(Box::new(modal_input));
@@ -349,7 +436,6 @@ mod tests_other_actions {
     use crate::editor::core::actions::action::Action;
     use crate::editor::core::mode::Mode;
     use crate::editor::Editor;
-    use crate::log_message;
 
     fn mock_editor() -> Editor<Cursor<Vec<u8>>> {
         Editor::default()
@@ -401,7 +487,8 @@ mod tests_other_actions {
     #[test]
     fn test_save_file() {
         let mut editor = mock_editor();
-        editor.viewports.c_mut_viewport().buffer.path = "test_file.txt".to_string();
+        editor.viewports.c_mut_viewport().buffer.borrow_mut().path =
+            Some("test_file.txt".to_string());
         File::create("test_file.txt").unwrap();
 
         Action::Save.execute(&mut editor).unwrap();
@@ -420,7 +507,8 @@ mod tests_other_actions {
     fn test_create_file_or_directory_success() {
         let mut editor = mock_editor();
         editor.viewports.is_explorer = true;
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(TMP_DIR.to_string()));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() =
+            Buffer::new(Some(TMP_DIR.to_string()));
         let filename = format!("{TMP_DIR}/test_folder");
 
         match Action::CreateFileOrDirectory("test_folder/".to_string()).execute(&mut editor) {
@@ -440,12 +528,13 @@ mod tests_other_actions {
         let mut editor = mock_editor();
         editor.viewports.is_explorer = true;
         File::create(format!("{TMP_DIR}/old_file.txt")).unwrap();
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(TMP_DIR.to_string()));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() =
+            Buffer::new(Some(TMP_DIR.to_string()));
         let filename = format!("{TMP_DIR}/new_file.txt");
         editor.cursor.1 = 1; // poiting the file to rename (old_file.txt)
         println!(
             "current_file {:?}",
-            editor.viewports.c_viewport().buffer.get(1)
+            editor.viewports.c_viewport().buffer.borrow().get(1)
         );
 
         match Action::RenameFileOrDirectory(filename.to_string()).execute(&mut editor) {
@@ -476,7 +565,7 @@ mod tests_other_actions {
     // fn test_delete_file_or_directory() {
     //     let mut editor = mock_editor();
     //     editor.viewports.set_current_to_file_explorer_viewport();
-    //     editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(TMP_DIR.to_string()));
+    //     *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new(Some(TMP_DIR.to_string()));
     //     let filename = format!("{TMP_DIR}/delete_me.txt");
     //     File::create(&filename).unwrap();
     //     editor.cursor.1 = 1; // poiting the file to rename (delete_me.txt)
@@ -510,11 +599,15 @@ mod tests_other_actions {
         let current_path = std::env::current_dir().unwrap();
         let parent_path = current_path.parent().unwrap().to_str().unwrap().to_string();
 
-        editor.viewports.c_mut_viewport().buffer.path = current_path.to_str().unwrap().to_string();
+        editor.viewports.c_mut_viewport().buffer.borrow_mut().path =
+            Some(current_path.to_str().unwrap().to_string());
 
         Action::GotoParentDirectory.execute(&mut editor).unwrap();
 
-        assert_eq!(editor.viewports.c_viewport().buffer.path, parent_path);
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            parent_path
+        );
     }
 
     // --- Command Execution Tests ---
@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::editor::{core::chartype::CharType, Editor, TERMINAL_LINE_LEN_MINUS};
+use crate::editor::{core::chartype::CharType, Editor};
 
 use super::action::Action;
 
@@ -63,15 +63,16 @@ impl Action {
 
             Action::MoveNext => {
                 editor.clear_buffer_x_cursor();
-                let current_viewport = editor.viewports.c_viewport();
+                let buffer = editor.viewports.c_viewport().buffer.clone();
                 let v_cursor = editor.v_cursor();
 
-                if let Some(line) = current_viewport.buffer.get(v_cursor.1 as usize) {
+                let line = buffer.borrow().get(v_cursor.1 as usize);
+                if let Some(line) = line {
                     let base_len = line.len().saturating_sub(1) as u16;
                     let line = line[v_cursor.0 as usize..].to_string();
                     if line.len() > 1 {
                         CharType::goto_diff_type(line, Some(base_len), &mut editor.cursor.0);
-                    } else if current_viewport.buffer.lines.len() - 1 > v_cursor.1 as usize {
+                    } else if buffer.borrow().lines.len() - 1 > v_cursor.1 as usize {
                         editor.cursor.0 = 0;
                         editor.move_next_line();
                     }
@@ -79,15 +80,15 @@ impl Action {
             }
             Action::MovePrev => {
                 editor.clear_buffer_x_cursor();
-                let current_viewport = editor.viewports.c_viewport();
+                let buffer = editor.viewports.c_viewport().buffer.clone();
                 let v_cursor = editor.v_cursor();
-                if let Some(line) = current_viewport.buffer.get(v_cursor.1 as usize) {
+                let line = buffer.borrow().get(v_cursor.1 as usize);
+                if let Some(line) = line {
+                    let prev_line = buffer.borrow().get((v_cursor.1 as usize).wrapping_sub(1));
                     if line.is_empty() && v_cursor.1 == 0 {
                         return Ok(());
                     } else if line.is_empty() && v_cursor.1 > 0 {
-                        if let Some(prev_line) =
-                            current_viewport.buffer.get(v_cursor.1 as usize - 1)
-                        {
+                        if let Some(prev_line) = prev_line {
                             editor.cursor.0 = prev_line.len().saturating_sub(1) as u16;
 
                             editor.move_prev_line();
@@ -97,9 +98,7 @@ impl Action {
                         if line.len() > 1 {
                             CharType::goto_diff_type(line, None, &mut editor.cursor.0);
                         } else if v_cursor.1 > 0 {
-                            if let Some(prev_line) =
-                                current_viewport.buffer.get(v_cursor.1 as usize - 1)
-                            {
+                            if let Some(prev_line) = prev_line {
                                 editor.cursor.0 = prev_line.len().saturating_sub(1) as u16;
                                 editor.move_prev_line();
                             }
@@ -108,6 +107,76 @@ impl Action {
                 }
             }
 
+            Action::JumpMatchingBracket => {
+                let v_cursor = editor.v_cursor();
+                let pos =
+                    (editor.viewports.c_viewport().buffer.borrow()).next_matching_bracket(v_cursor);
+                if let Some(pos) = pos {
+                    editor.clear_buffer_x_cursor();
+                    editor.goto_buffer_pos(pos);
+                }
+            }
+
+            Action::NextParagraph | Action::PrevParagraph => {
+                editor.clear_buffer_x_cursor();
+                let (_, y) = editor.v_cursor();
+                let buffer = editor.viewports.c_viewport().buffer.borrow();
+                let y = match self {
+                    Action::NextParagraph => buffer.next_paragraph(y),
+                    _ => buffer.prev_paragraph(y),
+                };
+                drop(buffer);
+                editor.goto_buffer_pos((0, y));
+            }
+
+            Action::NextSentence | Action::PrevSentence => {
+                editor.clear_buffer_x_cursor();
+                let v_cursor = editor.v_cursor();
+                let buffer = editor.viewports.c_viewport().buffer.borrow();
+                let pos = match self {
+                    Action::NextSentence => buffer.next_sentence(v_cursor),
+                    _ => buffer.prev_sentence(v_cursor),
+                };
+                drop(buffer);
+                editor.goto_buffer_pos(pos);
+            }
+
+            Action::ScreenTop | Action::ScreenMiddle | Action::ScreenBottom => {
+                let scrolloff = editor.c_scrolloff();
+                let c_viewport = editor.viewports.c_viewport();
+                editor.cursor.1 = match self {
+                    Action::ScreenTop => c_viewport.screen_top(scrolloff),
+                    Action::ScreenMiddle => c_viewport.screen_middle(),
+                    _ => c_viewport.screen_bottom(scrolloff),
+                };
+            }
+
+            Action::HalfPageDown => editor
+                .viewports
+                .c_mut_viewport()
+                .half_page_down(&mut editor.cursor),
+
+            Action::HalfPageUp => editor
+                .viewports
+                .c_mut_viewport()
+                .half_page_up(&mut editor.cursor),
+
+            Action::ScrollLineDown => {
+                let scrolloff = editor.c_scrolloff();
+                editor
+                    .viewports
+                    .c_mut_viewport()
+                    .scroll_line_down(&mut editor.cursor, scrolloff);
+            }
+
+            Action::ScrollLineUp => {
+                let scrolloff = editor.c_scrolloff();
+                editor
+                    .viewports
+                    .c_mut_viewport()
+                    .scroll_line_up(&mut editor.cursor, scrolloff);
+            }
+
             Action::GotoPos(new_cursor_pos) => {
                 let current_viewport = editor.viewports.c_mut_viewport();
                 if new_cursor_pos.1 as usize > current_viewport.get_buffer_len() {
@@ -128,7 +197,10 @@ mod tests_movement {
 
     use crate::{
         buff::Buffer,
-        editor::{core::actions::action::Action, Editor},
+        editor::{
+            core::{actions::action::Action, options::OptionValue},
+            Editor,
+        },
     };
     use std::io::{Cursor, Seek, Write};
 
@@ -156,7 +228,7 @@ mod tests_movement {
         let tmp_file = setup_temp_file();
         let path = tmp_file.path().to_str().unwrap().to_string();
         let mut editor = create_mock_editor();
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(path));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new(Some(path));
         editor
     }
 
@@ -273,6 +345,140 @@ mod tests_movement {
         assert!(editor.cursor == (0, 1), "cursor should be at 0, 2");
     }
 
+    #[test]
+    fn test_jump_matching_bracket() {
+        let mut editor = create_mock_editor();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new_tmp(
+            vec![
+                "if (a) {".to_string(),
+                "    b();".to_string(),
+                "}".to_string(),
+            ],
+            "test.txt".to_string(),
+        );
+
+        Action::JumpMatchingBracket.execute(&mut editor).unwrap();
+        assert_eq!(
+            editor.cursor,
+            (5, 0),
+            "should jump to the first ) of the line"
+        );
+
+        editor.cursor = (7, 0);
+        Action::JumpMatchingBracket.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor, (0, 2), "should jump to the closing brace");
+
+        Action::JumpMatchingBracket.execute(&mut editor).unwrap();
+        assert_eq!(
+            editor.cursor,
+            (7, 0),
+            "should jump back to the opening brace"
+        );
+    }
+
+    fn mock_long_file_editor() -> Editor<Cursor<Vec<u8>>> {
+        let mut editor = create_mock_editor();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() =
+            Buffer::new_tmp((0..100).map(|i| i.to_string()).collect(), "test.txt".into());
+        editor
+    }
+
+    #[test]
+    fn test_half_page() {
+        let mut editor = mock_long_file_editor();
+        let half = editor.viewports.c_viewport().max_vheight() / 2;
+
+        Action::HalfPageDown.execute(&mut editor).unwrap();
+        assert_eq!(editor.viewports.c_viewport().top, half);
+        assert_eq!(
+            editor.cursor.1, 0,
+            "cursor should stay on the same screen line"
+        );
+
+        Action::HalfPageUp.execute(&mut editor).unwrap();
+        assert_eq!(editor.viewports.c_viewport().top, 0);
+
+        Action::HalfPageUp.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor.1, 0);
+    }
+
+    #[test]
+    fn test_scroll_line() {
+        let mut editor = mock_long_file_editor();
+        editor.options.set("scrolloff", OptionValue::Number(0));
+        editor.cursor.1 = 5;
+
+        Action::ScrollLineDown.execute(&mut editor).unwrap();
+        assert_eq!(editor.viewports.c_viewport().top, 1);
+        assert_eq!(
+            editor.v_cursor().1,
+            5,
+            "cursor should stay on the same line"
+        );
+
+        Action::ScrollLineUp.execute(&mut editor).unwrap();
+        assert_eq!(editor.viewports.c_viewport().top, 0);
+        assert_eq!(editor.v_cursor().1, 5);
+    }
+
+    #[test]
+    fn test_screen_lines() {
+        let mut editor = mock_long_file_editor();
+        editor.options.set("scrolloff", OptionValue::Number(2));
+        editor.viewports.c_mut_viewport().top = 10;
+        let max_vheight = editor.viewports.c_viewport().max_vheight();
+
+        Action::ScreenTop.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor.1, 2);
+        Action::ScreenMiddle.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor.1, (max_vheight - 1) / 2);
+        Action::ScreenBottom.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor.1, max_vheight - 1 - 2);
+    }
+
+    #[test]
+    fn test_scrolloff() {
+        let mut editor = mock_long_file_editor();
+        editor.options.set("scrolloff", OptionValue::Number(3));
+        let max_vheight = editor.viewports.c_viewport().max_vheight();
+        editor.cursor.1 = max_vheight - 1 - 3;
+
+        Action::MoveDown.execute(&mut editor).unwrap();
+        assert_eq!(
+            editor.viewports.c_viewport().top,
+            1,
+            "should scroll before the bottom"
+        );
+        assert_eq!(editor.cursor.1, max_vheight - 1 - 3);
+
+        editor.cursor.1 = 3;
+        Action::MoveUp.execute(&mut editor).unwrap();
+        assert_eq!(
+            editor.viewports.c_viewport().top,
+            0,
+            "should scroll before the top"
+        );
+        assert_eq!(editor.cursor.1, 3);
+    }
+
+    #[test]
+    fn test_paragraph_and_sentence() {
+        let mut editor = create_mock_editor();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new_tmp(
+            vec!["One. Two.".into(), "".into(), "Three".into()],
+            "test.txt".into(),
+        );
+
+        Action::NextParagraph.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor, (0, 1));
+        Action::PrevParagraph.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor, (0, 0));
+        Action::NextSentence.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor, (5, 0));
+        Action::PrevSentence.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursor, (0, 0));
+    }
+
     #[test]
     fn test_move_next() {
         let mut editor = mock_file_editor();
@@ -0,0 +1,20 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{core::mode::Mode, Editor};
+
+impl Action {
+    pub fn multi_cursor<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::AddCursorNextMatch => editor.add_cursor_next_match(),
+            Action::AddCursorBelow => editor.add_cursor_vertical(true),
+            Action::AddCursorAbove => editor.add_cursor_vertical(false),
+            Action::CursorsFromVisual => {
+                editor.cursors_from_visual();
+                editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
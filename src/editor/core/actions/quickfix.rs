@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{
+    core::quickfix::{lua_entries, parse_compiler_output, ListKind, QuickfixList},
+    Editor,
+};
+
+impl<W: Write> Editor<W> {
+    fn fill_list(&mut self, kind: ListKind, list: QuickfixList) {
+        match list.entries.len() {
+            0 => self.toast.error(format!("{}: no entries", list.title)),
+            count => self
+                .toast
+                .indication(format!("{}: {count} entries", list.title)),
+        }
+        self.set_list(kind, list);
+    }
+}
+
+impl Action {
+    pub fn quickfix<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::ListOpen(kind) => editor.open_list(*kind),
+            Action::ListClose(kind) => editor.close_list(*kind),
+            Action::ListGoto(kind, list_move) => editor.goto_list_entry(*kind, Some(*list_move), 0),
+            // the entries returned by a lua expression
+            Action::ListExpr(kind, expression) => {
+                match lua_entries(&editor.user_commands.lua, expression) {
+                    Ok(entries) => {
+                        let list = QuickfixList::new(format!("expr {expression}"), entries);
+                        editor.fill_list(*kind, list);
+                    }
+                    Err(err) => editor.toast.error(err.to_string()),
+                }
+            }
+            // the errors in the output of a compiler saved in a file
+            Action::ListFile(kind, path) => match std::fs::read_to_string(path) {
+                Ok(output) => {
+                    let list = QuickfixList::new(path.clone(), parse_compiler_output(&output));
+                    editor.fill_list(*kind, list);
+                }
+                Err(err) => editor.toast.error(format!("{path}: {err}")),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+}
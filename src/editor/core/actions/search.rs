@@ -1,45 +1,107 @@
 use std::io::Write;
 
 use super::action::Action;
+use crate::buff::search_regex;
 use crate::editor::{core::mode::Mode, Editor};
 
+// go to the match after or before the cursor, with a toast when it wraps around the buffer
+fn goto_search_match<W: Write>(editor: &mut Editor<W>, forward: bool) {
+    let v_cursor = editor.v_cursor();
+    let case = editor.search_case();
+    let current_viewport = editor.viewports.c_mut_viewport();
+    // the matches are cleared by some commands but the last search is kept
+    if current_viewport.search_pos.is_empty() && !editor.search.is_empty() {
+        current_viewport.find_occurence(&search_regex(&editor.search, case));
+    }
+    let Some((index, wrapped)) = current_viewport.search_match(v_cursor, forward, false) else {
+        if !editor.search.is_empty() {
+            let error = format!("pattern not found: {}", editor.search);
+            editor.toast.error(error);
+        }
+        return;
+    };
+    current_viewport.search_index = index;
+    let (x, y, _) = current_viewport.search_pos[index];
+    editor.buffer_actions.push(Action::GotoPos((x, y)));
+    if wrapped {
+        editor.toast.indication(String::from(match forward {
+            true => "search hit BOTTOM, continuing at TOP",
+            false => "search hit TOP, continuing at BOTTOM",
+        }));
+    }
+}
+
+// put back the view where the search was started
+fn restore_search_view<W: Write>(editor: &mut Editor<W>) {
+    editor.viewports.c_mut_viewport().top = editor.search_top;
+    editor.goto_buffer_pos(editor.search_origin);
+}
+
 impl Action {
     pub fn search<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
         match self {
             // allow us to clear search string
             Action::ClearToNormalMode => {
+                // esc while typing the search goes back where it was started
+                if matches!(editor.mode, Mode::Search) {
+                    restore_search_view(editor);
+                }
                 let current_viewport = editor.viewports.c_mut_viewport();
                 current_viewport.clear_search();
                 editor.search = String::new();
+                editor.cursors.clear();
                 editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
             }
 
+            Action::StartSearch(backward) => {
+                editor.search_backward = *backward;
+                editor.search = String::new();
+                editor.search_origin = editor.v_cursor();
+                editor.search_top = editor.viewports.c_viewport().top;
+                editor.viewports.c_mut_viewport().clear_search();
+                editor.buffer_actions.push(Action::EnterMode(Mode::Search));
+            }
+
             // research correspondng value in file when editor.search got updated
             Action::FindSearchValue => {
+                let case = editor.search_case();
                 let current_viewport = editor.viewports.c_mut_viewport();
-                current_viewport.find_occurence(&editor.search);
+                match editor.search.is_empty() {
+                    true => current_viewport.clear_search(),
+                    false => current_viewport.find_occurence(&search_regex(&editor.search, case)),
+                }
 
-                if let Some(cursor) = current_viewport.search_pos.first() {
-                    let goto_cursor = (cursor.0, cursor.1);
-                    editor.buffer_actions.push(Action::GotoPos(goto_cursor))
+                // the match the nearest of where the search started
+                match current_viewport.search_match(
+                    editor.search_origin,
+                    !editor.search_backward,
+                    true,
+                ) {
+                    Some((index, _)) => {
+                        current_viewport.search_index = index;
+                        let (x, y, _) = current_viewport.search_pos[index];
+                        editor.buffer_actions.push(Action::GotoPos((x, y)))
+                    }
+                    None => restore_search_view(editor),
                 }
             }
 
-            Action::IterNextSearch => {
-                // iter through the list of search
-                let current_viewport = editor.viewports.c_mut_viewport();
-                match current_viewport.search_index < current_viewport.search_pos.len() {
-                    true => current_viewport.search_index += 1,
-                    false => current_viewport.search_index = 0,
-                }
+            // the matches are not highlighted anymore but n still find them
+            Action::ClearSearchHighlight => editor.viewports.c_mut_viewport().clear_search(),
 
-                if let Some(cursor) = current_viewport
-                    .search_pos
-                    .get(current_viewport.search_index)
-                {
-                    let goto_cursor = (cursor.0, cursor.1);
-                    editor.buffer_actions.push(Action::GotoPos(goto_cursor));
-                }
+            // n and N follow the direction of the search
+            Action::IterNextSearch => goto_search_match(editor, !editor.search_backward),
+            Action::IterPrevSearch => goto_search_match(editor, editor.search_backward),
+
+            Action::SearchWordUnderCursor(backward) => {
+                let Some((word, _)) = editor.word_under_cursor() else {
+                    editor.toast.error("no word under the cursor".to_string());
+                    return Ok(());
+                };
+                editor.search = format!("\\<{}\\>", regex::escape(&word));
+                editor.search_backward = *backward;
+                editor.viewports.c_mut_viewport().clear_search();
+                goto_search_match(editor, !backward);
             }
             _ => {}
         }
@@ -99,7 +161,7 @@ mod tests_search {
         let path = tmp_file.path().to_str().unwrap().to_string();
         let mut editor = create_mock_editor();
         let old_cursor = editor.cursor;
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(path));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new(Some(path));
 
         editor.search = "line".to_string();
         editor.mode = Mode::Search;
@@ -125,7 +187,7 @@ mod tests_search {
         let tmp_file = setup_temp_file();
         let path = tmp_file.path().to_str().unwrap().to_string();
         let mut editor = create_mock_editor();
-        editor.viewports.c_mut_viewport().buffer = Buffer::new(Some(path));
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new(Some(path));
 
         editor.search = "line".to_string();
         editor.mode = Mode::Search;
@@ -151,4 +213,82 @@ mod tests_search {
             "cursor should be different after iterating"
         );
     }
+
+    #[test]
+    fn test_backward_search_and_wrap() {
+        let mut editor = create_mock_editor();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new_tmp(
+            vec!["a x".to_string(), "x x".to_string(), "a".to_string()],
+            "a.txt".to_string(),
+        );
+        editor.cursor = (0, 1);
+        Action::StartSearch(true).execute(&mut editor).unwrap();
+        assert!(matches!(editor.mode, Mode::Search));
+        Action::AddSearchChar('x').execute(&mut editor).unwrap();
+        // the nearest match before the cursor
+        assert_eq!(editor.cursor, (0, 1));
+        Action::EnterMode(Mode::Normal)
+            .execute(&mut editor)
+            .unwrap();
+
+        Action::IterNextSearch.execute(&mut editor).unwrap();
+        assert_eq!(editor.v_cursor(), (2, 0));
+        assert!(editor.toast.is_empty());
+        // n keeps going backward and wraps to the bottom
+        Action::IterNextSearch.execute(&mut editor).unwrap();
+        assert_eq!(editor.v_cursor(), (2, 1));
+        assert!(!editor.toast.is_empty());
+        // N goes the other way
+        Action::IterPrevSearch.execute(&mut editor).unwrap();
+        assert_eq!(editor.v_cursor(), (2, 0));
+        assert_eq!(editor.viewports.c_viewport().search_index, 0);
+    }
+
+    #[test]
+    fn test_search_word_under_cursor() {
+        let mut editor = create_mock_editor();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new_tmp(
+            vec!["foo foobar".to_string(), "bar foo".to_string()],
+            "a.txt".to_string(),
+        );
+        editor.cursor = (1, 0);
+        Action::SearchWordUnderCursor(false)
+            .execute(&mut editor)
+            .unwrap();
+        assert_eq!(editor.search, "\\<foo\\>");
+        assert_eq!(editor.viewports.c_viewport().search_pos.len(), 2);
+        assert_eq!(editor.v_cursor(), (4, 1));
+
+        Action::SearchWordUnderCursor(true)
+            .execute(&mut editor)
+            .unwrap();
+        assert_eq!(editor.v_cursor(), (0, 0));
+    }
+
+    #[test]
+    fn test_incsearch_esc_and_nohlsearch() {
+        let mut editor = create_mock_editor();
+        let lines = (0..100).map(|i| format!("line {i}")).collect();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() =
+            Buffer::new_tmp(lines, "a.txt".to_string());
+        editor.cursor = (0, 2);
+        Action::StartSearch(false).execute(&mut editor).unwrap();
+        for c in "line 90".chars() {
+            Action::AddSearchChar(c).execute(&mut editor).unwrap();
+        }
+        assert_eq!(editor.v_cursor(), (0, 90));
+
+        // esc put back the view
+        Action::ClearToNormalMode.execute(&mut editor).unwrap();
+        assert_eq!(editor.v_cursor(), (0, 2));
+        assert_eq!(editor.viewports.c_viewport().top, 0);
+
+        // :noh clears the highlights but keeps the pattern
+        editor.search = "line 9".to_string();
+        Action::IterNextSearch.execute(&mut editor).unwrap();
+        Action::ClearSearchHighlight.execute(&mut editor).unwrap();
+        assert!(editor.viewports.c_viewport().search_pos.is_empty());
+        Action::IterNextSearch.execute(&mut editor).unwrap();
+        assert_eq!(editor.v_cursor(), (0, 90));
+    }
 }
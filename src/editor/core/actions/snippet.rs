@@ -0,0 +1,19 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::Editor;
+
+impl Action {
+    pub fn snippet<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::ExpandSnippet(fallback) if editor.is_viewport_modifiable() => {
+                editor.expand_snippet(fallback)
+            }
+            Action::JumpSnippetStop(forward) => editor.jump_snippet_stop(*forward),
+            // keep the mirrors of the snippet up to date after each edit
+            Action::AddChar(_) | Action::AddStr(_) | Action::RemoveChar => editor.sync_snippet(),
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::Editor;
+
+impl Action {
+    pub fn substitute<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::Substitute(substitute) => editor.start_substitution(substitute),
+            Action::SubstituteAnswer(answer) => editor.answer_substitution(*answer),
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::{core::mode::Mode, Editor};
+
+impl Action {
+    pub fn surround<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        let replacements = match self {
+            Action::SurroundAdd(start, end, open, close) => {
+                if editor.is_visual_mode() {
+                    editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
+                }
+                vec![(*start, *start, open.clone()), (*end, *end, close.clone())]
+            }
+
+            Action::SurroundChange(obj, _, _) | Action::SurroundDelete(obj) => {
+                let (open, close) = match self {
+                    Action::SurroundChange(_, open, close) => (open.clone(), close.clone()),
+                    _ => (String::new(), String::new()),
+                };
+                let v_cursor = editor.v_cursor();
+                let surrounding =
+                    (editor.viewports.c_viewport().buffer.borrow()).surrounding(v_cursor, *obj);
+                match surrounding {
+                    Some(((open_start, open_end), (close_start, close_end))) => vec![
+                        (open_start, open_end, open),
+                        (close_start, close_end, close),
+                    ],
+                    None => {
+                        editor.toast.error(format!("no surrounding {obj} found"));
+                        return Ok(());
+                    }
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        if !editor.is_viewport_modifiable() {
+            return Ok(());
+        }
+        let buffer = editor.viewports.c_viewport().buffer.clone();
+        let (range, lines) = buffer.borrow().replaced_lines(&replacements);
+        editor.replace_buffer_lines(range.start, range.len(), lines);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_surround {
+    use super::*;
+    use crate::editor::{
+        core::operator::Operator,
+        test_helpers::{editor_with_path, lines, TestEditor},
+    };
+    use crossterm::event::KeyCode;
+
+    fn type_keys(editor: &mut TestEditor, operator: Operator, keys: &str) {
+        Action::OperatorPending(operator).execute(editor).unwrap();
+        for c in keys.chars() {
+            if let Some(action) = editor.handle_pending_operator(KeyCode::Char(c)) {
+                action.execute(editor).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_surround_add_change_delete() {
+        let mut editor = editor_with_path(&["let a = hello;"], "index.html");
+        editor.cursor = (9, 0);
+
+        type_keys(&mut editor, Operator::SurroundAdd, "iw\"");
+        assert_eq!(lines(&editor), vec!["let a = \"hello\";"]);
+
+        type_keys(&mut editor, Operator::SurroundChange, "\"<span>");
+        assert_eq!(lines(&editor), vec!["let a = <span>hello</span>;"]);
+
+        type_keys(&mut editor, Operator::SurroundChange, "t]");
+        assert_eq!(lines(&editor), vec!["let a = [hello];"]);
+
+        type_keys(&mut editor, Operator::SurroundDelete, "[");
+        assert_eq!(lines(&editor), vec!["let a = hello;"]);
+
+        // each surround is a single undo
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["let a = [hello];"]);
+        assert_eq!(editor.undo_actions.len(), 3);
+    }
+
+    #[test]
+    fn test_surround_across_lines() {
+        let mut editor = editor_with_path(&["<div>", "  <p>hi</p>", "</div>"], "index.html");
+        editor.cursor = (5, 1);
+        type_keys(&mut editor, Operator::SurroundDelete, "t");
+        assert_eq!(lines(&editor), vec!["<div>", "  hi", "</div>"]);
+
+        type_keys(&mut editor, Operator::SurroundChange, "t<section>");
+        assert_eq!(lines(&editor), vec!["<section>", "  hi", "</section>"]);
+    }
+
+    #[test]
+    fn test_surround_not_found() {
+        let mut editor = editor_with_path(&["abc"], "index.html");
+        type_keys(&mut editor, Operator::SurroundDelete, "(");
+        assert_eq!(lines(&editor), vec!["abc"]);
+        assert!(editor.undo_actions.is_empty());
+    }
+}
@@ -21,24 +21,29 @@ impl Action {
                         .viewports
                         .c_mut_viewport()
                         .buffer
+                        .borrow_mut()
                         .remove_char(v_cursor);
                 }
             }
 
             Action::UndoStrAt(old_cursor, v_cursor, str_len) => {
-                if let Some(line) = editor
-                    .viewports
-                    .c_mut_viewport()
-                    .buffer
-                    .lines
-                    .get_mut(v_cursor.1 as usize)
-                {
-                    line.drain(v_cursor.0 as usize..v_cursor.0 as usize + *str_len);
+                let buffer = editor.viewports.c_viewport().buffer.clone();
+                let y = v_cursor.1 as usize;
+                let line = buffer.borrow().get(y);
+                if let Some(line) = line {
+                    // str_len is a count of chars like the columns
+                    let mut chars: Vec<char> = line.chars().collect();
+                    let start = (v_cursor.0 as usize).min(chars.len());
+                    chars.drain(start..(start + *str_len).min(chars.len()));
+                    let line = chars.into_iter().collect();
+                    buffer.borrow_mut().replace_lines(y..y + 1, vec![line]);
                     editor.cursor = old_cursor.cursor
                 };
             }
 
             Action::Undo => {
+                // the undo replay some edits which must only be made at the primary cursor
+                editor.cursors.clear();
                 if let Some(action) = editor.undo_actions.pop() {
                     action.execute(editor)?;
                 }
@@ -49,14 +54,12 @@ impl Action {
                 let current_viewport = editor.viewports.c_mut_viewport();
                 let buffer_len = current_viewport.get_buffer_len();
                 if cy as usize >= buffer_len {
-                    current_viewport.buffer.lines.push(content.clone());
                     editor.cursor.1 += 1;
-                } else {
-                    current_viewport
-                        .buffer
-                        .lines
-                        .insert(cy as usize, content.clone());
                 }
+                current_viewport
+                    .buffer
+                    .borrow_mut()
+                    .push_or_insert(content.clone(), cy as usize);
                 current_viewport.top = old_cursor.top;
                 editor.cursor.1 = old_cursor.cursor.1;
 
@@ -67,7 +70,7 @@ impl Action {
             Action::UndoNewLine(old_cursor) => {
                 let cy = old_cursor.cursor.1 + old_cursor.top;
                 let c_mut_viewport = editor.viewports.c_mut_viewport();
-                c_mut_viewport.buffer.remove(cy as usize);
+                c_mut_viewport.buffer.borrow_mut().remove(cy as usize);
                 c_mut_viewport.top = old_cursor.top;
                 editor.cursor.1 = old_cursor.cursor.1;
             }
@@ -75,17 +78,19 @@ impl Action {
             Action::UndoNewLineWithText(old_cursor, indentation) => {
                 let cy = old_cursor.cursor.1 + old_cursor.top;
                 let c_mut_viewport = editor.viewports.c_mut_viewport();
+                let mut buffer = c_mut_viewport.buffer.borrow_mut();
                 let mut buffer_line = String::new();
 
                 // get the y + 1 line to copy and remove it;
-                if let Some(line) = c_mut_viewport.buffer.lines.get_mut(cy as usize + 1) {
+                if let Some(line) = buffer.get(cy as usize + 1) {
                     buffer_line = line.replacen(" ", "", *indentation);
-                    c_mut_viewport.buffer.remove(cy as usize + 1);
+                    buffer.remove(cy as usize + 1);
                 }
                 // push the content of y + 1 in y
-                if let Some(line) = c_mut_viewport.buffer.lines.get_mut(cy as usize) {
-                    line.push_str(&buffer_line);
+                if let Some(line) = buffer.get(cy as usize) {
+                    buffer.insert_str(cy as usize, line.len(), &buffer_line);
                 }
+                drop(buffer);
 
                 c_mut_viewport.top = old_cursor.top;
                 editor.cursor.1 = old_cursor.cursor.1;
@@ -106,9 +111,11 @@ impl Action {
                 // no need to run a big iterator when the size = 1
                 if content.len() == 1 {
                     if let Some(line) = content.first().unwrap() {
-                        current_viewport
-                            .buffer
-                            .insert_str(y, start.cursor.0 as usize, line);
+                        current_viewport.buffer.borrow_mut().insert_str(
+                            y,
+                            start.cursor.0 as usize,
+                            line,
+                        );
                     }
                 } else {
                     for (i, c) in content.iter().enumerate() {
@@ -119,14 +126,21 @@ impl Action {
                             match i {
                                 _ if i == 0 && len > 0 && start.cursor.0 == len => current_viewport
                                     .buffer
+                                    .borrow_mut()
                                     .insert_str(y, start.cursor.0 as usize, line),
                                 _ if i == content.len() - 1 => match line.contains('\n') {
                                     true => current_viewport
                                         .buffer
+                                        .borrow_mut()
                                         .push_or_insert(line[0..line.len() - 1].to_string(), y),
-                                    false => current_viewport.buffer.insert_str(y, 0, line),
+                                    false => {
+                                        current_viewport.buffer.borrow_mut().insert_str(y, 0, line)
+                                    }
                                 },
-                                _ => current_viewport.buffer.push_or_insert(line.clone(), y),
+                                _ => current_viewport
+                                    .buffer
+                                    .borrow_mut()
+                                    .push_or_insert(line.clone(), y),
                             }
                         }
                         y += 1;
@@ -143,16 +157,27 @@ impl Action {
                 let end_y = cursor.end.1 + top;
                 current_viewport
                     .buffer
+                    .borrow_mut()
                     .remove_block((cursor.start.0, start_y), (cursor.end.0, end_y));
 
                 if !remove_past_line {
-                    current_viewport.buffer.new_line((0, start_y));
+                    current_viewport.buffer.borrow_mut().new_line((0, start_y));
                 }
                 current_viewport.top = *top;
                 editor.cursor.1 = cursor.start.1;
                 editor.buffer_actions.push(Action::CenterLine);
             }
 
+            Action::UndoLines(old_cursor, start, old_lines, new_len) => {
+                let c_mut_viewport = editor.viewports.c_mut_viewport();
+                c_mut_viewport
+                    .buffer
+                    .borrow_mut()
+                    .replace_lines(*start..*start + *new_len, old_lines.clone());
+                c_mut_viewport.top = old_cursor.top;
+                editor.cursor = old_cursor.cursor;
+            }
+
             _ => {}
         }
         Ok(())
@@ -12,23 +12,18 @@ impl Action {
         match self {
             Action::PrevViewport => editor.viewports.prev_viewport(),
             Action::NextViewport => editor.viewports.next_viewport(),
-            Action::DeleteViewport => {
-                editor.viewports.values.remove(editor.viewports.index);
-                let action = match editor.viewports.values.is_empty() {
-                    true => Action::PushEmptyViewport,
-                    false => Action::PrevViewport,
-                };
-                editor.buffer_actions.push(action);
+            Action::DeleteViewport => editor.close_viewport(),
+            Action::DeleteOtherViewport => editor.viewports.keep_current(),
+            Action::ListBuffers => editor.list_buffers(),
+            Action::GotoBuffer(arg) => {
+                if let Err(err) = editor.goto_buffer(arg) {
+                    editor.toast.error(err.to_string());
+                }
             }
-            Action::DeleteOtherViewport => {
-                let viewport_to_keep = editor
-                    .viewports
-                    .values
-                    .drain(..)
-                    .nth(editor.viewports.index)
-                    .unwrap();
-                editor.viewports.values = vec![viewport_to_keep];
-                editor.viewports.index = 0;
+            Action::DeleteBuffer(arg, force) => {
+                if let Err(err) = editor.delete_buffer(arg, *force) {
+                    editor.toast.error(err.to_string());
+                }
             }
             Action::PushEmptyViewport => {
                 editor.viewports.push(Viewport::new(
@@ -0,0 +1,27 @@
+use std::io::Write;
+
+use super::action::Action;
+use crate::editor::Editor;
+
+impl Action {
+    pub fn window<W: Write>(&self, editor: &mut Editor<W>) -> anyhow::Result<()> {
+        match self {
+            Action::Split(vertical, path) => {
+                editor.split_window(*vertical);
+                // :split file opens the file in the new window
+                if let Some(path) = path {
+                    editor.edit_file(Some(path), false)?;
+                    editor.layout_windows();
+                }
+            }
+            Action::FocusWindow(direction) => editor.focus_direction(*direction),
+            Action::NextWindow => editor.focus_next_window(),
+            Action::CloseWindow => editor.close_window(),
+            Action::OnlyWindow => editor.only_window(),
+            Action::EqualizeWindows => editor.equalize_windows(),
+            Action::ResizeWindow(vertical, delta) => editor.resize_window(*vertical, *delta),
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ impl Action {
             Action::YankLine => {
                 let current_viewport = editor.viewports.c_mut_viewport();
                 let (_, y) = current_viewport.viewport_cursor(&editor.cursor);
-                if let Some(str) = current_viewport.buffer.get(y as usize) {
+                if let Some(str) = current_viewport.buffer.borrow().get(y as usize) {
                     clipboard::copy_to_clipboard(&str);
                 }
                 editor.buffer_actions.push(Action::EnterMode(Mode::Normal));
@@ -22,7 +22,7 @@ impl Action {
             Action::YankBlock => {
                 if let Some(v_block) = editor.get_visual_block_pos() {
                     let c_mut_viewport = editor.viewports.c_mut_viewport();
-                    let to_copy = c_mut_viewport.buffer.get_block(
+                    let to_copy = c_mut_viewport.buffer.borrow().get_block(
                         c_mut_viewport.viewport_cursor(&v_block.start),
                         c_mut_viewport.viewport_cursor(&v_block.end),
                     );
@@ -39,12 +39,12 @@ impl Action {
                     let v_cursor = &current_viewport.viewport_cursor(&editor.cursor);
                     let mut end_x: usize = 0;
                     let mut start_x: usize = 0;
-                    let mut y: usize = v_cursor.1 as usize;
                     let mut end_y: u16 = 0; // we can know where the past end
                     let mut start_y: usize = 0;
                     let mut remove_past_line = true;
 
                     for (i, line) in content.iter().enumerate() {
+                        let y: usize = v_cursor.1 as usize + i;
                         match i {
                             _ if i == 0 => {
                                 let line_len =
@@ -71,10 +71,11 @@ impl Action {
                                         start_x = 0;
                                         current_viewport
                                             .buffer
+                                            .borrow_mut()
                                             .push_or_insert(line.clone(), y + start_y)
                                     }
                                     false => {
-                                        current_viewport.buffer.insert_str(
+                                        current_viewport.buffer.borrow_mut().insert_str(
                                             v_cursor.1 as usize,
                                             start_x,
                                             line,
@@ -88,10 +89,10 @@ impl Action {
                                 }
                                 current_viewport
                                     .buffer
+                                    .borrow_mut()
                                     .push_or_insert(line.clone(), y + start_y)
                             }
                         }
-                        y += 1;
                         end_y += 1;
                     }
 
@@ -0,0 +1,322 @@
+use std::io::Write;
+
+use crate::editor::{core::mode::Mode, Editor};
+
+use super::actions::action::{Action, OldCursorPosition};
+
+// the closing chars inserted with their open one, only them are skipped when they are typed
+// and only while the cursor has moved by typing since they were inserted
+#[derive(Debug, Default)]
+pub struct InsertedClosers {
+    positions: Vec<(u16, u16)>,
+    cursor: (u16, u16), // where the last typed char left the cursor
+}
+
+impl InsertedClosers {
+    // the closers are forgotten when the cursor moved another way than by typing
+    fn check_cursor(&mut self, cursor: (u16, u16)) {
+        if self.cursor != cursor {
+            self.positions.clear();
+        }
+    }
+
+    // the closers after the chars added or removed at pos move with them
+    fn shift(&mut self, (x, y): (u16, u16), offset: isize) {
+        for pos in self
+            .positions
+            .iter_mut()
+            .filter(|pos| pos.1 == y && pos.0 >= x)
+        {
+            pos.0 = pos.0.saturating_add_signed(offset as i16);
+        }
+    }
+
+    fn remove(&mut self, pos: (u16, u16)) -> bool {
+        let len = self.positions.len();
+        self.positions.retain(|p| *p != pos);
+        self.positions.len() != len
+    }
+}
+
+impl<W: Write> Editor<W> {
+    fn c_autopairs(&self) -> Vec<(char, char)> {
+        let viewport = self.viewports.c_viewport();
+        viewport
+            .languages
+            .autopairs(viewport.buffer.borrow().name())
+    }
+
+    // the chars before and under the cursor
+    fn chars_around_cursor(&self) -> (Option<char>, Option<char>) {
+        let (x, y) = self.v_cursor();
+        let buffer = &self.viewports.c_viewport().buffer.borrow();
+        let prev = x.checked_sub(1).and_then(|x| buffer._get_char(&(x, y)));
+        (prev, buffer._get_char(&(x, y)))
+    }
+
+    // the cursor is between an open char and its closing one like (|)
+    fn is_in_empty_pair(&self) -> Option<(char, char)> {
+        match self.chars_around_cursor() {
+            (Some(open), Some(close)) => self
+                .c_autopairs()
+                .into_iter()
+                .find(|pair| *pair == (open, close)),
+            _ => None,
+        }
+    }
+
+    // insert the closing char with the open one or skip the closing char under the cursor when
+    // it was inserted that way, return true if the char has been handled
+    pub fn autopair_char(&mut self, c: char) -> bool {
+        if !matches!(self.mode, Mode::Insert) {
+            return false;
+        }
+        let (x, y) = self.v_cursor();
+        self.closers.check_cursor((x, y));
+        let handled = match self.chars_around_cursor().1 == Some(c) && self.closers.remove((x, y)) {
+            true => {
+                self.cursor.0 += 1;
+                true
+            }
+            false => self.insert_pair(c),
+        };
+        // the char is added at the cursor when it isnt handled
+        if !handled {
+            self.closers.shift((x, y), 1);
+        }
+        self.closers.cursor = (x + 1, y);
+        handled
+    }
+
+    fn insert_pair(&mut self, c: char) -> bool {
+        let pairs = self.c_autopairs();
+        let (prev, _) = self.chars_around_cursor();
+        let Some(&(open, close)) = pairs.iter().find(|(open, _)| *open == c) else {
+            return false;
+        };
+        // a quote after a word is an apostrophe like in don't
+        if open == close && prev.is_some_and(|prev| prev.is_alphanumeric()) {
+            return false;
+        }
+        let v_cursor = self.v_cursor();
+        if self
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .is_in_string_or_comment(v_cursor)
+        {
+            return false;
+        }
+
+        let pair = format!("{open}{close}");
+        let old_cursor = OldCursorPosition::new(self.cursor, self.viewports.c_viewport().top);
+        self.undo_insert_actions
+            .push(Action::UndoStrAt(old_cursor, v_cursor, 2));
+        self.viewports
+            .c_mut_viewport()
+            .buffer
+            .borrow_mut()
+            .add_str(pair, v_cursor);
+        self.closers.shift(v_cursor, 2);
+        self.closers.positions.push((v_cursor.0 + 1, v_cursor.1));
+        self.clear_buffer_x_cursor();
+        self.cursor.0 += 1;
+        true
+    }
+
+    // remove both chars of an empty pair
+    pub fn autopair_backspace(&mut self) -> bool {
+        if !matches!(self.mode, Mode::Insert) {
+            return false;
+        }
+        let (x, y) = self.v_cursor();
+        self.closers.check_cursor((x, y));
+        self.closers.cursor = (x.saturating_sub(1), y);
+        if self.is_in_empty_pair().is_none() {
+            // the char before the cursor is removed, or the line is joined to the previous one
+            match x > 0 {
+                true => self.closers.shift((x, y), -1),
+                false => self.closers.positions.clear(),
+            }
+            return false;
+        }
+        self.closers.remove((x, y));
+        self.closers.shift((x, y), -2);
+        let mut line: Vec<char> = self.viewports.c_viewport().buffer.borrow().lines[y as usize]
+            .chars()
+            .collect();
+        line.drain(x as usize - 1..=x as usize);
+        self.replace_buffer_lines(y as usize, 1, vec![line.into_iter().collect()]);
+        self.cursor.0 -= 1;
+        true
+    }
+
+    // open an indented block when we press enter between brackets like {|}
+    pub fn autopair_newline(&mut self) -> bool {
+        if !matches!(self.mode, Mode::Insert) {
+            return false;
+        }
+        match self.is_in_empty_pair() {
+            Some((open, close)) if open != close => (),
+            _ => return false,
+        }
+
+        let (x, y) = self.v_cursor();
+        let old_cursor = OldCursorPosition::new(self.cursor, self.viewports.c_viewport().top);
+        let shiftwidth = self.option_number("shiftwidth");
+        let mut buffer = self.viewports.c_mut_viewport().buffer.borrow_mut();
+        let old_line = buffer.lines[y as usize].clone();
+        let indentation = old_line.len() - old_line.trim_start().len();
+
+        buffer.new_line_with_text((x, y));
+        // the closing char go back to the indentation of the line and we add a line between
+        let closing_line = buffer.lines[y as usize + 1].trim_start().to_string();
+        let closing_line = format!("{}{closing_line}", " ".repeat(indentation));
+        buffer.replace_lines(y as usize + 1..y as usize + 2, vec![closing_line]);
+        buffer.push_or_insert(" ".repeat(indentation + shiftwidth), y as usize + 1);
+        drop(buffer);
+
+        self.undo_insert_actions
+            .push(Action::UndoLines(old_cursor, y as usize, vec![old_line], 3));
+        self.move_next_line();
+        self.cursor.0 = (indentation + shiftwidth) as u16;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests_autopair {
+    use std::io::Cursor;
+
+    use tree_sitter::{Language, Query};
+
+    use super::*;
+    use crate::buff::Buffer;
+
+    fn insert_editor(lines: Vec<&str>, path: &str) -> Editor<Cursor<Vec<u8>>> {
+        let mut editor = Editor::default();
+        let viewport = editor.viewports.c_mut_viewport();
+        *viewport.buffer.borrow_mut() = Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            path.to_string(),
+        );
+        if path.ends_with(".rs") {
+            let language: Language = tree_sitter_rust::LANGUAGE.into();
+            viewport.buffer.borrow_mut().query_language = Some((
+                Query::new(&language, tree_sitter_rust::HIGHLIGHTS_QUERY).unwrap(),
+                language,
+            ));
+        }
+        editor.mode = Mode::Insert;
+        editor
+    }
+
+    fn line(editor: &Editor<Cursor<Vec<u8>>>, y: usize) -> String {
+        editor.viewports.c_viewport().buffer.borrow().lines[y].clone()
+    }
+
+    #[test]
+    fn test_autopair_open_and_skip_close() -> anyhow::Result<()> {
+        let mut editor = insert_editor(vec![""], "a.txt");
+        Action::AddChar('(').execute(&mut editor)?;
+        Action::AddChar('a').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "(a)");
+        Action::AddChar(')').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "(a)");
+        assert_eq!(editor.cursor.0, 3);
+
+        // an apostrophe isnt paired
+        Action::AddChar('n').execute(&mut editor)?;
+        Action::AddChar('\'').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "(a)n'");
+        Ok(())
+    }
+
+    #[test]
+    fn test_autopair_skip_only_inserted_closers() -> anyhow::Result<()> {
+        let mut editor = insert_editor(vec![")"], "a.txt");
+        Action::AddChar(')').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "))");
+
+        // the inserted closers follow the chars typed and removed before them
+        let mut editor = insert_editor(vec![""], "a.txt");
+        for c in "((ab".chars() {
+            Action::AddChar(c).execute(&mut editor)?;
+        }
+        Action::RemoveChar.execute(&mut editor)?;
+        Action::AddChar(')').execute(&mut editor)?;
+        Action::AddChar(')').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "((a))");
+        assert_eq!(editor.cursor.0, 5);
+
+        // and are forgotten when the cursor moves
+        let mut editor = insert_editor(vec![""], "a.txt");
+        Action::AddChar('[').execute(&mut editor)?;
+        Action::MoveLeft.execute(&mut editor)?;
+        Action::AddChar('x').execute(&mut editor)?;
+        Action::MoveRight.execute(&mut editor)?;
+        Action::AddChar(']').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "x[]]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_autopair_backspace_and_undo() -> anyhow::Result<()> {
+        let mut editor = insert_editor(vec!["x"], "a.txt");
+        Action::AddChar('[').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "[]x");
+        Action::RemoveChar.execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "x");
+
+        Action::AddChar('{').execute(&mut editor)?;
+        Action::EnterMode(Mode::Normal).execute(&mut editor)?;
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "x");
+        Ok(())
+    }
+
+    #[test]
+    fn test_autopair_after_non_ascii() -> anyhow::Result<()> {
+        let mut editor = insert_editor(vec!["é"], "a.txt");
+        editor.cursor.0 = 1;
+        Action::AddChar('(').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "é()");
+        assert_eq!(editor.cursor.0, 2);
+        Action::AddChar('«').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "é(«)");
+
+        Action::EnterMode(Mode::Normal).execute(&mut editor)?;
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "é");
+        Ok(())
+    }
+
+    #[test]
+    fn test_autopair_newline_between_braces() -> anyhow::Result<()> {
+        let mut editor = insert_editor(vec!["  fn a() {}"], "a.rs");
+        editor.cursor.0 = 10;
+        Action::NewLine.execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "  fn a() {");
+        assert_eq!(line(&editor, 1), "      ");
+        assert_eq!(line(&editor, 2), "  }");
+        assert_eq!(editor.cursor, (6, 1));
+
+        Action::EnterMode(Mode::Normal).execute(&mut editor)?;
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().lines,
+            vec!["  fn a() {}"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_autopair_disabled_in_strings() -> anyhow::Result<()> {
+        let mut editor = insert_editor(vec!["let s = \"ab\";"], "a.rs");
+        editor.cursor.0 = 10;
+        Action::AddChar('(').execute(&mut editor)?;
+        assert_eq!(line(&editor, 0), "let s = \"a(b\";");
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use std::io::Write;
+
+use crate::editor::Editor;
+
+use super::ex::ExError;
+
+impl<W: Write> Editor<W> {
+    // the id of :b N or :b name, a part of the path is enough when it matches only one buffer
+    fn buffer_arg(&self, arg: &str) -> Result<usize, ExError> {
+        let viewports = &self.viewports;
+        if let Ok(id) = arg.parse::<usize>() {
+            return viewports
+                .buffer(id)
+                .map(|_| id)
+                .ok_or(ExError::NoSuchBuffer(id));
+        }
+        if let Some(id) = viewports.find_buffer(arg) {
+            return Ok(id);
+        }
+        let matches: Vec<usize> = viewports
+            .buffers
+            .iter()
+            .filter(|(_, buffer)| buffer.borrow().name().contains(arg))
+            .map(|(id, _)| *id)
+            .collect();
+        match matches.as_slice() {
+            [id] => Ok(*id),
+            [] => Err(ExError::NoMatchingBuffer(arg.to_string())),
+            _ => Err(ExError::MoreThanOneMatch(arg.to_string())),
+        }
+    }
+
+    // :ls, % is the current buffer, a the ones shown in a window, h the hidden ones and + the
+    // modified ones
+    pub fn list_buffers(&mut self) {
+        let viewports = &self.viewports;
+        let current = viewports.current_id().filter(|_| !viewports.is_explorer);
+        let mut list = vec![];
+        for (id, buffer) in &viewports.buffers {
+            let views = viewports.views(*id);
+            let flags: String = [
+                (current == Some(*id), '%'),
+                (views.iter().any(|index| viewports.refs(*index) > 0), 'a'),
+                (views.is_empty(), 'h'),
+                (viewports.is_modified(*id), '+'),
+            ]
+            .into_iter()
+            .filter_map(|(set, flag)| set.then_some(flag))
+            .collect();
+            let name = buffer.borrow().name().to_string();
+            list.push(match flags.is_empty() {
+                true => format!("{id} \"{name}\""),
+                false => format!("{id} {flags} \"{name}\""),
+            });
+        }
+        self.toast.indication(list.join(" | "));
+    }
+
+    pub fn goto_buffer(&mut self, arg: &str) -> Result<(), ExError> {
+        if arg.is_empty() {
+            return Ok(());
+        }
+        let id = self.buffer_arg(arg)?;
+        if Some(id) != self.viewports.current_id() || self.viewports.is_explorer {
+            self.viewports.open(id);
+            self.reset_cursor();
+        }
+        Ok(())
+    }
+
+    // :bdelete removes the buffer from the list, the windows showing it show the current one
+    pub fn delete_buffer(&mut self, arg: &str, force: bool) -> Result<(), ExError> {
+        let id = match arg.is_empty() {
+            true => self.viewports.current_id().unwrap_or_default(),
+            false => self.buffer_arg(arg)?,
+        };
+        if !force && self.viewports.is_modified(id) {
+            return Err(ExError::BufferModified(id));
+        }
+        let is_current = self.viewports.current_id() == Some(id);
+        self.viewports.remove_buffer(id, true);
+        if is_current {
+            self.reset_cursor();
+        }
+        Ok(())
+    }
+
+    // a modified buffer is hidden instead of being lost, :ls and :b bring it back
+    pub fn close_viewport(&mut self) {
+        let Some(id) = self.viewports.current_id() else {
+            return;
+        };
+        let index = self.viewports.index;
+        let modified = self.viewports.is_modified(id);
+        self.viewports.remove_buffer(id, !modified);
+        if !modified && self.viewports.index == index {
+            self.viewports.prev_viewport();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_buffer_list {
+    use std::io::Cursor;
+
+    use crate::{
+        buff::Buffer,
+        editor::core::{actions::action::Action, command::Command, user_command::UserCommands},
+        viewport::Viewport,
+    };
+
+    use super::*;
+
+    // b.txt is modified and c.txt isnt
+    fn editor() -> Editor<Cursor<Vec<u8>>> {
+        let mut editor = Editor::default();
+        for (path, line) in [("b.txt", "b"), ("c.txt", "")] {
+            let mut buffer = Buffer::new_tmp(vec![String::new()], path.to_string());
+            if !line.is_empty() {
+                buffer.insert_str(0, 0, line);
+            }
+            let viewport = Viewport::new(buffer, 80, 20, 0, true);
+            editor.viewports.push(viewport);
+        }
+        editor
+    }
+
+    fn run(editor: &mut Editor<Cursor<Vec<u8>>>, action: Action) {
+        action.execute(editor).unwrap();
+    }
+
+    #[test]
+    fn test_list_and_goto_buffers() {
+        let mut editor = editor();
+        assert_eq!(
+            Command::execute("b3", &UserCommands::default()),
+            Ok(Action::GotoBuffer("3".to_string()))
+        );
+        assert_eq!(
+            Command::execute("bd! c", &UserCommands::default()),
+            Ok(Action::DeleteBuffer("c".to_string(), true))
+        );
+
+        run(&mut editor, Action::GotoBuffer("2".to_string()));
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            "b.txt"
+        );
+        run(&mut editor, Action::ListBuffers);
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("1 \"[No Name]\" | 2 %a+ \"b.txt\" | 3 \"c.txt\"")
+        );
+
+        run(&mut editor, Action::GotoBuffer("c.t".to_string()));
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            "c.txt"
+        );
+        run(&mut editor, Action::GotoBuffer(".txt".to_string()));
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E93: More than one match for .txt")
+        );
+        run(&mut editor, Action::GotoBuffer("7".to_string()));
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E86: Buffer 7 does not exist")
+        );
+    }
+
+    #[test]
+    fn test_modified_buffers_stay_hidden() {
+        let mut editor = editor();
+        run(&mut editor, Action::GotoBuffer("2".to_string()));
+        run(&mut editor, Action::DeleteViewport);
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            "c.txt"
+        );
+        run(&mut editor, Action::ListBuffers);
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("1 \"[No Name]\" | 2 h+ \"b.txt\" | 3 %a \"c.txt\"")
+        );
+        // :q names the hidden buffer which isnt saved, not the current one
+        run(&mut editor, Action::Quit);
+        assert!(!editor.quit);
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E162: No write since last change for buffer 2 \"b.txt\"")
+        );
+
+        run(&mut editor, Action::DeleteBuffer("2".to_string(), false));
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E89: No write since last change for buffer 2 (add ! to override)")
+        );
+        // :bnext reaches the hidden buffer and brings it back with its changes
+        run(&mut editor, Action::NextViewport);
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().path, None);
+        run(&mut editor, Action::NextViewport);
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().get(0),
+            Some("b".to_string())
+        );
+        assert!(!editor.viewports.is_hidden(2));
+        run(&mut editor, Action::PrevViewport);
+        run(&mut editor, Action::GotoBuffer("2".to_string()));
+        assert_eq!(editor.viewports.current_id(), Some(2));
+
+        run(&mut editor, Action::DeleteBuffer("2".to_string(), true));
+        run(&mut editor, Action::DeleteBuffer(String::new(), false));
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().path, None);
+
+        // the ids arent reused
+        editor.open_file("d.txt".to_string());
+        assert_eq!(editor.viewports.current_id(), Some(4));
+    }
+}
@@ -0,0 +1,155 @@
+use std::io::Write;
+
+use crate::editor::Editor;
+
+use super::{history::HistoryKind, mode::Mode};
+
+// byte index of the char at `index`
+fn byte_index(line: &str, index: usize) -> usize {
+    line.char_indices()
+        .nth(index)
+        .map(|(i, _)| i)
+        .unwrap_or(line.len())
+}
+
+impl<W: Write> Editor<W> {
+    // the line edited at the bottom and its cursor as the number of chars before it
+    fn cmdline(&mut self, is_search: bool) -> (&mut String, usize) {
+        let line = match is_search {
+            true => &mut self.search,
+            false => &mut self.command,
+        };
+        let len = line.chars().count();
+        self.cmdline_back = self.cmdline_back.min(len);
+        (line, len - self.cmdline_back)
+    }
+
+    fn is_search_cmdline(&self) -> bool {
+        matches!(self.mode, Mode::Search)
+    }
+
+    // the column of the cursor on the command line, after the : or /
+    pub fn cmdline_cursor(&mut self) -> u16 {
+        let is_search = self.is_search_cmdline();
+        self.cmdline(is_search).1 as u16 + 1
+    }
+
+    pub fn cmdline_insert(&mut self, is_search: bool, c: char) {
+        let (line, cursor) = self.cmdline(is_search);
+        line.insert(byte_index(line, cursor), c);
+    }
+
+    // remove the char before the cursor, false when the line is empty
+    pub fn cmdline_remove(&mut self, is_search: bool) -> bool {
+        let (line, cursor) = self.cmdline(is_search);
+        if line.is_empty() {
+            return false;
+        }
+        if cursor > 0 {
+            line.remove(byte_index(line, cursor - 1));
+        }
+        true
+    }
+
+    // move the cursor by `dx` chars, it stays in the line
+    pub fn cmdline_move(&mut self, dx: isize) {
+        let is_search = self.is_search_cmdline();
+        let (line, cursor) = self.cmdline(is_search);
+        let len = line.chars().count();
+        let cursor = (cursor as isize + dx).clamp(0, len as isize) as usize;
+        self.cmdline_back = len - cursor;
+    }
+
+    // ctrl-w remove the word before the cursor and ctrl-u everything before it
+    pub fn cmdline_delete_before(&mut self, word: bool) {
+        let is_search = self.is_search_cmdline();
+        let (line, cursor) = self.cmdline(is_search);
+        let chars: Vec<char> = line.chars().take(cursor).collect();
+        let mut start = cursor;
+        if word {
+            while start > 0 && chars[start - 1].is_whitespace() {
+                start -= 1;
+            }
+            let is_word = |c: char| c.is_alphanumeric() || c == '_';
+            let kind = start.checked_sub(1).map(|i| is_word(chars[i]));
+            while start > 0
+                && !chars[start - 1].is_whitespace()
+                && Some(is_word(chars[start - 1])) == kind
+            {
+                start -= 1;
+            }
+        } else {
+            start = 0;
+        }
+        line.replace_range(byte_index(line, start)..byte_index(line, cursor), "");
+    }
+
+    // replace the line by an older or newer entry of the history
+    pub fn cmdline_history(&mut self, older: bool) {
+        let is_search = self.is_search_cmdline();
+        let kind = match is_search {
+            true => HistoryKind::Search,
+            false => HistoryKind::Command,
+        };
+        let typed = self.cmdline(is_search).0.clone();
+        if let Some(entry) = self.history.navigate(kind, &typed, older) {
+            *self.cmdline(is_search).0 = entry;
+            self.cmdline_back = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_cmdline {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::editor::core::actions::action::Action;
+
+    #[test]
+    fn test_edit_in_the_middle() -> anyhow::Result<()> {
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        Action::EnterMode(Mode::Command).execute(&mut editor)?;
+        for c in "s/foo bar/x/".chars() {
+            Action::AddCommandChar(c).execute(&mut editor)?;
+        }
+        Action::CmdlineHome.execute(&mut editor)?;
+        Action::AddCommandChar('%').execute(&mut editor)?;
+        assert_eq!(editor.command, "%s/foo bar/x/");
+
+        Action::CmdlineEnd.execute(&mut editor)?;
+        Action::CmdlineLeft.execute(&mut editor)?;
+        Action::CmdlineLeft.execute(&mut editor)?;
+        Action::CmdlineLeft.execute(&mut editor)?;
+        Action::CmdlineDeleteWord.execute(&mut editor)?;
+        assert_eq!(editor.command, "%s/foo /x/");
+        Action::RemoveCharFrom(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "%s/foo/x/");
+        assert_eq!(editor.cmdline_cursor(), 7);
+
+        Action::CmdlineDeleteToStart.execute(&mut editor)?;
+        assert_eq!(editor.command, "/x/");
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_up_and_down() -> anyhow::Result<()> {
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        editor.history.push(HistoryKind::Command, "map");
+        editor.history.push(HistoryKind::Command, "w");
+        Action::EnterMode(Mode::Command).execute(&mut editor)?;
+        Action::AddCommandChar('m').execute(&mut editor)?;
+        Action::HistoryPrev.execute(&mut editor)?;
+        assert_eq!(editor.command, "map");
+        Action::HistoryNext.execute(&mut editor)?;
+        assert_eq!(editor.command, "m");
+
+        // the executed commands are added to the history
+        editor.command = "noh".to_string();
+        Action::ExecuteCommand.execute(&mut editor)?;
+        Action::EnterMode(Mode::Command).execute(&mut editor)?;
+        Action::HistoryPrev.execute(&mut editor)?;
+        assert_eq!(editor.command, "noh");
+        Ok(())
+    }
+}
@@ -1,21 +1,316 @@
-use super::actions::action::Action;
+use super::{
+    actions::action::Action,
+    ex::{Address, Ex, ExCommand, ExError, ExRange, Global},
+    file::FileWrite,
+    quickfix::{ListKind, ListMove},
+    substitute::Substitute,
+    user_command::UserCommands,
+    wildmenu::Complete,
+};
 
 pub struct Command;
 
+// the ex commands, the length of their shortest abbreviation and how their argument is completed,
+// the first one matching what is typed is used like :d for :delete before :de for :delmarks
+const COMMANDS: &[(&str, usize, Complete)] = &[
+    ("write", 1, Complete::File),
+    ("quit", 1, Complete::Nothing),
+    ("edit", 1, Complete::File),
+    ("wq", 2, Complete::File),
+    ("wall", 2, Complete::Nothing),
+    ("xit", 1, Complete::File),
+    ("exit", 3, Complete::File),
+    ("qall", 2, Complete::Nothing),
+    ("quitall", 5, Complete::Nothing),
+    ("saveas", 3, Complete::File),
+    ("read", 1, Complete::File),
+    ("set", 2, Complete::Option),
+    ("setlocal", 4, Complete::Option),
+    ("delete", 1, Complete::Nothing),
+    ("yank", 1, Complete::Nothing),
+    ("move", 1, Complete::Nothing),
+    ("mark", 2, Complete::Nothing),
+    ("map", 3, Complete::Mode),
+    ("copy", 2, Complete::Nothing),
+    ("t", 1, Complete::Nothing),
+    ("normal", 4, Complete::Nothing),
+    ("nohlsearch", 3, Complete::Nothing),
+    ("substitute", 1, Complete::Nothing),
+    ("global", 1, Complete::Nothing),
+    ("vglobal", 1, Complete::Nothing),
+    ("grep", 2, Complete::File),
+    ("make", 3, Complete::File),
+    ("copen", 4, Complete::Nothing),
+    ("cclose", 3, Complete::Nothing),
+    ("cnext", 2, Complete::Nothing),
+    ("cNext", 2, Complete::Nothing),
+    ("cprevious", 2, Complete::Nothing),
+    ("cfirst", 4, Complete::Nothing),
+    ("crewind", 2, Complete::Nothing),
+    ("clast", 3, Complete::Nothing),
+    ("cexpr", 3, Complete::Nothing),
+    ("cfile", 2, Complete::File),
+    ("lopen", 4, Complete::Nothing),
+    ("lclose", 3, Complete::Nothing),
+    ("lnext", 3, Complete::Nothing),
+    ("lNext", 2, Complete::Nothing),
+    ("lprevious", 2, Complete::Nothing),
+    ("lfirst", 4, Complete::Nothing),
+    ("lrewind", 2, Complete::Nothing),
+    ("llast", 3, Complete::Nothing),
+    ("lexpr", 3, Complete::Nothing),
+    ("lfile", 2, Complete::File),
+    (">", 1, Complete::Nothing),
+    ("<", 1, Complete::Nothing),
+    ("split", 2, Complete::File),
+    ("vsplit", 2, Complete::File),
+    ("close", 3, Complete::Nothing),
+    ("only", 2, Complete::Nothing),
+    ("buffer", 1, Complete::Buffer),
+    ("buffers", 7, Complete::Nothing),
+    ("ls", 2, Complete::Nothing),
+    ("files", 5, Complete::Nothing),
+    ("bnext", 2, Complete::Nothing),
+    ("bNext", 2, Complete::Nothing),
+    ("bprevious", 2, Complete::Nothing),
+    ("bdelete", 2, Complete::Buffer),
+];
+
+// the commands which accept a range and the ones which accept a !
+const RANGE_COMMANDS: &[&str] = &[
+    "delete",
+    "yank",
+    "move",
+    "mark",
+    "copy",
+    "t",
+    "normal",
+    "substitute",
+    "global",
+    "vglobal",
+    ">",
+    "<",
+    "read",
+];
+const BANG_COMMANDS: &[&str] = &[
+    "write", "quit", "normal", "global", "edit", "wq", "xit", "exit", "qall", "quitall", "saveas",
+    "read", "bdelete",
+];
+
+// the full name of a command from its abbreviation
+fn full_name(name: &str) -> Option<&'static str> {
+    COMMANDS
+        .iter()
+        .find(|(full, abbrev, _)| name.len() >= *abbrev && full.starts_with(name))
+        .map(|(full, _, _)| *full)
+}
+
+// the letters of the name, or the repeated > or < of a shift
+pub fn split_name(command: &str) -> (&str, &str) {
+    let name_len = match command.chars().next() {
+        Some(c @ ('>' | '<')) => command.find(|n| n != c).unwrap_or(command.len()),
+        _ => command
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(command.len()),
+    };
+    command.split_at(name_len)
+}
+
+fn no_args(args: &str) -> Result<(), ExError> {
+    match args.is_empty() {
+        true => Ok(()),
+        false => Err(ExError::TrailingCharacters(args.to_string())),
+    }
+}
+
+fn required(args: &str) -> Result<String, ExError> {
+    match args.is_empty() {
+        true => Err(ExError::ArgumentRequired),
+        false => Ok(args.to_string()),
+    }
+}
+
+// the count of :d 3 or :> 2
+fn count(args: &str) -> Result<Option<usize>, ExError> {
+    match args {
+        "" => Ok(None),
+        args => match args.parse::<usize>() {
+            Ok(count) if count > 0 => Ok(Some(count)),
+            _ => Err(ExError::TrailingCharacters(args.to_string())),
+        },
+    }
+}
+
+fn list_kind(name: &str) -> ListKind {
+    match name.starts_with('l') {
+        true => ListKind::Location,
+        false => ListKind::Quickfix,
+    }
+}
+
 impl Command {
-    pub fn execute(command: &str) -> Option<Action> {
-        match command {
-            "w" => Some(Action::Save),
-            "map" => Some(Action::HelpKeybinds(None)),
-            cmd => {
-                if let Ok(num) = cmd.parse::<u16>() {
-                    return Some(Action::GotoPos((0, num)));
-                }
-                if cmd.contains("map") {
-                    let cmd = cmd.replace("map", "");
-                    return Some(Action::HelpKeybinds(Some(cmd.trim().to_string())));
+    // the commands starting like the typed name with the ones of config.lua, sorted for the
+    // completion
+    pub fn names(typed: &str, user_commands: &UserCommands) -> Vec<String> {
+        let mut names: Vec<String> = COMMANDS
+            .iter()
+            .filter(|(full, _, _)| full.starts_with(typed) && full.starts_with(char::is_alphabetic))
+            .map(|(full, _, _)| full.to_string())
+            .chain(user_commands.names(typed))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // how the argument of a command is completed
+    pub fn completion(name: &str, user_commands: &UserCommands) -> Complete {
+        match full_name(name) {
+            Some(full) => COMMANDS
+                .iter()
+                .find(|(command, _, _)| *command == full)
+                .map(|(_, _, complete)| *complete)
+                .unwrap_or(Complete::Nothing),
+            None => user_commands
+                .find(name)
+                .map(|def| def.complete)
+                .unwrap_or(Complete::Nothing),
+        }
+    }
+
+    // parse a command line like :[range]name[!] [args]
+    pub fn execute(command: &str, user_commands: &UserCommands) -> Result<Action, ExError> {
+        let command = command.trim_start_matches([' ', ':']);
+        let (range, rest) = ExRange::parse_prefix(command)?;
+        let rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(Action::Ex(Ex::new(range, ExCommand::Goto)));
+        }
+
+        // :!cmd runs a shell command, with a range the lines are filtered through it
+        if let Some(cmd) = rest.strip_prefix('!') {
+            let cmd = required(cmd.trim())?;
+            return Ok(match range {
+                Some(range) => Action::Ex(Ex::new(Some(range), ExCommand::Filter(cmd))),
+                None => Action::Shell(cmd),
+            });
+        }
+
+        let (name, rest) = split_name(rest);
+        let not_a_command = || ExError::NotACommand(command.to_string());
+        // :>> is :> shifting twice
+        let typed = match name.starts_with(['>', '<']) {
+            true => &name[..1],
+            false => name,
+        };
+        // the commands of config.lua are used when no built-in one matches
+        let Some(full) = full_name(typed) else {
+            let def = user_commands.find(name).ok_or_else(not_a_command)?;
+            return def.parse(range, rest).map(Action::UserCommand);
+        };
+        // the arguments of :s and :g start right after the name like in :s/a/b/
+        let (bang, args) = match rest.strip_prefix('!') {
+            Some(args) => (true, args),
+            None => (false, rest),
+        };
+        if bang && !BANG_COMMANDS.contains(&full) {
+            return Err(ExError::NoBang);
+        }
+        if range.is_some() && !RANGE_COMMANDS.contains(&full) {
+            return Err(ExError::NoRange);
+        }
+        let raw_args = args;
+        let args = args.trim();
+        let ex = |command| Ok(Action::Ex(Ex::new(range.clone(), command)));
+
+        match full {
+            "write" if args.is_empty() => Ok(Action::Save),
+            "write" | "saveas" | "wq" | "xit" | "exit" => {
+                let write = FileWrite {
+                    force: bang,
+                    rename: full == "saveas",
+                    quit: full != "write" && full != "saveas",
+                    if_modified: full == "xit" || full == "exit",
+                    ..FileWrite::parse(args)
+                };
+                match full == "saveas" && write.path.is_none() {
+                    true => Err(ExError::ArgumentRequired),
+                    false => Ok(Action::WriteFile(write)),
                 }
-                None
+            }
+            "wall" => no_args(args).map(|_| Action::SaveAll),
+            // :r !cmd and :r! cmd read the output of a command
+            "read" => match (bang, args.strip_prefix('!')) {
+                (true, _) => ex(ExCommand::ReadShell(required(args)?)),
+                (false, Some(cmd)) => ex(ExCommand::ReadShell(required(cmd.trim())?)),
+                (false, None) => ex(ExCommand::ReadFile(required(args)?)),
+            },
+            "set" | "setlocal" => Ok(Action::Set(args.to_string(), full == "setlocal")),
+            "edit" => Ok(Action::Edit(
+                (!args.is_empty()).then(|| args.to_string()),
+                bang,
+            )),
+            "quit" => no_args(args).map(|_| match bang {
+                true => Action::ForceQuit,
+                false => Action::Quit,
+            }),
+            "qall" | "quitall" => no_args(args).map(|_| Action::QuitAll(bang)),
+            "delete" | "yank" => Ok(Action::Ex(Ex {
+                range,
+                count: count(args)?,
+                command: match full {
+                    "delete" => ExCommand::Delete,
+                    _ => ExCommand::Yank,
+                },
+            })),
+            "move" => ex(ExCommand::Move(Address::parse(args)?)),
+            "copy" | "t" => ex(ExCommand::Copy(Address::parse(args)?)),
+            ">" | "<" => Ok(Action::Ex(Ex {
+                range,
+                count: count(args)?,
+                command: ExCommand::Shift(full == ">", name.len()),
+            })),
+            "normal" => ex(ExCommand::Normal(required(raw_args.trim_start())?)),
+            "mark" => match args.chars().collect::<Vec<char>>().as_slice() {
+                [] => Err(ExError::ArgumentRequired),
+                [mark] if mark.is_ascii_lowercase() => ex(ExCommand::Mark(*mark)),
+                _ => Err(ExError::InvalidArgument(args.to_string())),
+            },
+            "substitute" => Substitute::parse_args(range, raw_args)
+                .map(Action::Substitute)
+                .ok_or_else(|| ExError::TrailingCharacters(raw_args.to_string())),
+            "global" | "vglobal" => Global::parse_args(range, bang || full == "vglobal", raw_args)
+                .map(Action::Global)
+                .ok_or(ExError::ArgumentRequired),
+            "nohlsearch" => no_args(args).map(|_| Action::ClearSearchHighlight),
+            "map" => Ok(Action::HelpKeybinds(
+                (!args.is_empty()).then(|| args.to_string()),
+            )),
+            "grep" => required(args).map(Action::Grep),
+            "make" => Ok(Action::Make(args.to_string())),
+            "copen" | "lopen" => no_args(args).map(|_| Action::ListOpen(list_kind(full))),
+            "cclose" | "lclose" => no_args(args).map(|_| Action::ListClose(list_kind(full))),
+            "cexpr" | "lexpr" => required(args).map(|args| Action::ListExpr(list_kind(full), args)),
+            "cfile" | "lfile" => required(args).map(|args| Action::ListFile(list_kind(full), args)),
+            "split" | "vsplit" => Ok(Action::Split(
+                full == "vsplit",
+                (!args.is_empty()).then(|| args.to_string()),
+            )),
+            "close" => no_args(args).map(|_| Action::CloseWindow),
+            "only" => no_args(args).map(|_| Action::OnlyWindow),
+            "buffers" | "ls" | "files" => no_args(args).map(|_| Action::ListBuffers),
+            "buffer" => Ok(Action::GotoBuffer(args.to_string())),
+            "bnext" => no_args(args).map(|_| Action::NextViewport),
+            "bNext" | "bprevious" => no_args(args).map(|_| Action::PrevViewport),
+            "bdelete" => Ok(Action::DeleteBuffer(args.to_string(), bang)),
+            list => {
+                let list_move = match &list[1..] {
+                    "next" => ListMove::Next,
+                    "Next" | "previous" => ListMove::Prev,
+                    "first" | "rewind" => ListMove::First,
+                    _ => ListMove::Last,
+                };
+                no_args(args).map(|_| Action::ListGoto(list_kind(list), list_move))
             }
         }
     }
@@ -23,27 +318,129 @@ impl Command {
 
 #[cfg(test)]
 mod tests_command {
-    use crate::editor::core::actions::action::Action;
+    use crate::editor::core::{
+        actions::action::Action,
+        ex::{Address, Ex, ExCommand, ExError, ExRange},
+    };
 
-    use super::Command;
+    use super::{Command, UserCommands};
     #[test]
     fn test_basic_command() {
-        let result = Command::execute("w");
-        assert!(result == Some(Action::Save), "w shoudl save the app")
+        let result = Command::execute("w", &UserCommands::default());
+        assert!(result == Ok(Action::Save), "w shoudl save the app");
+        assert!(Command::execute(":wri", &UserCommands::default()) == Ok(Action::Save));
+        assert!(Command::execute("q!", &UserCommands::default()) == Ok(Action::ForceQuit));
     }
 
     #[test]
     fn test_command_with_param() {
-        let result = Command::execute("map e");
+        let result = Command::execute("map e", &UserCommands::default());
         assert!(
-            result == Some(Action::HelpKeybinds(Some("e".to_string()))),
+            result == Ok(Action::HelpKeybinds(Some("e".to_string()))),
             "help keybinds should have e in param"
         )
     }
 
+    #[test]
+    fn test_list_commands() {
+        use crate::editor::core::quickfix::{ListKind, ListMove};
+        assert!(
+            Command::execute("copen", &UserCommands::default())
+                == Ok(Action::ListOpen(ListKind::Quickfix))
+        );
+        assert!(
+            Command::execute("lnext", &UserCommands::default())
+                == Ok(Action::ListGoto(ListKind::Location, ListMove::Next))
+        );
+        assert!(
+            Command::execute("cfile out.txt", &UserCommands::default())
+                == Ok(Action::ListFile(ListKind::Quickfix, "out.txt".to_string()))
+        );
+        assert!(
+            Command::execute("cexpr", &UserCommands::default()) == Err(ExError::ArgumentRequired)
+        );
+        assert!(
+            Command::execute("mak --release", &UserCommands::default())
+                == Ok(Action::Make("--release".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_shell_commands() {
+        let read = |cmd: &str| Ok(Action::Ex(Ex::new(None, ExCommand::ReadShell(cmd.into()))));
+        assert!(
+            Command::execute("!ls -a", &UserCommands::default())
+                == Ok(Action::Shell("ls -a".to_string()))
+        );
+        assert!(Command::execute("r !ls", &UserCommands::default()) == read("ls"));
+        assert!(Command::execute("r! ls", &UserCommands::default()) == read("ls"));
+        assert!(
+            Command::execute("%!sort", &UserCommands::default())
+                == Ok(Action::Ex(Ex::new(
+                    Some(ExRange::all()),
+                    ExCommand::Filter("sort".to_string())
+                )))
+        );
+        assert!(Command::execute("!", &UserCommands::default()) == Err(ExError::ArgumentRequired));
+    }
+
+    #[test]
+    fn test_range_and_count() {
+        let range = Some(ExRange {
+            start: Address::Current(0),
+            end: Address::Last(0),
+        });
+        assert!(
+            Command::execute(".,$m0", &UserCommands::default())
+                == Ok(Action::Ex(Ex::new(
+                    range,
+                    ExCommand::Move(Address::Line(0))
+                )))
+        );
+        assert!(
+            Command::execute("%>>", &UserCommands::default())
+                == Ok(Action::Ex(Ex::new(
+                    Some(ExRange::all()),
+                    ExCommand::Shift(true, 2)
+                )))
+        );
+        assert!(
+            Command::execute("d 3", &UserCommands::default())
+                == Ok(Action::Ex(Ex {
+                    range: None,
+                    count: Some(3),
+                    command: ExCommand::Delete,
+                }))
+        );
+        assert!(
+            Command::execute("12", &UserCommands::default())
+                == Ok(Action::Ex(Ex::new(
+                    Some(ExRange {
+                        start: Address::Line(12),
+                        end: Address::Line(12),
+                    }),
+                    ExCommand::Goto
+                )))
+        );
+    }
+
     #[test]
     fn test_false_command() {
-        let result = Command::execute("false_cmd");
-        assert!(result.is_none(), "should be none")
+        let result = Command::execute("false_cmd", &UserCommands::default());
+        assert!(result.is_err(), "should be an error");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "E492: Not an editor command: false_cmd"
+        );
+        assert!(Command::execute("d!", &UserCommands::default()) == Err(ExError::NoBang));
+        assert!(Command::execute("1,2w", &UserCommands::default()) == Err(ExError::NoRange));
+        assert!(
+            Command::execute("ma 1", &UserCommands::default())
+                == Err(ExError::InvalidArgument("1".to_string()))
+        );
+        assert!(
+            Command::execute("d x", &UserCommands::default())
+                == Err(ExError::TrailingCharacters("x".to_string()))
+        );
     }
 }
@@ -0,0 +1,244 @@
+use std::{collections::HashMap, io::Write};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::{buff::Buffer, editor::Editor, viewports::Viewports};
+
+use super::actions::action::Action;
+
+const MAX_ITEMS: usize = 100;
+// words of the other buffers are always ranked after the ones of the current buffer
+const OTHER_BUFFER_DISTANCE: usize = usize::MAX / 2;
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub prefix: String,
+    pub items: Vec<String>,
+    pub index: usize,
+    pub start: (u16, u16), // buffer position of the start of the prefix
+}
+
+impl Completion {
+    pub fn selected(&self) -> Option<&String> {
+        self.items.get(self.index)
+    }
+
+    pub fn select_next(&mut self) {
+        self.index = (self.index + 1) % self.items.len().max(1);
+    }
+
+    pub fn select_prev(&mut self) {
+        self.index = match self.index {
+            0 => self.items.len().saturating_sub(1),
+            i => i - 1,
+        };
+    }
+}
+
+// add the words of the buffer with their distance to the line y
+fn collect_words(buffer: &Buffer, y: Option<usize>, words: &mut HashMap<String, usize>) {
+    for (i, line) in buffer.lines.iter().enumerate() {
+        let distance = match y {
+            Some(y) => i.abs_diff(y),
+            None => OTHER_BUFFER_DISTANCE,
+        };
+        for word in line.split(|c: char| !is_word_char(c)) {
+            if word.chars().count() < 2 {
+                continue;
+            }
+            let best = words.entry(word.to_string()).or_insert(distance);
+            *best = (*best).min(distance);
+        }
+    }
+}
+
+// words starting with the prefix, the ones with the same case first then the closest
+// to the cursor
+pub fn rank_words(viewports: &Viewports, prefix: &str, y: usize) -> Vec<String> {
+    let mut words = HashMap::new();
+    for (i, viewport) in viewports.values.iter().enumerate() {
+        let is_current = !viewports.is_explorer && i == viewports.index;
+        collect_words(
+            &viewport.buffer.borrow(),
+            is_current.then_some(y),
+            &mut words,
+        );
+    }
+
+    let lower_prefix = prefix.to_lowercase();
+    let mut matches: Vec<(bool, usize, String)> = words
+        .into_iter()
+        .filter(|(word, _)| word != prefix && word.to_lowercase().starts_with(&lower_prefix))
+        .map(|(word, distance)| (!word.starts_with(prefix), distance, word))
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .take(MAX_ITEMS)
+        .map(|(_, _, word)| word)
+        .collect()
+}
+
+impl<W: Write> Editor<W> {
+    // the word before the cursor and the position where it starts
+    pub fn word_before_cursor(&self) -> (String, (u16, u16)) {
+        let (x, y) = self.v_cursor();
+        let line = self.viewports.c_viewport().buffer.borrow().get(y as usize);
+        let before: Vec<char> = line
+            .map(|line| line.chars().take(x as usize).collect())
+            .unwrap_or_default();
+        let len = before
+            .iter()
+            .rev()
+            .take_while(|c| is_word_char(**c))
+            .count();
+        let prefix = before[before.len() - len..].iter().collect();
+        (prefix, (x - len as u16, y))
+    }
+
+    pub fn open_completion(&mut self, forward: bool) {
+        let (prefix, start) = self.word_before_cursor();
+        let items = rank_words(&self.viewports, &prefix, start.1 as usize);
+        if items.is_empty() {
+            self.toast.error(format!("no completion for {prefix}"));
+            return;
+        }
+        let index = match forward {
+            true => 0,
+            false => items.len() - 1,
+        };
+        self.completion = Some(Completion {
+            prefix,
+            items,
+            index,
+            start,
+        });
+    }
+
+    // while the list is open the keys are used to navigate in it, any other key close it
+    // and is handled as usual
+    pub fn handle_completion(
+        &mut self,
+        code: &KeyCode,
+        modifiers: &KeyModifiers,
+    ) -> Option<Action> {
+        let is_control = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Down => Some(Action::CompleteNext),
+            KeyCode::Up => Some(Action::CompletePrev),
+            KeyCode::Char('n') if is_control => Some(Action::CompleteNext),
+            KeyCode::Char('p') if is_control => Some(Action::CompletePrev),
+            KeyCode::Enter | KeyCode::Tab => Some(Action::AcceptCompletion),
+            KeyCode::Esc => Some(Action::CancelCompletion),
+            _ => {
+                self.completion = None;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_completion {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{editor::core::mode::Mode, viewport::Viewport};
+
+    fn editor_with(lines: Vec<&str>, other: Vec<&str>) -> Editor<Cursor<Vec<u8>>> {
+        let to_buffer = |lines: Vec<&str>| {
+            Buffer::new_tmp(
+                lines.into_iter().map(|l| l.to_string()).collect(),
+                "a.rs".to_string(),
+            )
+        };
+        let mut editor = Editor::default();
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = to_buffer(lines);
+        editor
+            .viewports
+            .push(Viewport::new(to_buffer(other), 80, 20, 0, true));
+        editor.mode = Mode::Insert;
+        editor
+    }
+
+    #[test]
+    fn test_rank_words_by_case_and_proximity() {
+        let editor = editor_with(
+            vec!["value_far", "", "", "Value_upper", "va", "value_near"],
+            vec!["value_other"],
+        );
+        let words = rank_words(&editor.viewports, "va", 4);
+        assert_eq!(
+            words,
+            vec!["value_near", "value_far", "value_other", "Value_upper"]
+        );
+    }
+
+    #[test]
+    fn test_complete_and_accept() -> anyhow::Result<()> {
+        let mut editor = editor_with(vec!["let counter = 1;", "cou"], vec![]);
+        editor.cursor = (3, 1);
+
+        Action::CompleteNext.execute(&mut editor)?;
+        let completion = editor.completion.clone().unwrap();
+        assert_eq!(completion.prefix, "cou");
+        assert_eq!(completion.selected(), Some(&"counter".to_string()));
+
+        let action = editor.handle_completion(&KeyCode::Enter, &KeyModifiers::NONE);
+        assert_eq!(action, Some(Action::AcceptCompletion));
+        action.unwrap().execute(&mut editor)?;
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().lines[1],
+            "counter"
+        );
+        assert!(editor.completion.is_none());
+
+        // the completion is undone with the rest of the insert
+        Action::EnterMode(Mode::Normal).execute(&mut editor)?;
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().lines[1],
+            "cou"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_accept_after_non_ascii() -> anyhow::Result<()> {
+        let mut editor = editor_with(vec!["counter caféine", "é cou", "caf"], vec![]);
+        let line = |editor: &Editor<Cursor<Vec<u8>>>, y: usize| {
+            editor.viewports.c_viewport().buffer.borrow().lines[y].clone()
+        };
+        editor.cursor = (5, 1);
+        Action::CompleteNext.execute(&mut editor)?;
+        Action::AcceptCompletion.execute(&mut editor)?;
+        assert_eq!(line(&editor, 1), "é counter");
+        assert_eq!(editor.cursor, (9, 1));
+
+        editor.cursor = (3, 2);
+        Action::CompleteNext.execute(&mut editor)?;
+        Action::AcceptCompletion.execute(&mut editor)?;
+        assert_eq!(line(&editor, 2), "caféine");
+        assert_eq!(editor.cursor, (7, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_completion_closed_by_other_keys() -> anyhow::Result<()> {
+        let mut editor = editor_with(vec!["alpha alpine", "al"], vec![]);
+        editor.cursor = (2, 1);
+        Action::CompletePrev.execute(&mut editor)?;
+        assert_eq!(editor.completion.as_ref().unwrap().index, 1);
+        Action::CompleteNext.execute(&mut editor)?;
+        assert_eq!(editor.completion.as_ref().unwrap().index, 0);
+
+        let action = editor.handle_completion(&KeyCode::Char('x'), &KeyModifiers::NONE);
+        assert_eq!(action, None);
+        assert!(editor.completion.is_none());
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::editor::Editor;
+use crate::editor::{core::mode::Mode, Editor};
 use anyhow::{Ok, Result};
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 
@@ -23,6 +23,22 @@ impl<W: Write> Editor<W> {
                 return modal.handle_action(&code, &modifiers);
             }
 
+            if self.pending_operator.is_some() {
+                return Ok(self.handle_pending_operator(code));
+            }
+
+            if self.completion.is_some() {
+                if let Some(action) = self.handle_completion(&code, &modifiers) {
+                    return Ok(Some(action));
+                }
+            }
+
+            if self.snippet.is_some() && matches!(self.mode, Mode::Insert) {
+                if let Some(action) = self.handle_snippet_key(&code) {
+                    return Ok(Some(action));
+                }
+            }
+
             // if let Some(c) = self.waiting_command {
             //     let action = self.handle_waiting_command(c, &code);
             //     self.waiting_command = None;
@@ -42,14 +58,9 @@ impl<W: Write> Editor<W> {
         modifiers: KeyModifiers, // not used for now
     ) -> Result<Option<Action>> {
         let is_file_explorer = self.viewports.c_viewport().is_file_explorer();
-        let result = self.keybinds.handle_keybinds(
-            self.mode,
-            code,
-            modifiers,
-            &self.v_cursor(),
-            &self.command,
-            is_file_explorer,
-        );
+        let result = self
+            .keybinds
+            .handle_keybinds(self.mode, code, modifiers, is_file_explorer);
 
         Ok(result)
     }
@@ -0,0 +1,907 @@
+use std::{fmt, io::Write};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{buff::search_regex, editor::Editor, helper::clipboard::copy_to_clipboard};
+
+use super::{
+    actions::action::{Action, OldCursorPosition},
+    command::Command,
+    mode::Mode,
+    shell::shell_lines,
+};
+
+// the errors of the ex commands, shown like the vim ones
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExError {
+    NotACommand(String),
+    TrailingCharacters(String),
+    InvalidArgument(String),
+    ArgumentRequired,
+    InvalidAddress,
+    InvalidRange,
+    MarkNotSet,
+    NoBang,
+    NoRange,
+    MoveIntoItself,
+    NoFileName,
+    FileExists,
+    NotAFile,
+    CantOpenFile(String),
+    NoWriteSinceLastChange,
+    UnknownOption(String),
+    NumberRequired(String),
+    CantReadFile(String),
+    CantRunShell(String),
+    ShellReturned(i32, String), // the exit code and the first line of the errors
+    LuaError(String),
+    NoSuchBuffer(usize),
+    NoMatchingBuffer(String),
+    MoreThanOneMatch(String),
+    BufferModified(usize),
+    BufferNotSaved(usize, String),
+}
+
+impl fmt::Display for ExError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExError::NotACommand(cmd) => write!(f, "E492: Not an editor command: {cmd}"),
+            ExError::TrailingCharacters(arg) => write!(f, "E488: Trailing characters: {arg}"),
+            ExError::InvalidArgument(arg) => write!(f, "E475: Invalid argument: {arg}"),
+            ExError::ArgumentRequired => write!(f, "E471: Argument required"),
+            ExError::InvalidAddress => write!(f, "E14: Invalid address"),
+            ExError::InvalidRange => write!(f, "E16: Invalid range"),
+            ExError::MarkNotSet => write!(f, "E20: Mark not set"),
+            ExError::NoBang => write!(f, "E477: No ! allowed"),
+            ExError::NoRange => write!(f, "E481: No range allowed"),
+            ExError::MoveIntoItself => write!(f, "E134: Cannot move a range of lines into itself"),
+            ExError::NoFileName => write!(f, "E32: No file name"),
+            ExError::FileExists => write!(f, "E13: File exists (add ! to override)"),
+            ExError::NotAFile => write!(f, "E382: Cannot write, the viewport is not a file"),
+            ExError::CantOpenFile(path) => write!(f, "E212: Can't open file for writing: {path}"),
+            ExError::NoWriteSinceLastChange => {
+                write!(f, "E37: No write since last change (add ! to override)")
+            }
+            ExError::UnknownOption(name) => write!(f, "E518: Unknown option: {name}"),
+            ExError::NumberRequired(arg) => write!(f, "E521: Number required after =: {arg}"),
+            ExError::CantReadFile(path) => write!(f, "E484: Can't open file {path}"),
+            ExError::CantRunShell(err) => write!(f, "Cannot run the shell: {err}"),
+            ExError::ShellReturned(code, err) if err.is_empty() => {
+                write!(f, "shell returned {code}")
+            }
+            ExError::ShellReturned(code, err) => write!(f, "shell returned {code}: {err}"),
+            ExError::LuaError(err) => write!(f, "E5108: Error executing lua: {err}"),
+            ExError::NoSuchBuffer(id) => write!(f, "E86: Buffer {id} does not exist"),
+            ExError::NoMatchingBuffer(name) => write!(f, "E94: No matching buffer for {name}"),
+            ExError::MoreThanOneMatch(name) => write!(f, "E93: More than one match for {name}"),
+            ExError::BufferModified(id) => write!(
+                f,
+                "E89: No write since last change for buffer {id} (add ! to override)"
+            ),
+            ExError::BufferNotSaved(id, name) => write!(
+                f,
+                "E162: No write since last change for buffer {id} \"{name}\""
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExError {}
+
+// a line of an ex command, . $ and the marks can have an offset like .+2 or 'a-1
+#[derive(Debug, Clone, PartialEq)]
+pub enum Address {
+    Current(isize),
+    Last(isize),
+    Line(usize), // starting at 1, 0 is before the first line
+    Mark(char, isize),
+}
+
+// read the offsets like +2-1, a sign alone count for one
+fn parse_offsets(offsets: &str) -> (isize, &str) {
+    let mut offset = 0;
+    let mut rest = offsets;
+    while let Some(sign @ ('+' | '-')) = rest.chars().next() {
+        let digits = rest[1..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map(|i| i + 1)
+            .unwrap_or(rest.len());
+        let n = rest[1..digits].parse::<isize>().unwrap_or(1);
+        offset += if sign == '+' { n } else { -n };
+        rest = &rest[digits..];
+    }
+    (offset, rest)
+}
+
+impl Address {
+    // the address at the start of the command and what follows it
+    pub fn parse_prefix(command: &str) -> Result<Option<(Self, &str)>, ExError> {
+        let mut chars = command.chars();
+        let (base, rest) = match chars.next() {
+            Some('.') => (Some(Address::Current(0)), chars.as_str()),
+            Some('$') => (Some(Address::Last(0)), chars.as_str()),
+            Some('\'') => match chars.next() {
+                Some(mark) if mark.is_ascii_lowercase() || mark == '<' || mark == '>' => {
+                    (Some(Address::Mark(mark, 0)), chars.as_str())
+                }
+                _ => return Err(ExError::MarkNotSet),
+            },
+            Some(c) if c.is_ascii_digit() => {
+                let digits = command
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(command.len());
+                let line = command[..digits]
+                    .parse::<usize>()
+                    .map_err(|_| ExError::InvalidRange)?;
+                (Some(Address::Line(line)), &command[digits..])
+            }
+            Some('+' | '-') => (Some(Address::Current(0)), command),
+            _ => (None, command),
+        };
+        let Some(base) = base else {
+            return Ok(None);
+        };
+        let (offset, rest) = parse_offsets(rest);
+        let address = match base {
+            Address::Current(_) => Address::Current(offset),
+            Address::Last(_) => Address::Last(offset),
+            Address::Mark(mark, _) => Address::Mark(mark, offset),
+            Address::Line(line) => {
+                let line = line as isize + offset;
+                Address::Line(usize::try_from(line).map_err(|_| ExError::InvalidRange)?)
+            }
+        };
+        Ok(Some((address, rest)))
+    }
+
+    // an address which is the whole argument like the one of :m or :t
+    pub fn parse(address: &str) -> Result<Self, ExError> {
+        match Self::parse_prefix(address.trim())? {
+            Some((address, "")) => Ok(address),
+            _ => Err(ExError::InvalidAddress),
+        }
+    }
+}
+
+// the lines of an ex command, a single address is a range of one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExRange {
+    pub start: Address,
+    pub end: Address,
+}
+
+impl ExRange {
+    pub fn all() -> Self {
+        Self {
+            start: Address::Line(1),
+            end: Address::Last(0),
+        }
+    }
+
+    pub fn current() -> Self {
+        Self {
+            start: Address::Current(0),
+            end: Address::Current(0),
+        }
+    }
+
+    // the range at the start of the command like %, 1,10, .,$ or '<,'> and what follows it
+    pub fn parse_prefix(command: &str) -> Result<(Option<Self>, &str), ExError> {
+        if let Some(rest) = command.strip_prefix('%') {
+            return Ok((Some(Self::all()), rest));
+        }
+        let (start, rest) = match Address::parse_prefix(command)? {
+            Some((start, rest)) => (Some(start), rest),
+            None => (None, command),
+        };
+        let Some(rest) = rest.strip_prefix(',') else {
+            let range = start.map(|start| Self {
+                end: start.clone(),
+                start,
+            });
+            return Ok((range, rest));
+        };
+        // a missing address of a range is the current line
+        let (end, rest) = Address::parse_prefix(rest)?.unwrap_or((Address::Current(0), rest));
+        let start = start.unwrap_or(Address::Current(0));
+        Ok((Some(Self { start, end }), rest))
+    }
+}
+
+// the ex commands about the lines of the buffer
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExCommand {
+    Goto, // only a range, the cursor goes to its last line
+    Delete,
+    Yank,
+    Move(Address), // the lines go below the address
+    Copy(Address),
+    Shift(bool, usize), // right and the number of shifts
+    Normal(String),     // keys run in normal mode on each line
+    Mark(char),
+    Filter(String), // the lines are replaced by the output of the command they are given to
+    ReadFile(String), // the lines of the file go below the range
+    ReadShell(String),
+}
+
+// an ex command with its range, a count makes the range start at its last line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ex {
+    pub range: Option<ExRange>,
+    pub count: Option<usize>,
+    pub command: ExCommand,
+}
+
+impl Ex {
+    pub fn new(range: Option<ExRange>, command: ExCommand) -> Self {
+        Self {
+            range,
+            count: None,
+            command,
+        }
+    }
+}
+
+// :g/pattern/command run the command on the matching lines, :v or :g! on the others
+#[derive(Debug, Clone, PartialEq)]
+pub struct Global {
+    pub range: Option<ExRange>,
+    pub pattern: String,
+    pub inverse: bool,
+    pub command: String,
+}
+
+impl Global {
+    // the /pattern/command after :g
+    pub fn parse_args(range: Option<ExRange>, inverse: bool, args: &str) -> Option<Self> {
+        let sep = args.chars().next()?;
+        if sep.is_alphanumeric() || sep.is_whitespace() || sep == '\\' || sep == '"' {
+            return None;
+        }
+
+        // the pattern ends at the first separator which is not escaped
+        let rest = &args[sep.len_utf8()..];
+        let mut pattern = String::new();
+        let mut chars = rest.char_indices();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, next)) if next == sep => pattern.push(next),
+                    Some((_, next)) => {
+                        pattern.push('\\');
+                        pattern.push(next);
+                    }
+                    None => pattern.push('\\'),
+                },
+                c if c == sep => {
+                    end = i + c.len_utf8();
+                    break;
+                }
+                c => pattern.push(c),
+            }
+        }
+        let command = rest[end.min(rest.len())..].trim().to_string();
+        if command.is_empty() {
+            return None;
+        }
+        Some(Self {
+            range,
+            pattern,
+            inverse,
+            command,
+        })
+    }
+}
+
+// the keys of :normal, <Esc> <CR> <Tab> <BS> <Space> <lt> and <C-x> are read as special keys
+pub fn parse_keys(keys: &str) -> Vec<KeyEvent> {
+    let mut events = vec![];
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        let special = rest
+            .strip_prefix('<')
+            .and_then(|r| r.split_once('>'))
+            .and_then(|(name, after)| {
+                let key = match name.to_lowercase().as_str() {
+                    "esc" => (KeyCode::Esc, KeyModifiers::NONE),
+                    "cr" | "enter" | "return" => (KeyCode::Enter, KeyModifiers::NONE),
+                    "tab" => (KeyCode::Tab, KeyModifiers::NONE),
+                    "bs" => (KeyCode::Backspace, KeyModifiers::NONE),
+                    "space" => (KeyCode::Char(' '), KeyModifiers::NONE),
+                    "lt" => (KeyCode::Char('<'), KeyModifiers::NONE),
+                    ctrl => {
+                        let c = ctrl.strip_prefix("c-")?;
+                        let mut chars = c.chars();
+                        let c = chars.next().filter(|_| chars.next().is_none())?;
+                        (KeyCode::Char(c), KeyModifiers::CONTROL)
+                    }
+                };
+                Some((key, after))
+            });
+        match special {
+            Some(((code, modifiers), after)) => {
+                events.push(KeyEvent::new(code, modifiers));
+                rest = after;
+            }
+            None => {
+                let modifiers = match c.is_uppercase() {
+                    true => KeyModifiers::SHIFT,
+                    false => KeyModifiers::NONE,
+                };
+                events.push(KeyEvent::new(KeyCode::Char(c), modifiers));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    events
+}
+
+// the lines of start..end go to the insertion point `to` which is outside of them
+fn move_lines<T>(lines: &mut [T], (start, end): (usize, usize), to: usize) {
+    match to > end {
+        true => lines[start..to].rotate_left(end - start),
+        false => lines[to..end].rotate_right(end - start),
+    }
+}
+
+fn shift_line(line: &str, right: bool, shifts: usize, shiftwidth: usize) -> String {
+    let width = shiftwidth * shifts;
+    match right {
+        true if line.is_empty() => String::new(),
+        true => format!("{}{line}", " ".repeat(width)),
+        false => {
+            // a tab is removed like the spaces of a shift
+            let mut removed = 0;
+            let start = line
+                .char_indices()
+                .find(|(_, c)| {
+                    let len = match c {
+                        ' ' => 1,
+                        '\t' => shiftwidth,
+                        _ => return true,
+                    };
+                    removed += len;
+                    removed > width
+                })
+                .map(|(i, _)| i)
+                .unwrap_or(line.len());
+            line[start..].to_string()
+        }
+    }
+}
+
+impl<W: Write> Editor<W> {
+    // run an action and the ones it buffers before going back to the caller
+    fn run_action(&mut self, action: Action) -> anyhow::Result<()> {
+        let depth = self.buffer_actions.len();
+        action.execute(self)?;
+        while self.buffer_actions.len() > depth {
+            if let Some(action) = self.buffer_actions.pop() {
+                action.execute(self)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn goto_line(&mut self, y: usize) -> anyhow::Result<()> {
+        self.goto_pos((0, y))
+    }
+
+    // the line and the column are kept in the buffer
+    pub(super) fn goto_pos(&mut self, (x, y): (usize, usize)) -> anyhow::Result<()> {
+        let y = y.min(self.buffer_len().saturating_sub(1));
+        let x = x.min(
+            self.viewports.c_viewport().buffer.borrow().lines[y]
+                .chars()
+                .count(),
+        );
+        let depth = self.buffer_actions.len();
+        self.goto_buffer_pos((x as u16, y as u16));
+        while self.buffer_actions.len() > depth {
+            if let Some(action) = self.buffer_actions.pop() {
+                action.execute(self)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn buffer_len(&self) -> usize {
+        self.viewports.c_viewport().buffer.borrow().lines.len()
+    }
+
+    // the line number starting at 1, 0 is before the first line
+    pub fn resolve_address(&self, address: &Address) -> Result<usize, ExError> {
+        let len = self.buffer_len() as isize;
+        let line = match address {
+            Address::Current(offset) => self.v_cursor().1 as isize + 1 + offset,
+            Address::Last(offset) => len + offset,
+            Address::Line(line) => *line as isize,
+            Address::Mark(mark, offset) => {
+                let line = match mark {
+                    '<' => self.visual_lines.map(|(start, _)| start as usize),
+                    '>' => self.visual_lines.map(|(_, end)| end as usize),
+                    mark => self
+                        .viewports
+                        .c_viewport()
+                        .buffer
+                        .borrow()
+                        .marks
+                        .get(mark)
+                        .copied(),
+                };
+                line.ok_or(ExError::MarkNotSet)? as isize + 1 + offset
+            }
+        };
+        match (0..=len).contains(&line) {
+            true => Ok(line as usize),
+            false => Err(ExError::InvalidRange),
+        }
+    }
+
+    // the lines start..end of the range, or of the default one when it has none
+    pub fn resolve_range(
+        &self,
+        range: &Option<ExRange>,
+        default: ExRange,
+    ) -> Result<(usize, usize), ExError> {
+        let range = range.clone().unwrap_or(default);
+        let start = self.resolve_address(&range.start)?.max(1);
+        let end = self.resolve_address(&range.end)?.max(1);
+        Ok((start.min(end) - 1, start.max(end)))
+    }
+
+    // replace lines and keep the marks of a running :g on the lines which are not replaced
+    pub(super) fn edit_lines(&mut self, start: usize, old_len: usize, lines: Vec<String>) {
+        let new_len = lines.len();
+        self.replace_buffer_lines(start, old_len, lines);
+        if let Some(marks) = self.global_marks.as_mut() {
+            let start = start.min(marks.len());
+            let end = (start + old_len).min(marks.len());
+            if new_len != old_len {
+                marks.splice(start..end, vec![false; new_len]);
+            }
+        }
+    }
+
+    // the keys are pressed one after the other and the editor goes back to normal mode
+    fn run_normal(&mut self, keys: &str) -> anyhow::Result<()> {
+        for key in parse_keys(keys) {
+            if let Some(action) = self.handle_action(Event::Key(key))? {
+                if matches!(
+                    action,
+                    Action::Quit | Action::ForceQuit | Action::QuitAll(_)
+                ) {
+                    continue;
+                }
+                self.run_action(action)?;
+            }
+        }
+        self.pending_operator = None;
+        self.modal = None;
+        if self.mode != Mode::Normal {
+            self.run_action(Action::EnterMode(Mode::Normal))?;
+        }
+        Ok(())
+    }
+
+    // the errors of the command are shown in a toast
+    pub fn run_ex(&mut self, ex: &Ex) -> anyhow::Result<()> {
+        match self.try_run_ex(ex) {
+            Err(err) => match err.downcast::<ExError>() {
+                Ok(err) => {
+                    self.toast.error(err.to_string());
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            },
+            result => result,
+        }
+    }
+
+    fn try_run_ex(&mut self, ex: &Ex) -> anyhow::Result<()> {
+        let (mut start, mut end) = self.resolve_range(&ex.range, ExRange::current())?;
+        if let Some(count) = ex.count {
+            start = end - 1;
+            end = (start + count.max(1)).min(self.buffer_len());
+        }
+        let modifies = !matches!(
+            ex.command,
+            ExCommand::Goto | ExCommand::Yank | ExCommand::Mark(_)
+        );
+        if modifies && !self.is_viewport_modifiable() {
+            return Ok(());
+        }
+        let lines = self.viewports.c_viewport().buffer.borrow().lines[start..end].to_vec();
+
+        match &ex.command {
+            ExCommand::Goto => self.goto_line(end - 1)?,
+            ExCommand::Delete => {
+                // the register is not filled again for each line of a :g
+                if self.global_marks.is_none() {
+                    copy_to_clipboard(&lines.join("\n"));
+                }
+                self.edit_lines(start, end - start, vec![]);
+                self.goto_line(start)?;
+                if lines.len() > 2 {
+                    self.toast
+                        .indication(format!("{} fewer lines", lines.len()));
+                }
+            }
+            ExCommand::Yank => {
+                copy_to_clipboard(&lines.join("\n"));
+                if lines.len() > 2 {
+                    self.toast
+                        .indication(format!("{} lines yanked", lines.len()));
+                }
+            }
+            ExCommand::Move(address) => {
+                let to = self.resolve_address(address)?;
+                if to > start && to < end {
+                    return Err(ExError::MoveIntoItself.into());
+                }
+                if to == start || to == end {
+                    self.goto_line(end - 1)?;
+                    return Ok(());
+                }
+                let (first, last) = (start.min(to), end.max(to));
+                let mut moved = self.viewports.c_viewport().buffer.borrow().lines.clone();
+                move_lines(&mut moved, (start, end), to);
+                let marks = self.global_marks.take().map(|mut marks| {
+                    move_lines(&mut marks, (start, end), to);
+                    marks
+                });
+                self.replace_buffer_lines(first, last - first, moved[first..last].to_vec());
+                self.global_marks = marks;
+                let last_moved = match to > end {
+                    true => to - 1,
+                    false => to + lines.len() - 1,
+                };
+                self.goto_line(last_moved)?;
+            }
+            ExCommand::Copy(address) => {
+                let to = self.resolve_address(address)?;
+                let len = lines.len();
+                self.edit_lines(to, 0, lines);
+                self.goto_line(to + len - 1)?;
+            }
+            ExCommand::Shift(right, shifts) => {
+                let shiftwidth = self.option_number("shiftwidth");
+                let shifted = lines
+                    .iter()
+                    .map(|l| shift_line(l, *right, *shifts, shiftwidth))
+                    .collect();
+                self.edit_lines(start, end - start, shifted);
+                self.goto_line(end - 1)?;
+            }
+            ExCommand::Normal(keys) if ex.range.is_none() && ex.count.is_none() => {
+                self.run_normal(keys)?;
+            }
+            // each line of the range is done like with :g
+            ExCommand::Normal(keys) => {
+                let mut marks = vec![false; self.buffer_len()];
+                marks[start..end].fill(true);
+                let action = Action::Ex(Ex::new(None, ExCommand::Normal(keys.clone())));
+                self.run_marked(marks, action)?;
+            }
+            ExCommand::Mark(mark) => {
+                self.viewports
+                    .c_mut_viewport()
+                    .buffer
+                    .borrow_mut()
+                    .marks
+                    .insert(*mark, end - 1);
+            }
+            ExCommand::Filter(cmd) => {
+                let filtered = shell_lines(cmd, Some(lines))?;
+                let len = filtered.len();
+                self.edit_lines(start, end - start, filtered);
+                self.goto_line(start)?;
+                if len > 2 {
+                    self.toast.indication(format!("{len} lines filtered"));
+                }
+            }
+            ExCommand::ReadFile(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|_| ExError::CantReadFile(path.clone()))?;
+                self.read_lines(end, text.lines().map(|l| l.to_string()).collect())?;
+            }
+            ExCommand::ReadShell(cmd) => self.read_lines(end, shell_lines(cmd, None)?)?,
+        }
+        Ok(())
+    }
+
+    // the lines of :r go below the line y and the cursor on the first one
+    fn read_lines(&mut self, y: usize, lines: Vec<String>) -> anyhow::Result<()> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        self.edit_lines(y, 0, lines);
+        self.goto_line(y)
+    }
+
+    pub fn set_mark(&mut self, mark: char) {
+        let y = self.v_cursor().1 as usize;
+        self.viewports
+            .c_mut_viewport()
+            .buffer
+            .borrow_mut()
+            .marks
+            .insert(mark, y);
+    }
+
+    // every marked line is visited once even when the action add, remove or move lines, and
+    // everything is undone at once
+    fn run_marked(&mut self, marks: Vec<bool>, action: Action) -> anyhow::Result<()> {
+        let viewport = self.viewports.c_viewport();
+        let old_lines = viewport.buffer.borrow().lines.clone();
+        let old_cursor = OldCursorPosition::new(self.cursor, viewport.top);
+        let undo_len = self.undo_actions.len();
+        let toast_len = self.toast.len();
+        self.global_marks = Some(marks);
+        let mut result = Ok(());
+        while let Some(y) = self
+            .global_marks
+            .as_ref()
+            .and_then(|marks| marks.iter().position(|m| *m))
+        {
+            if let Some(marks) = self.global_marks.as_mut() {
+                marks[y] = false;
+            }
+            result = self
+                .goto_line(y)
+                .and_then(|_| self.run_action(action.clone()));
+            if result.is_err() {
+                break;
+            }
+            // the lines added without edit_lines are supposed to be below the cursor line and the
+            // removed ones to start at it, like o and dd
+            let len = self.buffer_len();
+            if let Some(marks) = self.global_marks.as_mut() {
+                match len.cmp(&marks.len()) {
+                    std::cmp::Ordering::Greater => {
+                        let y = (y + 1).min(marks.len());
+                        marks.splice(y..y, vec![false; len - marks.len()]);
+                    }
+                    std::cmp::Ordering::Less => {
+                        let end = (y + marks.len() - len).min(marks.len());
+                        marks.drain(y..end);
+                    }
+                    std::cmp::Ordering::Equal => {}
+                }
+            }
+        }
+        self.global_marks = None;
+
+        self.undo_actions.truncate(undo_len);
+        self.toast.truncate(toast_len);
+        let new_len = self.buffer_len();
+        if self.viewports.c_viewport().buffer.borrow().lines != old_lines {
+            self.undo_actions
+                .push(Action::UndoLines(old_cursor, 0, old_lines, new_len));
+        }
+        result
+    }
+
+    pub fn run_global(&mut self, global: &Global) -> anyhow::Result<()> {
+        if !self.is_viewport_modifiable() {
+            return Ok(());
+        }
+        let pattern = match global.pattern.is_empty() {
+            true => self.search.clone(),
+            false => global.pattern.clone(),
+        };
+        if pattern.is_empty() {
+            self.toast
+                .error("E35: No previous regular expression".to_string());
+            return Ok(());
+        }
+        let action = match Command::execute(&global.command, &self.user_commands) {
+            Ok(Action::Global(_)) => {
+                let err = "E147: Cannot do :global recursive";
+                self.toast.error(err.to_string());
+                return Ok(());
+            }
+            Ok(action) => action,
+            Err(err) => {
+                self.toast.error(err.to_string());
+                return Ok(());
+            }
+        };
+        let (start, end) = match self.resolve_range(&global.range, ExRange::all()) {
+            Ok(range) => range,
+            Err(err) => {
+                self.toast.error(err.to_string());
+                return Ok(());
+            }
+        };
+
+        let regex = search_regex(&pattern, self.search_case());
+        let marks: Vec<bool> = (self
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .lines
+            .iter()
+            .enumerate())
+        .map(|(y, line)| (start..end).contains(&y) && regex.is_match(line) != global.inverse)
+        .collect();
+        let count = marks.iter().filter(|m| **m).count();
+        if count == 0 {
+            self.toast
+                .error(format!("E486: Pattern not found: {pattern}"));
+            return Ok(());
+        }
+        let result = self.run_marked(marks, action);
+        self.toast
+            .indication(format!("{} run on {count} lines", global.command));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests_ex {
+    use super::*;
+    use crate::editor::{
+        core::user_command::UserCommands,
+        test_helpers::{editor_with, lines, run},
+    };
+
+    #[test]
+    fn test_parse_range() {
+        let parse = |range| ExRange::parse_prefix(range).unwrap();
+        assert_eq!(parse("%d"), (Some(ExRange::all()), "d"));
+        let range = ExRange {
+            start: Address::Current(0),
+            end: Address::Last(-1),
+        };
+        assert_eq!(parse(".,$-1y"), (Some(range), "y"));
+        let range = ExRange {
+            start: Address::Mark('a', 1),
+            end: Address::Line(12),
+        };
+        assert_eq!(parse("'a+,10+2>"), (Some(range), ">"));
+        assert_eq!(parse(",+2").0.unwrap().end, Address::Current(2));
+        assert_eq!(parse("s/a/b/"), (None, "s/a/b/"));
+        assert_eq!(Address::parse("$-1"), Ok(Address::Last(-1)));
+        assert_eq!(Address::parse("x"), Err(ExError::InvalidAddress));
+
+        let keys = parse_keys("A;<Esc>");
+        assert_eq!(
+            keys[0],
+            KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(keys[2], KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_range_commands() {
+        let mut editor = editor_with(&["1", "2", "3", "4", "5"]);
+        run(&mut editor, "2,3m$");
+        assert_eq!(lines(&editor), vec!["1", "4", "5", "2", "3"]);
+        assert_eq!(editor.v_cursor().1, 4);
+        run(&mut editor, "4,5m0");
+        assert_eq!(lines(&editor), vec!["2", "3", "1", "4", "5"]);
+        run(&mut editor, "1t.");
+        assert_eq!(lines(&editor), vec!["2", "3", "2", "1", "4", "5"]);
+        run(&mut editor, "2,$-1d");
+        assert_eq!(lines(&editor), vec!["2", "5"]);
+        run(&mut editor, "%>");
+        run(&mut editor, "1>>");
+        assert_eq!(lines(&editor), vec!["            2", "    5"]);
+        run(&mut editor, "1< 2");
+        assert_eq!(lines(&editor), vec!["        2", "5"]);
+
+        Action::Undo.execute(&mut editor).unwrap();
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["    2", "    5"]);
+
+        // a range can not be moved inside itself
+        run(&mut editor, "1,2m1");
+        assert_eq!(lines(&editor), vec!["    2", "    5"]);
+        assert!(!editor.toast.is_empty());
+    }
+
+    #[test]
+    fn test_delete_every_line_and_undo() {
+        let mut editor = editor_with(&["a", "b"]);
+        run(&mut editor, "%d");
+        assert_eq!(lines(&editor), vec![""]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a", "b"]);
+
+        run(&mut editor, "1,$d");
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_marks_and_normal_range() {
+        let mut editor = editor_with(&["a", "b", "c", "d"]);
+        editor.cursor = (0, 1);
+        run(&mut editor, "mark x");
+        run(&mut editor, "3ma y");
+        run(&mut editor, "'x,'ynormal $a;");
+        assert_eq!(lines(&editor), vec!["a", "b;", "c;", "d"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a", "b", "c", "d"]);
+        assert!(Command::execute("'q,$d", &UserCommands::default()).is_ok());
+        run(&mut editor, "'q,$d");
+        assert_eq!(lines(&editor).len(), 4);
+    }
+
+    #[test]
+    fn test_marks_follow_their_lines() {
+        let mut editor = editor_with(&["a", "b", "c", "d", "e"]);
+        run(&mut editor, "3ma x");
+        run(&mut editor, "5ma y");
+        run(&mut editor, "1d");
+        run(&mut editor, "1r !echo z");
+        run(&mut editor, "'x,'ys/$/;/");
+        assert_eq!(lines(&editor), vec!["b", "z", "c;", "d;", "e;"]);
+
+        // the mark of a deleted line is deleted
+        run(&mut editor, "'xd");
+        assert_eq!(
+            editor.resolve_address(&Address::Mark('x', 0)),
+            Err(ExError::MarkNotSet)
+        );
+        run(&mut editor, "'yd");
+        assert_eq!(lines(&editor), vec!["b", "z", "d;"]);
+    }
+
+    #[test]
+    fn test_global_delete_and_undo() {
+        let mut editor = editor_with(&["INFO a", "DEBUG b", "DEBUG c", "INFO d", "DEBUG e"]);
+        run(&mut editor, "g/DEBUG/d");
+        assert_eq!(lines(&editor), vec!["INFO a", "INFO d"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor).len(), 5);
+
+        run(&mut editor, "v/INFO/d");
+        assert_eq!(lines(&editor), vec!["INFO a", "INFO d"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        run(&mut editor, "1,3g!/INFO/d");
+        assert_eq!(lines(&editor), vec!["INFO a", "INFO d", "DEBUG e"]);
+    }
+
+    #[test]
+    fn test_global_move_copy_and_substitute() {
+        let mut editor = editor_with(&["1", "2", "3"]);
+        run(&mut editor, "g/^/m0");
+        assert_eq!(lines(&editor), vec!["3", "2", "1"]);
+
+        let mut editor = editor_with(&["a,1", "b", "c,2"]);
+        run(&mut editor, r"g/,/t$");
+        assert_eq!(lines(&editor), vec!["a,1", "b", "c,2", "a,1", "c,2"]);
+        run(&mut editor, r"g/[ab]/s/\w/x/");
+        assert_eq!(lines(&editor), vec!["x,1", "x", "c,2", "x,1", "c,2"]);
+        // one undo for the whole :g
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a,1", "b", "c,2", "a,1", "c,2"]);
+    }
+
+    #[test]
+    fn test_global_normal() {
+        let mut editor = editor_with(&["a", "b", "a"]);
+        run(&mut editor, "g/a/normal $a;");
+        assert_eq!(lines(&editor), vec!["a;", "b", "a;"]);
+        assert_eq!(editor.mode, Mode::Normal);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a", "b", "a"]);
+    }
+
+    #[test]
+    fn test_global_normal_delete() {
+        let mut editor = editor_with(&["x1", "x2", "x3", "y", "x4"]);
+        run(&mut editor, "g/x/normal dd");
+        assert_eq!(lines(&editor), vec!["y"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["x1", "x2", "x3", "y", "x4"]);
+
+        let mut editor = editor_with(&["a", "b"]);
+        run(&mut editor, "%normal dd");
+        assert!(lines(&editor).is_empty());
+    }
+}
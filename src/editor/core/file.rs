@@ -0,0 +1,287 @@
+use std::{fs::File, io::Write, path::Path};
+
+use crate::{buff::Buffer, editor::Editor};
+
+use super::{actions::action::Action, ex::ExError};
+
+// the arguments of :w, :saveas, :wq and :x
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileWrite {
+    pub path: Option<String>,
+    pub force: bool,       // overwrite another file which exists
+    pub rename: bool,      // :saveas, the buffer takes the name of the file
+    pub parents: bool,     // ++p, the missing directories are created
+    pub quit: bool,        // :wq and :x
+    pub if_modified: bool, // :x only writes when there are changes
+}
+
+impl FileWrite {
+    // the path and the ++p of the arguments
+    pub fn parse(args: &str) -> Self {
+        let mut write = Self::default();
+        let mut path = vec![];
+        for arg in args.split_whitespace() {
+            match arg {
+                "++p" => write.parents = true,
+                arg => path.push(arg),
+            }
+        }
+        write.path = (!path.is_empty()).then(|| path.join(" "));
+        write
+    }
+}
+
+impl<W: Write> Editor<W> {
+    // write the buffer of the current viewport to its file or to another one, a buffer without
+    // name takes the name of the file it is written to
+    pub fn write_file(&mut self, write: &FileWrite) -> anyhow::Result<()> {
+        let viewport = self.viewports.c_mut_viewport();
+        if viewport.is_file_explorer() || !viewport.modifiable {
+            return Err(ExError::NotAFile.into());
+        }
+        let buffer = &mut viewport.buffer.borrow_mut();
+        let Some(path) = write.path.clone().or_else(|| buffer.path.clone()) else {
+            return Err(ExError::NoFileName.into());
+        };
+        let other_file = buffer.path.as_ref() != Some(&path);
+        if other_file && !write.force && Path::new(&path).exists() {
+            return Err(ExError::FileExists.into());
+        }
+
+        let written = !write.if_modified || other_file || buffer.is_modified();
+        if written {
+            buffer
+                .write_to(&path, write.parents)
+                .map_err(|_| ExError::CantOpenFile(path.clone()))?;
+        }
+        if other_file && (write.rename || buffer.path.is_none()) {
+            buffer.path = Some(path.clone());
+            buffer.file = File::open(&path).ok();
+            buffer.set_query_language(viewport.languages);
+        }
+        // the copy written to another file doesnt save the buffer
+        if written && buffer.path.as_ref() == Some(&path) {
+            buffer.mark_saved();
+        }
+        if written {
+            self.toast.indication(format!("file: {path} is saved"));
+        }
+        if write.quit {
+            self.buffer_actions.push(Action::Quit);
+        }
+        Ok(())
+    }
+
+    // :wa write every modified file
+    pub fn save_all(&mut self) -> anyhow::Result<()> {
+        let mut saved = 0;
+        // the buffer list has each buffer once, with the hidden ones
+        let viewports = &self.viewports;
+        for (_, buffer) in viewports
+            .buffers
+            .iter()
+            .filter(|(id, _)| viewports.is_modified(*id))
+        {
+            let buffer = &mut buffer.borrow_mut();
+            buffer
+                .save()
+                .map_err(|err| match err.downcast::<ExError>() {
+                    Ok(err) => err,
+                    Err(_) => ExError::CantOpenFile(buffer.name().to_string()),
+                })?;
+            saved += 1;
+        }
+        self.toast.indication(format!("{saved} files saved"));
+        Ok(())
+    }
+
+    // :e path open the file in its viewport or a new one, :e reload the current file and :e!
+    // drop its changes
+    pub fn edit_file(&mut self, path: Option<&str>, force: bool) -> anyhow::Result<()> {
+        let current = self.viewports.c_viewport().buffer.borrow().path.clone();
+        if let Some(path) = path.filter(|path| Some(*path) != current.as_deref()) {
+            let is_open = self.viewports.find_buffer(path).is_some();
+            self.viewports.is_explorer = false;
+            self.open_file(path.to_string());
+            self.reset_cursor();
+            // :e! on a file which is already open reload it
+            if !(is_open && force) {
+                return Ok(());
+            }
+        }
+
+        let viewport = self.viewports.c_mut_viewport();
+        if viewport.buffer.borrow().is_directory || !viewport.modifiable {
+            return Ok(());
+        }
+        let Some(path) = viewport.buffer.borrow().path.clone() else {
+            return Err(ExError::NoFileName.into());
+        };
+        if !force && viewport.buffer.borrow().is_modified() {
+            return Err(ExError::NoWriteSinceLastChange.into());
+        }
+        let old_len = viewport.buffer.borrow().lines.len();
+        // the reload can be undone like any other change
+        self.replace_buffer_lines(0, old_len, Buffer::new(Some(path.clone())).lines);
+        self.viewports.c_viewport().buffer.borrow_mut().mark_saved();
+        self.check_bounds();
+        self.toast.indication(format!("file: {path} is reloaded"));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_file {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::editor::{
+        core::{command::Command, user_command::UserCommands},
+        test_helpers::run,
+    };
+
+    fn path(dir: &TempDir, name: &str) -> String {
+        dir.path().join(name).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_file_commands() {
+        let write = |command| match Command::execute(command, &UserCommands::default()) {
+            Ok(Action::WriteFile(write)) => write,
+            action => panic!("{command} should write a file, not {action:?}"),
+        };
+        assert_eq!(
+            Command::execute("w", &UserCommands::default()),
+            Ok(Action::Save)
+        );
+        assert_eq!(write("w ++p a/b.txt").path.as_deref(), Some("a/b.txt"));
+        assert!(write("w ++p a/b.txt").parents);
+        assert!(write("sav! b.txt").rename && write("sav! b.txt").force);
+        assert!(write("x").quit && write("x").if_modified);
+        assert!(write("wq").quit && !write("wq").if_modified);
+        assert_eq!(
+            Command::execute("wa", &UserCommands::default()),
+            Ok(Action::SaveAll)
+        );
+        assert_eq!(
+            Command::execute("qa!", &UserCommands::default()),
+            Ok(Action::QuitAll(true))
+        );
+        assert_eq!(
+            Command::execute("e! src/main.rs", &UserCommands::default()),
+            Ok(Action::Edit(Some("src/main.rs".to_string()), true))
+        );
+        assert_eq!(
+            Command::execute("saveas", &UserCommands::default()),
+            Err(ExError::ArgumentRequired)
+        );
+    }
+
+    #[test]
+    fn test_write_empty_buffer() {
+        let dir = TempDir::new().unwrap();
+        let mut editor = Editor::default();
+        editor
+            .viewports
+            .c_mut_viewport()
+            .buffer
+            .borrow_mut()
+            .replace_lines(0..1, vec!["hello".to_string()]);
+        assert_eq!(editor.viewports.first_modified(), Some(1));
+
+        run(&mut editor, "w");
+        assert_eq!(editor.toast._last_message(), Some("E32: No file name"));
+
+        // the missing directories are only created with ++p
+        let file = path(&dir, "new/file.txt");
+        run(&mut editor, &format!("w {file}"));
+        assert!(editor.toast._last_message().unwrap().starts_with("E212"));
+        run(&mut editor, &format!("w ++p {file}"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hello\n");
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().name(), file);
+        assert_eq!(editor.viewports.first_modified(), None);
+
+        // another file isnt overwritten without !
+        run(&mut editor, &format!("w {}", path(&dir, "new")));
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E13: File exists (add ! to override)")
+        );
+    }
+
+    #[test]
+    fn test_saveas_write_copy_and_quit() {
+        let dir = TempDir::new().unwrap();
+        let (first, copy, second) = (
+            path(&dir, "a.txt"),
+            path(&dir, "b.txt"),
+            path(&dir, "c.txt"),
+        );
+        fs::write(&first, "a\n").unwrap();
+        let mut editor = Editor::default();
+        editor.edit_file(Some(&first), false).unwrap();
+        editor
+            .viewports
+            .c_mut_viewport()
+            .buffer
+            .borrow_mut()
+            .replace_lines(0..1, vec!["b".to_string()]);
+
+        // :w path keeps the name of the buffer and :saveas changes it
+        run(&mut editor, &format!("w {copy}"));
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().name(), first);
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "b\n");
+        run(&mut editor, &format!("saveas {second}"));
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().name(), second);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "a\n");
+
+        // :x quits without writing when nothing changed
+        editor.toast.truncate(0);
+        run(&mut editor, "x");
+        assert!(editor.toast.is_empty());
+        assert!(editor.quit);
+    }
+
+    #[test]
+    fn test_edit_and_revert() {
+        let dir = TempDir::new().unwrap();
+        let file = path(&dir, "a.txt");
+        fs::write(&file, "a\nb\n").unwrap();
+        let mut editor = Editor::default();
+        run(&mut editor, &format!("e {file}"));
+        assert_eq!(editor.viewports.values.len(), 2);
+        run(&mut editor, &format!("e {file}"));
+        assert_eq!(editor.viewports.values.len(), 2, "the viewport is reused");
+
+        editor
+            .viewports
+            .c_mut_viewport()
+            .buffer
+            .borrow_mut()
+            .replace_lines(0..2, vec!["c".to_string()]);
+        run(&mut editor, "e");
+        assert!(editor.toast._last_message().unwrap().starts_with("E37"));
+        run(&mut editor, "e!");
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().lines,
+            vec!["a", "b"]
+        );
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().lines,
+            vec!["c"]
+        );
+
+        // a new file is named by its path
+        let new_file = path(&dir, "new.txt");
+        run(&mut editor, &format!("e {new_file}"));
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            new_file
+        );
+        run(&mut editor, "wa");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "c\n");
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+use regex::Regex;
+
+use super::job::JobOutput;
+
+// a line of a .gitignore
+#[derive(Debug)]
+struct IgnoreRule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+// the rules of a .gitignore which apply to the paths under its directory
+#[derive(Debug)]
+pub struct Gitignore {
+    base: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+// translate a glob of a .gitignore to a regex, ** match any number of directories
+fn glob_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                match chars.peek() == Some(&'/') {
+                    true => {
+                        chars.next();
+                        regex.push_str("(.*/)?");
+                    }
+                    false => regex.push_str(".*"),
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    match c {
+                        ']' => break,
+                        '\\' | '[' | '&' | '~' => {
+                            regex.push('\\');
+                            regex.push(c);
+                        }
+                        c => regex.push(c),
+                    }
+                }
+                regex.push(']');
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    regex.push_str(&regex::escape(&c.to_string()));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        // a pattern with a / is relative to the .gitignore, otherwise it match at any depth
+        let prefix = match pattern.contains('/') {
+            true => "^",
+            false => "^(.*/)?",
+        };
+        let pattern = pattern.trim_start_matches('/');
+        let regex = Regex::new(&format!("{prefix}{}$", glob_regex(pattern))).ok()?;
+        Some(Self {
+            regex,
+            negated,
+            dir_only,
+        })
+    }
+}
+
+impl Gitignore {
+    pub fn new(base: &Path, content: &str) -> Self {
+        Self {
+            base: base.to_path_buf(),
+            rules: content.lines().filter_map(IgnoreRule::parse).collect(),
+        }
+    }
+
+    // Some(true) if the path is ignored, Some(false) if it is included again with a !
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.regex.is_match(&relative))
+            .map(|rule| !rule.negated)
+    }
+}
+
+// the deepest .gitignore with a rule for the path decides
+fn is_ignored(ignores: &[Gitignore], path: &Path, is_dir: bool) -> bool {
+    ignores
+        .iter()
+        .rev()
+        .find_map(|ignore| ignore.matched(path, is_dir))
+        .unwrap_or(false)
+}
+
+// visit the files of the tree which are not ignored, the walk stops when visit return false
+fn walk(dir: &Path, ignores: &mut Vec<Gitignore>, visit: &mut impl FnMut(&Path) -> bool) -> bool {
+    let gitignore = fs::read_to_string(dir.join(".gitignore")).ok();
+    if let Some(content) = &gitignore {
+        ignores.push(Gitignore::new(dir, content));
+    }
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().collect())
+        .unwrap_or_default();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut keep_going = true;
+    for entry in entries {
+        let path = entry.path();
+        // a symlink is not followed when it is a directory
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        if entry.file_name() == ".git" || is_ignored(ignores, &path, is_dir) {
+            continue;
+        }
+        keep_going = match is_dir {
+            true => walk(&path, ignores, visit),
+            false => visit(&path),
+        };
+        if !keep_going {
+            break;
+        }
+    }
+    if gitignore.is_some() {
+        ignores.pop();
+    }
+    keep_going
+}
+
+// the first match of each line as path:line:col: text, the binary files are skipped
+fn grep_file(path: &Path, regex: &Regex) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    if content.contains('\0') {
+        return vec![];
+    }
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let col = line[..regex.find(line)?.start()].chars().count();
+            let text = line.trim();
+            Some(format!("{}:{}:{}: {text}", path.display(), i + 1, col + 1))
+        })
+        .collect()
+}
+
+// search the tree in a thread, the matches are received while it is walked
+pub fn spawn_grep(root: PathBuf, regex: Regex) -> Receiver<JobOutput> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        walk(&root, &mut vec![], &mut |path| {
+            grep_file(path, &regex)
+                .into_iter()
+                .all(|result| sender.send(result.into()).is_ok())
+        });
+    });
+    receiver
+}
+
+// the file and the buffer position of a path:line:col: entry
+pub fn parse_result(line: &str) -> Option<(String, (u16, u16))> {
+    let mut parts = line.splitn(4, ':');
+    let path = parts.next()?.to_string();
+    let y = parts.next()?.trim().parse::<u16>().ok()?;
+    let x = parts.next()?.trim().parse::<u16>().unwrap_or(1);
+    Some((path, (x.saturating_sub(1), y.saturating_sub(1))))
+}
+
+#[cfg(test)]
+mod tests_grep {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_rules() {
+        let base = Path::new("/repo");
+        let ignore = Gitignore::new(
+            base,
+            "# comment\n*.log\n!keep.log\n/target\nbuild/\ndocs/**/*.md\nfile?.txt\n",
+        );
+        let matched = |path: &str, is_dir| ignore.matched(&base.join(path), is_dir);
+        assert_eq!(matched("a/b/out.log", false), Some(true));
+        assert_eq!(matched("a/keep.log", false), Some(false));
+        assert_eq!(matched("target", true), Some(true));
+        assert_eq!(matched("src/target", true), None);
+        assert_eq!(matched("src/build", true), Some(true));
+        assert_eq!(matched("build", false), None);
+        assert_eq!(matched("docs/a/b/c.md", false), Some(true));
+        assert_eq!(matched("docs/c.md", false), Some(true));
+        assert_eq!(matched("file1.txt", false), Some(true));
+        assert_eq!(matched("file10.txt", false), None);
+    }
+
+    #[test]
+    fn test_grep_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "*.tmp\n").unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    let café = foo();\n}",
+        )
+        .unwrap();
+        fs::write(root.join("src/a.tmp"), "foo").unwrap();
+        fs::write(root.join("target/out.rs"), "foo").unwrap();
+        fs::write(root.join("bin"), b"foo\0").unwrap();
+
+        let results: Vec<String> = spawn_grep(root.to_path_buf(), Regex::new("foo").unwrap())
+            .iter()
+            .filter_map(|output| match output {
+                JobOutput::Line(line) => Some(line),
+                _ => None,
+            })
+            .collect();
+        let main = root.join("src/main.rs");
+        assert_eq!(
+            results,
+            vec![format!("{}:2:16: let café = foo();", main.display())]
+        );
+        assert_eq!(
+            parse_result(&results[0]),
+            Some((main.display().to_string(), (15, 1)))
+        );
+    }
+}
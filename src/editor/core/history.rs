@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+
+// number of entries kept for the commands and for the searches
+const HISTORY_LEN: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryKind {
+    Command,
+    Search,
+}
+
+// the : commands and the / searches, the last entry is the most recent one
+#[derive(Debug, Default)]
+pub struct History {
+    commands: Vec<String>,
+    searches: Vec<String>,
+    path: Option<PathBuf>, // where it is saved, none for the editors of the tests
+    index: Option<usize>,  // entry shown by up and down
+    prefix: String,        // what was typed before going through the history
+}
+
+impl History {
+    // the file has one entry per line starting with : or /
+    pub fn load(path: PathBuf) -> Self {
+        let mut history = Self {
+            path: Some(path),
+            ..Self::default()
+        };
+        let content = history
+            .path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        for line in content.lines() {
+            match line.split_at_checked(1) {
+                Some((":", command)) => history.commands.push(command.to_string()),
+                Some(("/", search)) => history.searches.push(search.to_string()),
+                _ => {}
+            }
+        }
+        history
+    }
+
+    fn entries(&self, kind: HistoryKind) -> &Vec<String> {
+        match kind {
+            HistoryKind::Command => &self.commands,
+            HistoryKind::Search => &self.searches,
+        }
+    }
+
+    // an entry already in the history goes back to the end
+    pub fn push(&mut self, kind: HistoryKind, entry: &str) {
+        self.reset();
+        if entry.is_empty() {
+            return;
+        }
+        let entries = match kind {
+            HistoryKind::Command => &mut self.commands,
+            HistoryKind::Search => &mut self.searches,
+        };
+        entries.retain(|e| e != entry);
+        entries.push(entry.to_string());
+        if entries.len() > HISTORY_LEN {
+            entries.remove(0);
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let commands = self.commands.iter().map(|c| format!(":{c}\n"));
+        let searches = self.searches.iter().map(|s| format!("/{s}\n"));
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(path, commands.chain(searches).collect::<String>());
+    }
+
+    pub fn reset(&mut self) {
+        self.index = None;
+        self.prefix.clear();
+    }
+
+    // the older or newer entry starting with what was typed, going past the newest one gives
+    // back what was typed and None means there is nothing to show
+    pub fn navigate(&mut self, kind: HistoryKind, typed: &str, older: bool) -> Option<String> {
+        if self.index.is_none() {
+            self.prefix = typed.to_string();
+        }
+        let entries = self.entries(kind);
+        let matches = |i: &usize| entries[*i].starts_with(&self.prefix);
+        let next = match (older, self.index) {
+            (true, None) => (0..entries.len()).rev().find(matches),
+            (true, Some(i)) => (0..i).rev().find(matches),
+            (false, None) => return None,
+            (false, Some(i)) => (i + 1..entries.len()).find(matches),
+        };
+
+        match (next, older) {
+            (Some(i), _) => {
+                let entry = entries[i].clone();
+                self.index = Some(i);
+                Some(entry)
+            }
+            (None, true) => None,
+            (None, false) => {
+                self.index = None;
+                Some(self.prefix.clone())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_history {
+    use super::*;
+
+    #[test]
+    fn test_navigate_with_prefix() {
+        let mut history = History::default();
+        for command in ["w", "s/a/b/", "set nu", "s/c/d/", "w"] {
+            history.push(HistoryKind::Command, command);
+        }
+        let mut older = |typed| history.navigate(HistoryKind::Command, typed, true);
+        assert_eq!(older("s"), Some("s/c/d/".to_string()));
+        assert_eq!(older("s/c/d/"), Some("set nu".to_string()));
+        assert_eq!(older("set nu"), Some("s/a/b/".to_string()));
+        assert_eq!(older("s/a/b/"), None);
+
+        // down goes back to what was typed
+        let newer = history.navigate(HistoryKind::Command, "s/a/b/", false);
+        assert_eq!(newer, Some("set nu".to_string()));
+        history.navigate(HistoryKind::Command, "set nu", false);
+        let newer = history.navigate(HistoryKind::Command, "s/c/d/", false);
+        assert_eq!(newer, Some("s".to_string()));
+        assert!(history.navigate(HistoryKind::Search, "", true).is_none());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rusty/history");
+        let mut history = History::load(path.clone());
+        history.push(HistoryKind::Command, "w");
+        history.push(HistoryKind::Search, "fn \\w+");
+        history.push(HistoryKind::Command, "noh");
+
+        let mut history = History::load(path);
+        assert_eq!(history.commands, vec!["w", "noh"]);
+        assert_eq!(
+            history.navigate(HistoryKind::Search, "", true),
+            Some("fn \\w+".to_string())
+        );
+    }
+}
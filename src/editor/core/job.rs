@@ -0,0 +1,181 @@
+use std::{
+    io::Write,
+    sync::mpsc::{Receiver, TryRecvError},
+    time::Duration,
+};
+
+use crate::{
+    buff::Buffer,
+    editor::{Editor, TERMINAL_SIZE_MINUS},
+    viewport::Viewport,
+};
+
+use super::quickfix::{ListKind, QuickfixEntry, QuickfixList};
+
+// how long the editor waits for a key before reading the results again
+pub const JOB_POLL: Duration = Duration::from_millis(50);
+
+// turn the output of a job into the entries of the quickfix list
+pub type QuickfixParser = fn(&str) -> Vec<QuickfixEntry>;
+
+// the path:line:col: lines of :grep
+pub fn parse_results(output: &str) -> Vec<QuickfixEntry> {
+    output.lines().filter_map(QuickfixEntry::parse).collect()
+}
+
+// what a job sends, the lines are shown in its viewport and the entries go to the quickfix list
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobOutput {
+    Line(String),
+    Entry(QuickfixEntry),
+    Exit(i32), // the exit code of the command run by the job
+}
+
+impl From<String> for JobOutput {
+    fn from(line: String) -> Self {
+        JobOutput::Line(line)
+    }
+}
+
+// a command running in a thread whose lines are added to a results viewport
+#[derive(Debug)]
+pub struct ResultsJob {
+    receiver: Receiver<JobOutput>,
+    path: String, // path of the results buffer
+    lines: Vec<String>,
+    entries: Vec<QuickfixEntry>,
+    exit: Option<i32>,
+    parser: Option<QuickfixParser>, // fill the quickfix list when it is done
+}
+
+impl<W: Write> Editor<W> {
+    // show an empty results viewport named `path` and fill it with what the job sends
+    pub fn start_results_job(
+        &mut self,
+        path: String,
+        receiver: Receiver<JobOutput>,
+        parser: Option<QuickfixParser>,
+    ) {
+        let lines = vec![String::new()];
+        match self.viewports.find_buffer(&path) {
+            Some(id) => {
+                self.viewports.open(id);
+                let buffer = &self.viewports.c_viewport().buffer;
+                buffer.borrow_mut().replace_lines(0..usize::MAX, lines);
+            }
+            None => {
+                let viewport = Viewport::new(
+                    Buffer::new_tmp(lines, path.clone()),
+                    self.size.0,
+                    self.size.1 - TERMINAL_SIZE_MINUS,
+                    0,
+                    false,
+                );
+                let index = self.viewports.push(viewport);
+                self.viewports.show(index);
+            }
+        }
+        self.reset_cursor();
+        self.job = Some(ResultsJob {
+            receiver,
+            path,
+            lines: vec![],
+            entries: vec![],
+            exit: None,
+            parser,
+        });
+    }
+
+    // add the lines received since the last call, and tell how many there are when it is done
+    pub fn poll_job(&mut self) {
+        let Some(job) = self.job.as_mut() else {
+            return;
+        };
+        let mut viewport = self
+            .viewports
+            .values
+            .iter_mut()
+            .find(|v| v.buffer.borrow().path.as_ref() == Some(&job.path));
+        loop {
+            match job.receiver.try_recv() {
+                Ok(JobOutput::Line(line)) => {
+                    if let Some(viewport) = viewport.as_mut() {
+                        let mut buffer = viewport.buffer.borrow_mut();
+                        match job.lines.is_empty() {
+                            true => buffer.replace_lines(0..usize::MAX, vec![line.clone()]),
+                            false => {
+                                let len = buffer.lines.len();
+                                buffer.replace_lines(len..len, vec![line.clone()])
+                            }
+                        };
+                    }
+                    job.lines.push(line);
+                }
+                Ok(JobOutput::Entry(entry)) => job.entries.push(entry),
+                Ok(JobOutput::Exit(code)) => job.exit = Some(code),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        let Some(job) = self.job.take() else {
+            return;
+        };
+        let mut entries = job.entries;
+        if let Some(parser) = job.parser {
+            entries.extend(parser(&job.lines.join("\n")));
+        }
+        let count = match job.parser {
+            Some(_) => entries.len(),
+            None => job.lines.len(),
+        };
+        match (job.exit, count) {
+            (Some(code), _) if code != 0 => self
+                .toast
+                .error(format!("{}: {count} results, exit code {code}", job.path)),
+            (None, 0) => self.toast.error(format!("{}: no results", job.path)),
+            _ => self
+                .toast
+                .indication(format!("{}: {count} results", job.path)),
+        }
+        if job.parser.is_some() {
+            let list = QuickfixList::new(job.path, entries);
+            self.set_list(ListKind::Quickfix, list);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_job {
+    use std::{io::Cursor, sync::mpsc};
+
+    use super::*;
+    use crate::editor::core::actions::action::Action;
+
+    #[test]
+    fn test_results_job_and_open_result() -> anyhow::Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "one\ntwo\n  three")?;
+        let path = file.path().display().to_string();
+
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        let (sender, receiver) = mpsc::channel();
+        editor.start_results_job("[grep] t".to_string(), receiver, Some(parse_results));
+        sender.send(format!("{path}:2:1: two").into())?;
+        sender.send(format!("{path}:3:3: three").into())?;
+        editor.poll_job();
+        assert!(editor.job.is_some());
+        drop(sender);
+        editor.poll_job();
+        assert!(editor.job.is_none());
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().lines.len(), 2);
+        assert!(!editor.toast.is_empty());
+        assert_eq!(editor.quickfix.entries.len(), 2);
+        assert_eq!(editor.quickfix.entries[1].pos, (2, 2));
+
+        editor.cursor = (0, 1);
+        Action::OpenResult.execute(&mut editor)?;
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().name(), path);
+        assert_eq!(editor.v_cursor(), (2, 2));
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 
 use crate::helper::lua_handler::get_home_file;
 
-use super::{actions::action::Action, mode::Mode};
+use super::{actions::action::Action, mode::Mode, options::Options};
 use crossterm::event::{KeyCode, KeyModifiers};
 use mlua::{Lua, Table};
 
@@ -21,17 +21,14 @@ impl KeyAction {
         Self { action, desc }
     }
 }
-type Closure = dyn FnMut((&str, &(u16, u16))) -> Action;
 pub enum ActionOrClosure {
     Static(Action),
-    Dynamic(Box<Closure>),
 }
 impl PartialEq for ActionOrClosure {
     fn eq(&self, other: &Self) -> bool {
         // partial eq only for static because we dont need it on dyn ( closure )
         match (self, other) {
             (Self::Static(l0), Self::Static(r0)) => l0 == r0,
-            _ => false,
         }
     }
 }
@@ -41,10 +38,6 @@ impl fmt::Debug for ActionOrClosure {
             ActionOrClosure::Static(action) => {
                 write!(f, "Static({:?})", action)
             }
-            ActionOrClosure::Dynamic(_) => {
-                // Here you just print a placeholder because you cannot directly debug a closure
-                write!(f, "Dynamic(<closure>)")
-            }
         }
     }
 }
@@ -66,10 +59,17 @@ impl KeybindManagerV2 {
             keybinds: HashMap::new(),
             last_pressed: Vec::new(),
             leader_pressed: false,
-            double_tap_threshold: Duration::from_millis(1000),
+            double_tap_threshold: Duration::from_millis(
+                Options::from_config(None).number("timeoutlen") as u64,
+            ),
         }
     }
 
+    // :set timeoutlen, the time to type the next key of a sequence
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.double_tap_threshold = timeout;
+    }
+
     pub fn load_user_keybinds(&mut self) -> mlua::Result<()> {
         let lua = Lua::new();
         // Charger le fichier Lua
@@ -148,29 +148,9 @@ impl KeybindManagerV2 {
         Ok(())
     }
 
-    pub fn load_dyn_keybinds(&mut self) {
-        self.bind_key(
-            Mode::Command.to_string().to_lowercase(),
-            "Return",
-            "",
-            KeyAction::new(
-                ActionOrClosure::Dynamic(Box::new(move |(cmd, _): (&str, &(u16, u16))| {
-                    if cmd == "q" {
-                        return Action::Quit;
-                    } else if cmd == "q!" {
-                        return Action::ForceQuit;
-                    }
-                    Action::ExecuteCommand
-                })),
-                "Executes the entered command.".to_string(),
-            ),
-        );
-    }
-
     pub fn init_keybinds(&mut self) {
         self.load_default_lua_keybinds()
             .expect("Failed to load keybinds from lua");
-        self.load_dyn_keybinds();
         self.load_user_keybinds().unwrap();
     }
 
@@ -189,8 +169,6 @@ impl KeybindManagerV2 {
         mode: Mode,
         key: KeyCode,
         modifiers: KeyModifiers,
-        v_cursor: &(u16, u16),
-        cmd: &str,
         is_file_explorer: bool,
     ) -> Option<Action> {
         if mode == Mode::Normal && key.to_string() == LEADER {
@@ -204,8 +182,8 @@ impl KeybindManagerV2 {
         };
 
         let action = match self.leader_pressed {
-            true => self.handle_leader_keybinds(mode, key, modifiers, v_cursor, cmd),
-            false => self.handle_normal_keybinds(mode, key, modifiers, v_cursor, cmd),
+            true => self.handle_leader_keybinds(mode, key, modifiers),
+            false => self.handle_normal_keybinds(mode, key, modifiers),
         };
 
         if action.is_some() {
@@ -219,8 +197,6 @@ impl KeybindManagerV2 {
         mode: String,
         key: KeyCode,
         modifiers: KeyModifiers,
-        v_cursor: &(u16, u16),
-        cmd: &str,
     ) -> Option<Action> {
         let sequence = format!("<leader>{key}");
         let modifier = match modifiers.is_empty() {
@@ -234,9 +210,8 @@ impl KeybindManagerV2 {
         {
             Some(key_action) => match &mut key_action.action {
                 ActionOrClosure::Static(action) => Some(action.clone()),
-                ActionOrClosure::Dynamic(closure) => Some(closure((cmd, v_cursor))),
             },
-            None => self.handle_multiple_press(mode, key, modifiers, v_cursor, cmd),
+            None => self.handle_multiple_press(mode, key, modifiers),
         }
     }
 
@@ -251,8 +226,6 @@ impl KeybindManagerV2 {
         mode: String,
         key: KeyCode,
         modifiers: KeyModifiers,
-        v_cursor: &(u16, u16),
-        cmd: &str,
     ) -> Option<Action> {
         let modifier = match modifiers.is_empty() {
             true => "".to_string(),
@@ -265,7 +238,6 @@ impl KeybindManagerV2 {
         {
             Some(key_action) => match &mut key_action.action {
                 ActionOrClosure::Static(action) => Some(action.clone()),
-                ActionOrClosure::Dynamic(closure) => Some(closure((cmd, v_cursor))),
             },
             None => {
                 if let KeyCode::Char(c) = key {
@@ -279,7 +251,7 @@ impl KeybindManagerV2 {
                         return action;
                     }
                 };
-                self.handle_multiple_press(mode, key, modifiers, v_cursor, cmd)
+                self.handle_multiple_press(mode, key, modifiers)
             }
         }
     }
@@ -289,8 +261,6 @@ impl KeybindManagerV2 {
         mode: String,
         key: KeyCode,
         modifiers: KeyModifiers,
-        v_cursor: &(u16, u16),
-        cmd: &str,
     ) -> Option<Action> {
         let now = Instant::now();
         self.last_pressed.push((mode.clone(), key, modifiers, now));
@@ -316,7 +286,6 @@ impl KeybindManagerV2 {
             Some(key_action) => {
                 let action = match &mut key_action.action {
                     ActionOrClosure::Static(action) => Some(action.clone()),
-                    ActionOrClosure::Dynamic(closure) => Some(closure((cmd, v_cursor))),
                 };
                 return action;
             }
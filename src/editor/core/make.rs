@@ -0,0 +1,319 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use regex::{Captures, Regex};
+use serde_json::Value;
+
+use crate::{editor::Editor, helper::lua_handler::get_config_table_entry};
+
+use super::{
+    job::{JobOutput, QuickfixParser},
+    quickfix::{parse_compiler_output, QuickfixEntry},
+};
+
+const DEFAULT_MAKE: &str = "cargo build";
+
+// the cargo commands which can print their diagnostics as json
+const CARGO_JSON_COMMANDS: [&str; 11] = [
+    "build", "b", "check", "c", "test", "t", "run", "r", "clippy", "rustc", "bench",
+];
+
+// the patterns of make in config.lua, their named groups file, line, col, type and message make an
+// entry, a message without file waits for the location printed after it like with rustc and a
+// location without message waits for the message like in the python tracebacks
+#[derive(Debug, Default)]
+pub struct ErrorFormat {
+    patterns: Vec<Regex>,
+}
+
+// the file and the buffer position of the captures
+fn location(captures: &Captures) -> Option<(String, (u16, u16))> {
+    let path = captures.name("file")?.as_str().to_string();
+    let number = |name| {
+        captures
+            .name(name)
+            .and_then(|n| n.as_str().parse::<u16>().ok())
+            .unwrap_or(1)
+            .saturating_sub(1)
+    };
+    Some((path, (number("col"), number("line"))))
+}
+
+fn message(captures: &Captures) -> Option<String> {
+    let message = captures.name("message")?.as_str();
+    Some(match captures.name("type") {
+        Some(kind) => format!("{}: {message}", kind.as_str()),
+        None => message.to_string(),
+    })
+}
+
+impl ErrorFormat {
+    // the invalid patterns are skipped
+    pub fn load() -> Self {
+        let patterns =
+            get_config_table_entry::<Vec<String>>("make", "patterns").unwrap_or_default();
+        Self {
+            patterns: patterns.iter().filter_map(|p| Regex::new(p).ok()).collect(),
+        }
+    }
+
+    pub fn parse(&self, output: &str) -> Vec<QuickfixEntry> {
+        let mut entries = vec![];
+        let mut pending_message = None;
+        let mut pending_location = None;
+        for line in output.lines() {
+            let Some(captures) = self.patterns.iter().find_map(|p| p.captures(line)) else {
+                continue;
+            };
+            let (location, message) = match (location(&captures), message(&captures)) {
+                (Some(location), Some(message)) => (location, message),
+                (Some(location), None) => match pending_message.take() {
+                    Some(message) => (location, message),
+                    None => {
+                        pending_location = Some(location);
+                        continue;
+                    }
+                },
+                (None, Some(message)) => match pending_location.take() {
+                    Some(location) => (location, message),
+                    None => {
+                        pending_message = Some(message);
+                        continue;
+                    }
+                },
+                (None, None) => continue,
+            };
+            let (path, pos) = location;
+            entries.push(QuickfixEntry { path, pos, message });
+        }
+        entries
+    }
+}
+
+// the command of :make with its arguments, cargo prints its diagnostics as json when it can
+fn make_command(make: &str, args: &str) -> (String, bool) {
+    let command = format!("{make} {args}").trim().to_string();
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let json = matches!(words.as_slice(), ["cargo", sub, ..] if CARGO_JSON_COMMANDS.contains(sub))
+        && !command.contains("--message-format");
+    match json {
+        // the flag goes after the cargo command, before the arguments of a -- like cargo run -- x
+        true => {
+            let rest = words[2..].join(" ");
+            let command = format!("cargo {} --message-format=json {rest}", words[1]);
+            (command.trim().to_string(), true)
+        }
+        false => (command, false),
+    }
+}
+
+// a json line of cargo is shown like rustc prints it and its primary span is the entry, the
+// other messages like the built artifacts are hidden
+fn cargo_message(line: &str) -> Option<Vec<JobOutput>> {
+    let json: Value = serde_json::from_str(line).ok()?;
+    if !json.is_object() {
+        return None;
+    }
+    if json["reason"] != "compiler-message" {
+        return Some(vec![]);
+    }
+    let message = &json["message"];
+    let rendered = message["rendered"].as_str().unwrap_or_default();
+    let mut outputs: Vec<JobOutput> = rendered
+        .lines()
+        .map(|line| JobOutput::Line(line.to_string()))
+        .collect();
+    let spans = message["spans"].as_array()?;
+    if let Some(span) = spans.iter().find(|span| span["is_primary"] == true) {
+        let number = |key: &str| span[key].as_u64().unwrap_or(1).saturating_sub(1) as u16;
+        outputs.push(JobOutput::Entry(QuickfixEntry {
+            path: span["file_name"].as_str().unwrap_or_default().to_string(),
+            pos: (number("column_start"), number("line_start")),
+            message: format!(
+                "{}: {}",
+                message["level"].as_str().unwrap_or_default(),
+                message["message"].as_str().unwrap_or_default()
+            ),
+        }));
+    }
+    Some(outputs)
+}
+
+// the shell runs the command in its own process group, killing the group stops the compiler
+// and everything it started and not only the shell
+fn kill_make(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .args(["-TERM", "--", &format!("-{}", child.id())])
+        .status();
+    let _ = child.kill();
+}
+
+// run the command in a thread with its errors in its output, the lines are sent while it runs
+fn spawn_make(command: String, json: bool) -> Receiver<JobOutput> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut shell = Command::new("sh");
+        shell
+            .args(["-c", &format!("exec 2>&1; {command}")])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped());
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        let child = shell.spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                let _ = sender.send(JobOutput::Line(err.to_string()));
+                let _ = sender.send(JobOutput::Exit(-1));
+                return;
+            }
+        };
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let outputs = match json.then(|| cargo_message(&line)).flatten() {
+                    Some(outputs) => outputs,
+                    None => vec![JobOutput::Line(line)],
+                };
+                // the editor has started another job
+                if !outputs
+                    .into_iter()
+                    .all(|output| sender.send(output).is_ok())
+                {
+                    kill_make(&mut child);
+                    break;
+                }
+            }
+        }
+        let code = child.wait().ok().and_then(|s| s.code()).unwrap_or(-1);
+        let _ = sender.send(JobOutput::Exit(code));
+    });
+    receiver
+}
+
+impl<W: Write> Editor<W> {
+    // :make run the command of config.lua in the background, its errors fill the quickfix list
+    pub fn make(&mut self, args: &str) {
+        let make = get_config_table_entry::<String>("make", "command")
+            .unwrap_or_else(|| DEFAULT_MAKE.to_string());
+        let (command, json) = make_command(&make, args);
+        // the entries of the json messages are sent by the job, the rendered text isnt parsed
+        let parser: QuickfixParser = match json {
+            true => |_| vec![],
+            false => parse_compiler_output,
+        };
+        let receiver = spawn_make(command.clone(), json);
+        self.start_results_job(format!("[make] {command}"), receiver, Some(parser));
+    }
+}
+
+#[cfg(test)]
+mod tests_make {
+    use std::{io::Cursor, thread::sleep};
+
+    use super::*;
+    use crate::editor::core::job::JOB_POLL;
+
+    #[test]
+    fn test_make_command() {
+        assert_eq!(
+            make_command("cargo build", "--release"),
+            (
+                "cargo build --message-format=json --release".to_string(),
+                true
+            )
+        );
+        assert_eq!(
+            make_command("cargo", "run -- x"),
+            ("cargo run --message-format=json -- x".to_string(), true)
+        );
+        assert_eq!(
+            make_command("cargo fmt", ""),
+            ("cargo fmt".to_string(), false)
+        );
+        assert_eq!(make_command("make", "all"), ("make all".to_string(), false));
+    }
+
+    #[test]
+    fn test_error_formats() {
+        let output = r#"src/app.ts(3,7): error TS2322: Type 'string' is not assignable
+Traceback (most recent call last):
+  File "main.py", line 8, in <module>
+    run()
+  File "lib/run.py", line 2, in run
+    raise ValueError("bad")
+ValueError: bad
+main.c:4:5: warning: unused variable 'x'"#;
+        let displayed: Vec<String> = ErrorFormat::load()
+            .parse(output)
+            .iter()
+            .map(|e| e.display())
+            .collect();
+        assert_eq!(
+            displayed,
+            vec![
+                "src/app.ts:3:7: error: TS2322: Type 'string' is not assignable",
+                "lib/run.py:2:1: ValueError: bad",
+                "main.c:4:5: warning: unused variable 'x'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cargo_json_message() {
+        let line = r#"{"reason":"compiler-message","message":{"rendered":"error: oops\n --> src/a.rs:2:5\n","level":"error","message":"oops","spans":[{"file_name":"src/a.rs","line_start":2,"column_start":5,"is_primary":true}]}}"#;
+        let outputs = cargo_message(line).unwrap();
+        assert_eq!(outputs[0], JobOutput::Line("error: oops".to_string()));
+        assert_eq!(
+            outputs.last().and_then(|output| match output {
+                JobOutput::Entry(entry) => Some(entry.display()),
+                _ => None,
+            }),
+            Some("src/a.rs:2:5: error: oops".to_string())
+        );
+        assert_eq!(
+            cargo_message(r#"{"reason":"compiler-artifact"}"#),
+            Some(vec![])
+        );
+        assert_eq!(cargo_message("   Compiling rusty"), None);
+    }
+
+    #[test]
+    fn test_make_fills_the_quickfix_list() -> anyhow::Result<()> {
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        let receiver = spawn_make("echo 'a.c:1:2: error: x'; exit 2".to_string(), false);
+        editor.start_results_job("[make]".to_string(), receiver, Some(parse_compiler_output));
+        while editor.job.is_some() {
+            sleep(JOB_POLL);
+            editor.poll_job();
+        }
+        assert_eq!(editor.quickfix.entries.len(), 1);
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("[make]: 1 results, exit code 2")
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stopped_make_kills_its_children() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let done = dir.path().join("done");
+        let command = format!(
+            "echo a; (sleep 0.5; touch {}) & sleep 0.1; echo b; wait",
+            done.display()
+        );
+        let receiver = spawn_make(command, false);
+        assert!(matches!(receiver.recv()?, JobOutput::Line(line) if line == "a"));
+        // the next line cant be sent and the whole group is killed
+        drop(receiver);
+        sleep(std::time::Duration::from_millis(1000));
+        assert!(!done.exists());
+        Ok(())
+    }
+}
@@ -1,7 +1,26 @@
 pub mod actions;
+pub mod autopair;
+pub mod buffer_list;
 pub mod chartype;
+pub mod cmdline;
 pub mod command;
+pub mod completion;
 pub mod editor_handler;
+pub mod ex;
+pub mod file;
+pub mod grep;
+pub mod history;
+pub mod job;
 pub mod keybind_manager;
+pub mod make;
 pub mod mode;
-
+pub mod multi_cursor;
+pub mod operator;
+pub mod options;
+pub mod quickfix;
+pub mod shell;
+pub mod snippet;
+pub mod substitute;
+pub mod user_command;
+pub mod wildmenu;
+pub mod window;
@@ -0,0 +1,364 @@
+use std::io::Write;
+
+use crate::{buff::Pos, editor::Editor};
+
+use super::actions::action::Action;
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// move a cursor placed after an edit made by another cursor which went from `from` to `to`,
+// dx is the change of the line len when the edit stayed on the same line and dy the change
+// of the buffer len
+fn shift_cursor(cursor: Pos, from: Pos, to: Pos, dx: isize, dy: isize) -> Pos {
+    let (x, y) = cursor;
+    match y == from.1 {
+        true if dy != 0 && x >= from.0 => (x - from.0 + to.0, to.1),
+        true if x > from.0 => ((x as isize + dx).max(0) as u16, y),
+        false if y > from.1 => (x, (y as isize + dy).max(0) as u16),
+        _ => cursor,
+    }
+}
+
+// start of the whole word occurrences of word in the line
+fn word_matches(line: &str, word: &[char]) -> Vec<u16> {
+    let chars: Vec<char> = line.chars().collect();
+    (0..chars.len())
+        .filter(|i| chars[*i..].starts_with(word))
+        .filter(|i| *i == 0 || !is_word_char(chars[*i - 1]))
+        .filter(|i| chars.get(*i + word.len()).is_none_or(|c| !is_word_char(*c)))
+        .map(|i| i as u16)
+        .collect()
+}
+
+impl Action {
+    // the edits which are made at every cursor
+    pub fn is_multi_cursor_edit(&self) -> bool {
+        matches!(
+            self,
+            Action::AddChar(_)
+                | Action::AddStr(_)
+                | Action::NewLine
+                | Action::NewLineInsertionAtCursor
+                | Action::NewLineInsertionBelowCursor
+                | Action::RemoveChar
+                | Action::RemoveCharAt
+                | Action::DeleteLine
+                | Action::DeleteWord
+                | Action::DeleteBlock
+        )
+    }
+}
+
+impl<W: Write> Editor<W> {
+    pub fn has_multiple_cursors(&self) -> bool {
+        !self.cursors.is_empty()
+    }
+
+    // add a cursor and keep them sorted without the primary one
+    fn add_cursor(&mut self, pos: Pos) {
+        if pos != self.v_cursor() && !self.cursors.contains(&pos) {
+            self.cursors.push(pos);
+            self.cursors.sort_by_key(|(x, y)| (*y, *x));
+        }
+    }
+
+    // run the edit at each cursor from the last one in the buffer to the first one, like that
+    // the edit of a cursor never move the cursors not done yet
+    pub fn at_each_cursor(
+        &mut self,
+        edit: impl Fn(&mut Self) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.snippet = None;
+        let primary = self.v_cursor();
+        let (top, left) = {
+            let viewport = self.viewports.c_viewport();
+            (viewport.top, viewport.left)
+        };
+        let mut positions = self.cursors.clone();
+        positions.push(primary);
+        positions.sort_by_key(|(x, y)| (*y, *x));
+        positions.dedup();
+        // the other cursors stayed where the visual mode started, their selection goes as far
+        // as the one of the primary
+        let visual_cursor = self.visual_cursor;
+        let visual_start = visual_cursor.map(|(x, y)| (x + left, y + top));
+        let motion = visual_start.map(|(x, y)| {
+            (
+                primary.0 as isize - x as isize,
+                primary.1 as isize - y as isize,
+            )
+        });
+
+        let undo_len = self.undo_actions.len();
+        let actions_len = self.buffer_actions.len();
+        let mut done: Vec<(Pos, bool)> = vec![];
+        for pos in positions.into_iter().rev() {
+            let (buffer_len, line_len) = self.buffer_lens(pos.1);
+            let (cursor, visual) = match motion {
+                Some(_) if pos == primary => (pos, visual_start),
+                Some(motion) => (self.clamp_pos(pos, motion), Some(pos)),
+                None => (pos, None),
+            };
+            // the line above stays on the screen for the edits which go to the prev line
+            let first_line = visual.map_or(cursor.1, |(_, y)| y.min(cursor.1));
+            let viewport = self.viewports.c_mut_viewport();
+            viewport.top = first_line.saturating_sub(1);
+            viewport.left = 0;
+            self.cursor = (cursor.0, cursor.1 - viewport.top);
+            self.visual_cursor = visual.map(|(x, y)| (x, y - viewport.top));
+
+            edit(self)?;
+
+            let new_pos = self.v_cursor();
+            let (new_buffer_len, new_line_len) = self.buffer_lens(pos.1);
+            let dy = new_buffer_len as isize - buffer_len as isize;
+            let dx = new_line_len as isize - line_len as isize;
+            for (cursor, _) in done.iter_mut() {
+                *cursor = shift_cursor(*cursor, pos, new_pos, dx, dy);
+            }
+            done.push((new_pos, pos == primary));
+        }
+
+        let viewport = self.viewports.c_mut_viewport();
+        viewport.top = top;
+        viewport.left = left;
+        self.visual_cursor = visual_cursor;
+        // an action pushed by every edit like entering the insert mode is only done once
+        let pushed = self.buffer_actions.split_off(actions_len);
+        for action in pushed {
+            if !self.buffer_actions[actions_len..].contains(&action) {
+                self.buffer_actions.push(action);
+            }
+        }
+        // the edits of all the cursors are undone at once
+        if self.undo_actions.len() > undo_len + 1 {
+            let actions = self.undo_actions.split_off(undo_len);
+            self.undo_actions.push(Action::UndoMultiple(actions));
+        }
+
+        let primary = done.iter().find(|(_, is_primary)| *is_primary).map(|c| c.0);
+        self.cursors = done
+            .iter()
+            .filter(|(pos, is_primary)| !is_primary && Some(*pos) != primary)
+            .map(|(pos, _)| *pos)
+            .collect();
+        self.cursors.sort_by_key(|(x, y)| (*y, *x));
+        self.cursors.dedup();
+        if let Some(primary) = primary {
+            self.goto_buffer_pos(primary);
+        }
+        Ok(())
+    }
+
+    // the position at an offset of pos, kept in the buffer
+    fn clamp_pos(&self, pos: Pos, offset: (isize, isize)) -> Pos {
+        let buffer = &self.viewports.c_viewport().buffer.borrow();
+        let last = buffer.lines.len().saturating_sub(1) as isize;
+        let y = (pos.1 as isize + offset.1).clamp(0, last) as u16;
+        let line_len = buffer.get(y as usize).map_or(0, |l| l.chars().count()) as isize;
+        let x = (pos.0 as isize + offset.0).clamp(0, line_len.max(1) - 1) as u16;
+        (x, y)
+    }
+
+    fn buffer_lens(&self, y: u16) -> (usize, usize) {
+        let buffer = &self.viewports.c_viewport().buffer.borrow();
+        let line_len = buffer.get(y as usize).map(|l| l.chars().count());
+        (buffer.lines.len(), line_len.unwrap_or(0))
+    }
+
+    // the word under the cursor and its start
+    pub fn word_under_cursor(&self) -> Option<(String, Pos)> {
+        let v_cursor = self.v_cursor();
+        let buffer = &self.viewports.c_viewport().buffer.borrow();
+        let (start, end) = buffer
+            .text_object(v_cursor, false, 'w')
+            .filter(|_| buffer._get_char(&v_cursor).is_some_and(is_word_char))?;
+        let word = buffer.lines[start.1 as usize]
+            .chars()
+            .skip(start.0 as usize)
+            .take((end.0 - start.0) as usize)
+            .collect();
+        Some((word, start))
+    }
+
+    // add a cursor on the next occurrence of the word under the primary cursor, after the
+    // last cursor
+    pub fn add_cursor_next_match(&mut self) {
+        let v_cursor = self.v_cursor();
+        let Some((word, start)) = self.word_under_cursor() else {
+            self.toast.error("no word under the cursor".to_string());
+            return;
+        };
+        let word: Vec<char> = word.chars().collect();
+        let buffer = self.viewports.c_viewport().buffer.borrow();
+        let offset = v_cursor.0 - start.0;
+
+        // the matches after the last cursor then from the start of the buffer
+        let from = match self.cursors.last() {
+            Some(last) if (last.1, last.0) > (v_cursor.1, v_cursor.0) => *last,
+            _ => v_cursor,
+        };
+        let matches = buffer.lines.iter().enumerate().flat_map(|(y, line)| {
+            word_matches(line, &word)
+                .into_iter()
+                .map(move |x| (x + offset, y as u16))
+        });
+        let (after, before): (Vec<Pos>, Vec<Pos>) =
+            matches.partition(|(x, y)| (*y, *x) > (from.1, from.0));
+        let next = after
+            .into_iter()
+            .chain(before)
+            .find(|pos| *pos != v_cursor && !self.cursors.contains(pos));
+        drop(buffer);
+
+        match next {
+            Some(pos) => self.add_cursor(pos),
+            None => self.toast.error("no other match of the word".to_string()),
+        }
+    }
+
+    // add a cursor on the line above the first cursor or below the last one
+    pub fn add_cursor_vertical(&mut self, below: bool) {
+        let v_cursor = self.v_cursor();
+        let mut all = self.cursors.clone();
+        all.push(v_cursor);
+        all.sort_by_key(|(x, y)| (*y, *x));
+        let (x, y) = match below {
+            true => all[all.len() - 1],
+            false => all[0],
+        };
+        let buffer = self.viewports.c_viewport().buffer.borrow();
+        let y = match below {
+            true if (y as usize) + 1 < buffer.lines.len() => y + 1,
+            false if y > 0 => y - 1,
+            _ => return,
+        };
+        let line_len = buffer.lines[y as usize].chars().count() as u16;
+        drop(buffer);
+        self.add_cursor((x.min(line_len), y));
+    }
+
+    // one cursor per line of the visual selection at the column of the cursor
+    pub fn cursors_from_visual(&mut self) {
+        let Some(v_block) = self.get_visual_block_pos() else {
+            return;
+        };
+        let viewport = self.viewports.c_viewport();
+        let start = viewport.viewport_cursor(&v_block.start).1;
+        let end = viewport.viewport_cursor(&v_block.end).1;
+        let (x, _) = self.v_cursor();
+        let lines: Vec<(u16, u16)> = (start..=end)
+            .map(|y| {
+                let line_len = viewport.buffer.borrow().lines[y as usize].chars().count() as u16;
+                (x.min(line_len), y)
+            })
+            .collect();
+        for pos in lines {
+            self.add_cursor(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_multi_cursor {
+    use super::*;
+    use crate::editor::{
+        core::mode::Mode,
+        test_helpers::{editor_with, lines},
+    };
+
+    #[test]
+    fn test_add_cursor_next_match() {
+        let mut editor = editor_with(&["let foo = foo_bar;", "foo(foo);"]);
+        editor.cursor = (5, 0);
+        Action::AddCursorNextMatch.execute(&mut editor).unwrap();
+        Action::AddCursorNextMatch.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursors, vec![(1, 1), (5, 1)]);
+        // no more match
+        Action::AddCursorNextMatch.execute(&mut editor).unwrap();
+        assert_eq!(editor.cursors.len(), 2);
+    }
+
+    #[test]
+    fn test_insert_at_each_cursor() -> anyhow::Result<()> {
+        let mut editor = editor_with(&["ab", "ab", "ab"]);
+        editor.cursor = (1, 0);
+        Action::AddCursorBelow.execute(&mut editor)?;
+        Action::AddCursorBelow.execute(&mut editor)?;
+        assert_eq!(editor.cursors, vec![(1, 1), (1, 2)]);
+
+        editor.mode = Mode::Insert;
+        Action::AddChar('x').execute(&mut editor)?;
+        Action::AddChar('y').execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["axyb", "axyb", "axyb"]);
+        assert_eq!(editor.cursor, (3, 0));
+        assert_eq!(editor.cursors, vec![(3, 1), (3, 2)]);
+
+        Action::NewLine.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["axy", "b", "axy", "b", "axy", "b"]);
+        assert_eq!(editor.cursors, vec![(0, 3), (0, 5)]);
+
+        Action::RemoveChar.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["axyb", "axyb", "axyb"]);
+        assert_eq!(editor.cursors, vec![(3, 1), (3, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_line_cursors_and_undo() -> anyhow::Result<()> {
+        let mut editor = editor_with(&["xa xb xc"]);
+        editor.cursor = (0, 0);
+        editor.cursors = vec![(3, 0), (6, 0)];
+        Action::RemoveCharAt.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["a b c"]);
+        assert_eq!(editor.cursors, vec![(2, 0), (4, 0)]);
+
+        // the edits of all the cursors are a single undo
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["xa xb xc"]);
+
+        // esc collapse the cursors
+        Action::ClearToNormalMode.execute(&mut editor)?;
+        assert!(editor.cursors.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_lines_and_delete_blocks_at_each_cursor() -> anyhow::Result<()> {
+        let mut editor = editor_with(&["abcd", "abcd"]);
+        editor.cursor = (1, 0);
+        editor.cursors = vec![(1, 1)];
+        Action::NewLineInsertionBelowCursor.execute(&mut editor)?;
+        assert!(matches!(editor.mode, Mode::Insert));
+        assert!(editor.buffer_actions.is_empty());
+        Action::AddChar('x').execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["abcd", "x", "abcd", "x"]);
+        Action::ClearToNormalMode.execute(&mut editor)?;
+
+        // the selection of each cursor goes as far as the one of the primary
+        let mut editor = editor_with(&["abcd", "abcd", "abcd"]);
+        editor.cursor = (1, 0);
+        editor.cursors = vec![(1, 1), (1, 2)];
+        Action::EnterMode(Mode::Visual).execute(&mut editor)?;
+        editor.cursor = (2, 0);
+        Action::DeleteBlock.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["ad", "ad", "ad"]);
+        assert!(matches!(editor.mode, Mode::Normal));
+        assert_eq!(editor.cursors, vec![(1, 1), (1, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursors_from_visual() -> anyhow::Result<()> {
+        let mut editor = editor_with(&["one", "t", "three"]);
+        editor.cursor = (2, 0);
+        Action::EnterMode(Mode::Visual).execute(&mut editor)?;
+        editor.cursor = (2, 2);
+        Action::CursorsFromVisual.execute(&mut editor)?;
+        assert!(matches!(editor.mode, Mode::Normal));
+        assert_eq!(editor.cursors, vec![(2, 0), (1, 1)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+use std::io::Write;
+
+use crossterm::event::KeyCode;
+
+use crate::{
+    buff::{is_delimiter_object, Buffer, Pos},
+    editor::Editor,
+};
+
+use super::{actions::action::Action, window::window_command};
+
+// an operator wait for a motion to know on which part of the buffer it will be applied
+// like gc in gcj or gc}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Comment,
+    BlockComment,
+    SurroundAdd,
+    SurroundChange,
+    SurroundDelete,
+    SurroundVisual,
+    Mark,
+    Window,
+}
+
+impl From<&str> for Operator {
+    fn from(value: &str) -> Self {
+        match value {
+            "comment" => Operator::Comment,
+            "block_comment" => Operator::BlockComment,
+            "surround_add" => Operator::SurroundAdd,
+            "surround_change" => Operator::SurroundChange,
+            "surround_delete" => Operator::SurroundDelete,
+            "surround_visual" => Operator::SurroundVisual,
+            "mark" => Operator::Mark,
+            "window" => Operator::Window,
+            _ => panic!("Invalid Operator string: {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingOperator {
+    pub operator: Operator,
+    pub keys: String, // keys typed since the operator
+}
+
+impl PendingOperator {
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            keys: String::new(),
+        }
+    }
+}
+
+// result of the keys typed after an operator
+#[derive(Debug, PartialEq)]
+pub enum Parsed<T> {
+    Pending,
+    Invalid,
+    Done(T),
+}
+
+impl<T> Parsed<T> {
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Parsed<U> {
+        match self {
+            Parsed::Pending => Parsed::Pending,
+            Parsed::Invalid => Parsed::Invalid,
+            Parsed::Done(value) => Parsed::Done(f(value)),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Parsed<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Parsed::Done(value),
+            None => Parsed::Invalid,
+        }
+    }
+}
+
+// split the count from the motion: 3j => (3, "j")
+fn split_count(keys: &str) -> (u16, &str) {
+    let digits = keys
+        .char_indices()
+        .take_while(|(i, c)| c.is_ascii_digit() && !(*i == 0 && *c == '0'))
+        .count();
+    let count = keys[..digits].parse::<u16>().unwrap_or(1).max(1);
+    (count, &keys[digits..])
+}
+
+// find the lines covered by a motion starting on the line y
+pub fn line_range(keys: &str, buffer: &Buffer, y: u16, line_key: char) -> Parsed<(u16, u16)> {
+    let (count, motion) = split_count(keys);
+    let last = buffer.lines.len().saturating_sub(1) as u16;
+    let is_blank = |y: u16| buffer.lines[y as usize].trim().is_empty();
+
+    let (start, end) = match motion {
+        "" | "g" | "i" | "a" => return Parsed::Pending,
+        _ if motion.len() == 1 && motion.starts_with(line_key) => (y, y.saturating_add(count - 1)),
+        "j" => (y, y.saturating_add(count)),
+        "k" => (y.saturating_sub(count), y),
+        "G" => (y, last),
+        "gg" => (0, y),
+        "}" => (y, buffer.next_paragraph(y)),
+        "{" => (buffer.prev_paragraph(y), y),
+        "ip" | "ap" => {
+            let mut start = y;
+            while start > 0 && is_blank(start - 1) == is_blank(y) {
+                start -= 1;
+            }
+            let mut end = y;
+            while end < last && is_blank(end + 1) == is_blank(y) {
+                end += 1;
+            }
+            // ap take the blank lines after the paragraph too
+            if motion == "ap" && !is_blank(y) {
+                while end < last && is_blank(end + 1) {
+                    end += 1;
+                }
+            }
+            (start, end)
+        }
+        _ => return Parsed::Invalid,
+    };
+    Parsed::Done((start.min(end), end.min(last)))
+}
+
+// find the chars covered by a motion or a text object, the end is excluded
+pub fn char_range(motion: &str, buffer: &Buffer, pos: Pos, line_key: char) -> Parsed<(Pos, Pos)> {
+    let line = &buffer.lines[pos.1 as usize];
+    let len = line.chars().count() as u16;
+
+    match motion {
+        "" | "i" | "a" => Parsed::Pending,
+        _ if motion.len() == 1 && motion.starts_with(line_key) => {
+            let indent = (line.len() - line.trim_start().len()) as u16;
+            let end = line.trim_end().chars().count() as u16;
+            Parsed::Done(((indent, pos.1), (end.max(indent), pos.1)))
+        }
+        "w" | "e" => buffer
+            .text_object(pos, false, 'w')
+            .map(|(_, end)| (pos, end))
+            .into(),
+        "W" | "E" => buffer
+            .text_object(pos, false, 'W')
+            .map(|(_, end)| (pos, end))
+            .into(),
+        "$" => Parsed::Done((pos, (len, pos.1))),
+        "0" => Parsed::Done(((0, pos.1), pos)),
+        _ => {
+            let mut chars = motion.chars();
+            match (chars.next(), chars.next(), chars.next()) {
+                (Some(kind @ ('i' | 'a')), Some(obj), None) => {
+                    buffer.text_object(pos, kind == 'a', obj).into()
+                }
+                _ => Parsed::Invalid,
+            }
+        }
+    }
+}
+
+// the delimiters to add around a text, an open bracket add spaces inside like in vim-surround
+// and <tag attr="x"> is closed with </tag>
+pub fn surround_pair(keys: &str) -> Parsed<(String, String)> {
+    if let Some(tag) = keys.strip_prefix('<').filter(|tag| !tag.is_empty()) {
+        if !tag.ends_with('>') {
+            return Parsed::Pending;
+        }
+        let name: String = tag
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        return match name.is_empty() {
+            true => Parsed::Invalid,
+            false => Parsed::Done((keys.to_string(), format!("</{name}>"))),
+        };
+    }
+
+    let pair = |open: &str, close: &str| Parsed::Done((open.to_string(), close.to_string()));
+    match keys {
+        "" | "<" => Parsed::Pending,
+        "(" => pair("( ", " )"),
+        ")" | "b" => pair("(", ")"),
+        "[" => pair("[ ", " ]"),
+        "]" => pair("[", "]"),
+        "{" => pair("{ ", " }"),
+        "}" | "B" => pair("{", "}"),
+        ">" => pair("<", ">"),
+        _ if keys.len() == 1 && keys.chars().all(|c| c.is_ascii_punctuation()) => pair(keys, keys),
+        _ => Parsed::Invalid,
+    }
+}
+
+// ys{motion}{char}, the motion is complete as soon as char_range find a range
+fn surround_add(keys: &str, buffer: &Buffer, pos: Pos) -> Parsed<Action> {
+    for (i, c) in keys.char_indices() {
+        let end = i + c.len_utf8();
+        match char_range(&keys[..end], buffer, pos, 's') {
+            Parsed::Pending => continue,
+            Parsed::Invalid => return Parsed::Invalid,
+            Parsed::Done((start, stop)) => {
+                return surround_pair(&keys[end..])
+                    .map(|(open, close)| Action::SurroundAdd(start, stop, open, close))
+            }
+        }
+    }
+    Parsed::Pending
+}
+
+// cs{old}{new} and ds{old}
+fn surround_object(keys: &str) -> Parsed<(char, &str)> {
+    match keys.chars().next() {
+        None => Parsed::Pending,
+        Some(obj) if is_delimiter_object(obj) => Parsed::Done((obj, &keys[obj.len_utf8()..])),
+        Some(_) => Parsed::Invalid,
+    }
+}
+
+impl<W: Write> Editor<W> {
+    // every key is given to the operator until we know the range it applies to,
+    // any key that isnt a char cancel it
+    pub fn handle_pending_operator(&mut self, code: KeyCode) -> Option<Action> {
+        let mut pending = self.pending_operator.take()?;
+        let KeyCode::Char(c) = code else {
+            return None;
+        };
+        pending.keys.push(c);
+
+        let keys = pending.keys.as_str();
+        let pos = self.v_cursor();
+        let viewport = self.viewports.c_viewport();
+        let buffer = &viewport.buffer.borrow();
+        let parsed = match pending.operator {
+            Operator::Comment => line_range(keys, buffer, pos.1, 'c')
+                .map(|(start, end)| Action::ToggleComment(start, end)),
+            Operator::BlockComment => line_range(keys, buffer, pos.1, 'b')
+                .map(|(start, end)| Action::ToggleBlockComment(start, end)),
+            Operator::SurroundAdd => surround_add(keys, buffer, pos),
+            Operator::SurroundDelete => {
+                surround_object(keys).map(|(obj, _)| Action::SurroundDelete(obj))
+            }
+            Operator::SurroundChange => match surround_object(keys) {
+                Parsed::Done((obj, rest)) => surround_pair(rest)
+                    .map(|(open, close)| Action::SurroundChange(obj, open, close)),
+                Parsed::Pending => Parsed::Pending,
+                Parsed::Invalid => Parsed::Invalid,
+            },
+            Operator::SurroundVisual => match self.get_visual_block_pos() {
+                Some(v_block) => {
+                    let start = viewport.viewport_cursor(&v_block.start);
+                    let (x, y) = viewport.viewport_cursor(&v_block.end);
+                    surround_pair(keys)
+                        .map(|(open, close)| Action::SurroundAdd(start, (x + 1, y), open, close))
+                }
+                None => Parsed::Invalid,
+            },
+            Operator::Mark => match c.is_ascii_lowercase() {
+                true => Parsed::Done(Action::SetMark(c)),
+                false => Parsed::Invalid,
+            },
+            Operator::Window => window_command(keys),
+        };
+
+        match parsed {
+            Parsed::Pending => {
+                self.pending_operator = Some(pending);
+                None
+            }
+            Parsed::Invalid => None,
+            Parsed::Done(action) => Some(action),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_operator {
+    use super::*;
+
+    fn buffer(lines: Vec<&str>) -> Buffer {
+        Buffer::new_tmp(
+            lines.into_iter().map(|l| l.to_string()).collect(),
+            "test.txt".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_line_range() {
+        let buffer = buffer(vec!["a", "b", "", "c", "d", "", "e"]);
+        assert_eq!(line_range("c", &buffer, 1, 'c'), Parsed::Done((1, 1)));
+        assert_eq!(line_range("3c", &buffer, 1, 'c'), Parsed::Done((1, 3)));
+        assert_eq!(line_range("2j", &buffer, 5, 'c'), Parsed::Done((5, 6)));
+        assert_eq!(line_range("k", &buffer, 0, 'c'), Parsed::Done((0, 0)));
+        assert_eq!(line_range("gg", &buffer, 3, 'c'), Parsed::Done((0, 3)));
+        assert_eq!(line_range("G", &buffer, 3, 'c'), Parsed::Done((3, 6)));
+        assert_eq!(line_range("}", &buffer, 0, 'c'), Parsed::Done((0, 2)));
+        assert_eq!(line_range("ip", &buffer, 4, 'c'), Parsed::Done((3, 4)));
+        assert_eq!(line_range("ap", &buffer, 4, 'c'), Parsed::Done((3, 5)));
+        assert_eq!(line_range("1", &buffer, 4, 'c'), Parsed::Pending);
+        assert_eq!(line_range("g", &buffer, 4, 'c'), Parsed::Pending);
+        assert_eq!(line_range("x", &buffer, 4, 'c'), Parsed::Invalid);
+        assert_eq!(line_range("0", &buffer, 4, 'c'), Parsed::Invalid);
+    }
+
+    #[test]
+    fn test_surround_add_keys() {
+        let buffer = buffer(vec!["  say hello  "]);
+        let pos = (6, 0);
+        assert_eq!(surround_add("i", &buffer, pos), Parsed::Pending);
+        assert_eq!(surround_add("iw", &buffer, pos), Parsed::Pending);
+        assert_eq!(
+            surround_add("iw)", &buffer, pos),
+            Parsed::Done(Action::SurroundAdd(
+                (6, 0),
+                (11, 0),
+                "(".to_string(),
+                ")".to_string()
+            ))
+        );
+        assert_eq!(surround_add("s<p", &buffer, pos), Parsed::Pending);
+        assert_eq!(
+            surround_add("s<p id=\"a\">", &buffer, pos),
+            Parsed::Done(Action::SurroundAdd(
+                (2, 0),
+                (11, 0),
+                "<p id=\"a\">".to_string(),
+                "</p>".to_string()
+            ))
+        );
+        assert_eq!(surround_add("x", &buffer, pos), Parsed::Invalid);
+        assert_eq!(surround_add("iwa", &buffer, pos), Parsed::Invalid);
+    }
+
+    #[test]
+    fn test_surround_pair() {
+        let pair = |o: &str, c: &str| Parsed::Done((o.to_string(), c.to_string()));
+        assert_eq!(surround_pair("("), pair("( ", " )"));
+        assert_eq!(surround_pair("B"), pair("{", "}"));
+        assert_eq!(surround_pair("'"), pair("'", "'"));
+        assert_eq!(surround_pair("<div>"), pair("<div>", "</div>"));
+        assert_eq!(surround_pair("<>"), Parsed::Invalid);
+    }
+}
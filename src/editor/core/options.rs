@@ -0,0 +1,324 @@
+use std::{collections::HashMap, io::Write, time::Duration};
+
+use once_cell::sync::Lazy;
+
+use crate::{buff::CaseOptions, editor::Editor, helper::lua_handler::get_config_option};
+
+use super::ex::ExError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionValue {
+    Bool(bool),
+    Number(usize),
+}
+
+// where the value of an option is kept, :setlocal only changes the buffer or the viewport
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    Global,
+    Buffer,
+    Viewport,
+}
+
+#[derive(Debug)]
+pub struct OptionDef {
+    pub name: &'static str,
+    pub short: &'static str,
+    pub scope: Scope,
+    pub default: OptionValue,
+}
+
+const fn number(name: &'static str, short: &'static str, scope: Scope, n: usize) -> OptionDef {
+    OptionDef {
+        name,
+        short,
+        scope,
+        default: OptionValue::Number(n),
+    }
+}
+
+const fn flag(name: &'static str, short: &'static str, scope: Scope, b: bool) -> OptionDef {
+    OptionDef {
+        name,
+        short,
+        scope,
+        default: OptionValue::Bool(b),
+    }
+}
+
+// every option of the editor, their value is read from the options table of config.lua
+pub const OPTIONS: &[OptionDef] = &[
+    number("scrolloff", "so", Scope::Global, 0),
+    flag("ignorecase", "ic", Scope::Global, false),
+    flag("smartcase", "scs", Scope::Global, false),
+    number("timeoutlen", "tm", Scope::Global, 1000),
+    number("shiftwidth", "sw", Scope::Buffer, 4),
+    number("softtabstop", "sts", Scope::Buffer, 2),
+    number("numberwidth", "nuw", Scope::Viewport, 5),
+    number("popupmargin", "pm", Scope::Viewport, 30),
+    flag("onemore", "om", Scope::Viewport, false),
+];
+
+// the config is only read once, the changes made with :set are kept in the editor
+static CONFIG_OPTIONS: Lazy<Options> = Lazy::new(|| Options {
+    values: OPTIONS
+        .iter()
+        .map(|def| {
+            let value = match def.default {
+                OptionValue::Bool(default) => {
+                    OptionValue::Bool(get_config_option(def.name).unwrap_or(default))
+                }
+                OptionValue::Number(default) => {
+                    OptionValue::Number(get_config_option(def.name).unwrap_or(default))
+                }
+            };
+            (def.name, value)
+        })
+        .collect(),
+});
+
+pub fn find_option(name: &str) -> Option<&'static OptionDef> {
+    OPTIONS
+        .iter()
+        .find(|def| def.name == name || def.short == name)
+}
+
+// the options starting like the typed name for the completion
+pub fn option_names(typed: &str) -> Vec<String> {
+    let mut names: Vec<String> = OPTIONS
+        .iter()
+        .map(|def| def.name.to_string())
+        .filter(|name| name.starts_with(typed))
+        .collect();
+    names.sort();
+    names
+}
+
+fn display(name: &str, value: OptionValue) -> String {
+    match value {
+        OptionValue::Bool(true) => name.to_string(),
+        OptionValue::Bool(false) => format!("no{name}"),
+        OptionValue::Number(n) => format!("{name}={n}"),
+    }
+}
+
+// the values of the options, every one for the editor and the local ones of a buffer or viewport
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    values: HashMap<&'static str, OptionValue>,
+}
+
+impl Options {
+    // the values of config.lua, of every option or only the ones of a scope
+    pub fn from_config(scope: Option<Scope>) -> Self {
+        let mut options = CONFIG_OPTIONS.clone();
+        options
+            .values
+            .retain(|name, _| scope.is_none_or(|scope| find_option(name).unwrap().scope == scope));
+        options
+    }
+
+    pub fn get(&self, name: &str) -> Option<OptionValue> {
+        self.values.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &'static str, value: OptionValue) {
+        self.values.insert(name, value);
+    }
+
+    fn value(&self, name: &str) -> OptionValue {
+        self.get(name)
+            .or_else(|| find_option(name).map(|def| def.default))
+            .expect("unknown option")
+    }
+
+    pub fn number(&self, name: &str) -> usize {
+        match self.value(name) {
+            OptionValue::Number(n) => n,
+            OptionValue::Bool(b) => b as usize,
+        }
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        match self.value(name) {
+            OptionValue::Bool(b) => b,
+            OptionValue::Number(n) => n > 0,
+        }
+    }
+}
+
+impl<W: Write> Editor<W> {
+    // the value of an option for the current buffer and viewport
+    pub fn option(&self, name: &str) -> OptionValue {
+        let viewport = self.viewports.c_viewport();
+        let local = match find_option(name).map(|def| def.scope) {
+            Some(Scope::Viewport) => viewport.options.get(name),
+            Some(Scope::Buffer) => viewport.buffer.borrow().options.get(name),
+            _ => None,
+        };
+        local.unwrap_or_else(|| self.options.value(name))
+    }
+
+    pub fn option_number(&self, name: &str) -> usize {
+        match self.option(name) {
+            OptionValue::Number(n) => n,
+            OptionValue::Bool(b) => b as usize,
+        }
+    }
+
+    pub fn option_bool(&self, name: &str) -> bool {
+        self.option(name) == OptionValue::Bool(true)
+    }
+
+    pub fn search_case(&self) -> CaseOptions {
+        CaseOptions {
+            ignorecase: self.option_bool("ignorecase"),
+            smartcase: self.option_bool("smartcase"),
+        }
+    }
+
+    // :set sets the value of the editor and the current buffer or viewport, :setlocal only the
+    // local one
+    fn set_option(&mut self, def: &'static OptionDef, value: OptionValue, local: bool) {
+        if !local || def.scope == Scope::Global {
+            self.options.set(def.name, value);
+        }
+        let viewport = self.viewports.c_mut_viewport();
+        match def.scope {
+            Scope::Viewport => viewport.set_option(def.name, value),
+            Scope::Buffer => viewport.buffer.borrow_mut().options.set(def.name, value),
+            Scope::Global if def.name == "timeoutlen" => self.keybinds.set_timeout(
+                Duration::from_millis(self.options.number("timeoutlen") as u64),
+            ),
+            Scope::Global => {}
+        }
+    }
+
+    // the arguments of :set like opt, noopt, invopt, opt!, opt?, opt=value and opt+=value
+    pub fn set_options(&mut self, args: &str, local: bool) -> Result<(), ExError> {
+        if args.trim().is_empty() {
+            let values: Vec<String> = OPTIONS
+                .iter()
+                .map(|def| display(def.name, self.option(def.name)))
+                .collect();
+            self.toast.indication(values.join(" "));
+            return Ok(());
+        }
+
+        let mut shown = vec![];
+        for arg in args.split_whitespace() {
+            let invalid = || ExError::InvalidArgument(arg.to_string());
+            let unknown = |name: &str| ExError::UnknownOption(name.to_string());
+            let split = arg.find(['=', ':', '?', '!', '+', '-', '^']);
+            let (name, operator) = split.map_or((arg, ""), |i| arg.split_at(i));
+
+            // noopt and invopt for the booleans
+            let (def, prefix) = match find_option(name) {
+                Some(def) => (def, None),
+                None => match (name.strip_prefix("no"), name.strip_prefix("inv")) {
+                    (Some(name), _) => {
+                        (find_option(name).ok_or_else(|| unknown(arg))?, Some(false))
+                    }
+                    (_, Some(name)) => (find_option(name).ok_or_else(|| unknown(arg))?, Some(true)),
+                    _ => return Err(unknown(arg)),
+                },
+            };
+            let current = self.option(def.name);
+            let value = match (current, prefix, operator) {
+                (_, None, "?") => {
+                    shown.push(display(def.name, current));
+                    continue;
+                }
+                (OptionValue::Bool(_), Some(false), "") => OptionValue::Bool(false),
+                (OptionValue::Bool(b), Some(true), "") | (OptionValue::Bool(b), None, "!") => {
+                    OptionValue::Bool(!b)
+                }
+                (OptionValue::Bool(_), None, "") => OptionValue::Bool(true),
+                // a number without value is shown like with ?
+                (OptionValue::Number(_), None, "") => {
+                    shown.push(display(def.name, current));
+                    continue;
+                }
+                (OptionValue::Number(n), None, operator) => {
+                    let (operator, value) = operator.split_once(['=', ':']).ok_or_else(invalid)?;
+                    let value = value
+                        .parse::<usize>()
+                        .map_err(|_| ExError::NumberRequired(arg.to_string()))?;
+                    OptionValue::Number(match operator {
+                        "" => value,
+                        "+" => n + value,
+                        "-" => n.saturating_sub(value),
+                        "^" => n * value,
+                        _ => return Err(invalid()),
+                    })
+                }
+                _ => return Err(invalid()),
+            };
+            self.set_option(def, value, local);
+        }
+        if !shown.is_empty() {
+            self.toast.indication(shown.join(" "));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_options {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::editor::core::actions::action::Action;
+
+    fn set(editor: &mut Editor<Cursor<Vec<u8>>>, command: &str) {
+        editor.command = command.to_string();
+        Action::ExecuteCommand.execute(editor).unwrap();
+    }
+
+    #[test]
+    fn test_set_values() {
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        set(&mut editor, "set so:4 noic sw+=2 so+=1");
+        assert_eq!(editor.option_number("scrolloff"), 5);
+        assert!(!editor.option_bool("ignorecase"));
+        assert_eq!(editor.option_number("shiftwidth"), 6);
+
+        set(&mut editor, "set invic");
+        assert!(editor.option_bool("ignorecase"));
+        set(&mut editor, "set ic! scrolloff?");
+        assert!(!editor.option_bool("ignorecase"));
+        assert_eq!(editor.toast._last_message(), Some("scrolloff=5"));
+
+        set(&mut editor, "set foo");
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E518: Unknown option: foo")
+        );
+        set(&mut editor, "set so=x");
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E521: Number required after =: so=x")
+        );
+        set(&mut editor, "set noso");
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E475: Invalid argument: noso")
+        );
+    }
+
+    #[test]
+    fn test_setlocal_and_new_viewport() {
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        let min_vwidth = editor.viewports.c_viewport().min_vwidth;
+        set(&mut editor, "setlocal nuw=3 sw=2");
+        assert_eq!(editor.viewports.c_viewport().min_vwidth, min_vwidth - 2);
+        assert_eq!(editor.option_number("shiftwidth"), 2);
+        assert_eq!(editor.options.number("shiftwidth"), 4);
+
+        // a new viewport takes the options of the current one and the buffer the global ones
+        Action::PushEmptyViewport.execute(&mut editor).unwrap();
+        editor.viewports.index = 1;
+        assert_eq!(editor.option_number("numberwidth"), 3);
+        assert_eq!(editor.option_number("shiftwidth"), 4);
+    }
+}
@@ -0,0 +1,393 @@
+use std::io::Write;
+
+use mlua::{Lua, Table, Value};
+
+use crate::{
+    buff::Buffer,
+    editor::{Editor, TERMINAL_SIZE_MINUS},
+    viewport::Viewport,
+};
+
+use super::{grep::parse_result, make::ErrorFormat};
+
+// a position in a file with a message, like a compile error or a grep match
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickfixEntry {
+    pub path: String,
+    pub pos: (u16, u16), // buffer position
+    pub message: String,
+}
+
+impl QuickfixEntry {
+    // a path:line:col: message line
+    pub fn parse(line: &str) -> Option<Self> {
+        let (path, pos) = parse_result(line)?;
+        let message = line.splitn(4, ':').nth(3).unwrap_or("").trim().to_string();
+        Some(Self { path, pos, message })
+    }
+
+    pub fn display(&self) -> String {
+        let (x, y) = self.pos;
+        format!("{}:{}:{}: {}", self.path, y + 1, x + 1, self.message)
+    }
+
+    // a { filename, lnum, col, text } table like the vim setqflist or a path:line:col: string
+    pub fn from_lua(value: Value) -> Option<Self> {
+        match value {
+            Value::String(line) => Self::parse(&line.to_str().ok()?),
+            Value::Table(entry) => {
+                let get = |key: &str| entry.get::<Option<u16>>(key).ok().flatten();
+                Some(Self {
+                    path: entry.get::<String>("filename").ok()?,
+                    pos: (
+                        get("col").unwrap_or(1).saturating_sub(1),
+                        get("lnum").unwrap_or(1).saturating_sub(1),
+                    ),
+                    message: entry.get::<String>("text").unwrap_or_default(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // the table read by from_lua, the lines and columns start at 1
+    pub fn to_lua(&self, lua: &Lua) -> mlua::Result<Table> {
+        let entry = lua.create_table()?;
+        entry.set("filename", self.path.as_str())?;
+        entry.set("lnum", self.pos.1 + 1)?;
+        entry.set("col", self.pos.0 + 1)?;
+        entry.set("text", self.message.as_str())?;
+        Ok(entry)
+    }
+}
+
+// the entries of a lua list, the invalid ones are skipped
+pub fn entries_from_lua(table: Table) -> Vec<QuickfixEntry> {
+    table
+        .sequence_values::<Value>()
+        .flatten()
+        .filter_map(QuickfixEntry::from_lua)
+        .collect()
+}
+
+// the errors of rustc, gcc, tsc, python and the tools printing path:line:col: message, with the
+// patterns of make in config.lua
+pub fn parse_compiler_output(output: &str) -> Vec<QuickfixEntry> {
+    ErrorFormat::load().parse(output)
+}
+
+// evaluate a lua expression returning a list of entries, the lua of the user commands sees the
+// functions of config.lua
+pub fn lua_entries(lua: &Lua, expression: &str) -> anyhow::Result<Vec<QuickfixEntry>> {
+    let table = lua
+        .load(expression)
+        .eval::<Table>()
+        .map_err(|err| anyhow::anyhow!("{err}"))?;
+    Ok(entries_from_lua(table))
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QuickfixList {
+    pub title: String,
+    pub entries: Vec<QuickfixEntry>,
+    pub index: usize, // the current entry
+}
+
+impl QuickfixList {
+    pub fn new(title: String, entries: Vec<QuickfixEntry>) -> Self {
+        Self {
+            title,
+            entries,
+            index: 0,
+        }
+    }
+}
+
+// the quickfix list is global and each viewport has its location list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListKind {
+    Quickfix,
+    Location,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListMove {
+    Next,
+    Prev,
+    First,
+    Last,
+}
+
+impl ListKind {
+    fn viewport_path(&self) -> &str {
+        match self {
+            ListKind::Quickfix => "[quickfix]",
+            ListKind::Location => "[location]",
+        }
+    }
+}
+
+impl<W: Write> Editor<W> {
+    // the location list of the list viewport is the one of the file it was opened from
+    fn loclist_viewport(&mut self) -> &mut Viewport {
+        let viewports = &mut self.viewports;
+        let owner = viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .name()
+            .strip_prefix("[location] ")
+            .map(String::from);
+        let owner = owner.and_then(|owner| {
+            (viewports.values.iter()).position(|v| v.buffer.borrow().name() == owner)
+        });
+        match owner {
+            Some(index) => &mut viewports.values[index],
+            None => viewports.c_mut_viewport(),
+        }
+    }
+
+    pub fn list_mut(&mut self, kind: ListKind) -> &mut QuickfixList {
+        match kind {
+            ListKind::Quickfix => &mut self.quickfix,
+            ListKind::Location => &mut self.loclist_viewport().loclist,
+        }
+    }
+
+    pub fn set_list(&mut self, kind: ListKind, list: QuickfixList) {
+        *self.list_mut(kind) = list;
+        self.refresh_list_viewport(kind);
+    }
+
+    // the list shown by the current viewport
+    pub fn list_kind(&self) -> Option<ListKind> {
+        let path = self
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .name()
+            .to_string();
+        [ListKind::Quickfix, ListKind::Location]
+            .into_iter()
+            .find(|kind| path.starts_with(kind.viewport_path()))
+    }
+
+    // the location viewport of a file is named after it
+    fn list_path(&self, kind: ListKind) -> String {
+        let path = self
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow()
+            .name()
+            .to_string();
+        match kind {
+            ListKind::Quickfix => kind.viewport_path().to_string(),
+            ListKind::Location if path.starts_with("[location] ") => path.to_string(),
+            ListKind::Location => format!("{} {path}", kind.viewport_path()),
+        }
+    }
+
+    fn list_buffer_id(&self, kind: ListKind) -> Option<usize> {
+        let path = self.list_path(kind);
+        self.viewports.find_buffer(&path)
+    }
+
+    // update the entries and the highlighted line of the list viewport when it is open
+    fn refresh_list_viewport(&mut self, kind: ListKind) {
+        let list = self.list_mut(kind).clone();
+        let Some(id) = self.list_buffer_id(kind) else {
+            return;
+        };
+        if let Some(buffer) = self.viewports.buffer(id) {
+            let lines = list.entries.iter().map(|e| e.display()).collect();
+            buffer.borrow_mut().replace_lines(0..usize::MAX, lines);
+        }
+        for index in self.viewports.views(id) {
+            self.viewports.values[index].current_line = Some(list.index as u16);
+        }
+    }
+
+    pub fn open_list(&mut self, kind: ListKind) {
+        let path = self.list_path(kind);
+        let index = self.list_mut(kind).index;
+        match self.list_buffer_id(kind) {
+            Some(id) => self.viewports.open(id),
+            None => {
+                let viewport = Viewport::new(
+                    Buffer::new_tmp(vec![String::new()], path),
+                    self.size.0,
+                    self.size.1 - TERMINAL_SIZE_MINUS,
+                    0,
+                    false,
+                );
+                let index = self.viewports.push(viewport);
+                self.viewports.show(index);
+            }
+        }
+        self.refresh_list_viewport(kind);
+        self.reset_cursor();
+        self.goto_buffer_pos((0, index as u16));
+    }
+
+    pub fn close_list(&mut self, kind: ListKind) {
+        if let Some(id) = self.list_buffer_id(kind) {
+            let is_current = !self.viewports.is_explorer && self.viewports.current_id() == Some(id);
+            self.viewports.remove_buffer(id, true);
+            if is_current {
+                self.reset_cursor();
+            }
+        }
+    }
+
+    // go to an entry of the list, the enter key of the list viewport gives its index
+    pub fn goto_list_entry(&mut self, kind: ListKind, list_move: Option<ListMove>, index: usize) {
+        let list = self.list_mut(kind);
+        let (current, len) = (list.index, list.entries.len());
+        let next = match list_move {
+            _ if len == 0 => Err("no entries"),
+            Some(ListMove::Next) if current + 1 >= len => Err("no more entries"),
+            Some(ListMove::Prev) if current == 0 => Err("no previous entry"),
+            Some(ListMove::Next) => Ok(current + 1),
+            Some(ListMove::Prev) => Ok(current - 1),
+            Some(ListMove::First) => Ok(0),
+            Some(ListMove::Last) => Ok(len - 1),
+            None => Ok(index.min(len - 1)),
+        };
+        match next {
+            Ok(next) => list.index = next,
+            Err(err) => {
+                self.toast.error(err.to_string());
+                return;
+            }
+        }
+        let list = self.list_mut(kind).clone();
+        self.refresh_list_viewport(kind);
+
+        let entry = &list.entries[list.index];
+        if !std::path::Path::new(&entry.path).is_file() {
+            self.toast.error(format!("cannot open {}", entry.path));
+            return;
+        }
+        self.open_file(entry.path.clone());
+        self.viewports.is_explorer = false;
+        if kind == ListKind::Location {
+            self.viewports.c_mut_viewport().loclist = list.clone();
+        }
+        self.reset_cursor();
+        self.goto_buffer_pos(entry.pos);
+        self.toast.indication(format!(
+            "({} of {}) {}",
+            list.index + 1,
+            list.entries.len(),
+            entry.message
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests_quickfix {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::editor::core::actions::action::Action;
+
+    #[test]
+    fn test_parse_compiler_output() {
+        let output = "   Compiling rusty v0.1.0
+error[E0308]: mismatched types
+ --> src/main.rs:3:18
+  |
+warning: unused variable: `x`
+  --> src/lib.rs:10:9
+main.c:4:5: error: expected ';'
+script.ts:7: something wrong";
+        let entries = parse_compiler_output(output);
+        let displayed: Vec<String> = entries.iter().map(|e| e.display()).collect();
+        assert_eq!(
+            displayed,
+            vec![
+                "src/main.rs:3:18: error: mismatched types",
+                "src/lib.rs:10:9: warning: unused variable: `x`",
+                "main.c:4:5: error: expected ';'",
+                "script.ts:7:1: something wrong",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lua_entries() -> anyhow::Result<()> {
+        let lua = Lua::new();
+        let entries = lua_entries(
+            &lua,
+            "{ { filename = 'a.rs', lnum = 2, col = 3, text = 'todo' }, 'b.rs:4:1: fixme' }",
+        )
+        .unwrap();
+        assert_eq!(entries[0].pos, (2, 1));
+        assert_eq!(entries[1].display(), "b.rs:4:1: fixme");
+        assert!(lua_entries(&lua, "{ oops").is_err());
+
+        // the globals of the lua are visible
+        lua.load("function todos() return { 'c.rs:1:1: todo' } end")
+            .exec()
+            .unwrap();
+        assert_eq!(lua_entries(&lua, "todos()")?[0].path, "c.rs");
+        Ok(())
+    }
+
+    #[test]
+    fn test_navigate_quickfix() -> anyhow::Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "one\ntwo\nthree")?;
+        let path = file.path().display().to_string();
+        let entries = vec![
+            QuickfixEntry::parse(&format!("{path}:2:2: first")).unwrap(),
+            QuickfixEntry::parse(&format!("{path}:3:1: second")).unwrap(),
+        ];
+
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        editor.set_list(
+            ListKind::Quickfix,
+            QuickfixList::new("test".into(), entries),
+        );
+        Action::ListOpen(ListKind::Quickfix).execute(&mut editor)?;
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            "[quickfix]"
+        );
+        assert_eq!(editor.viewports.c_viewport().current_line, Some(0));
+
+        Action::ListGoto(ListKind::Quickfix, ListMove::Next).execute(&mut editor)?;
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().name(), path);
+        assert_eq!(editor.v_cursor(), (0, 2));
+        let list_id = editor.list_buffer_id(ListKind::Quickfix).unwrap();
+        let list_index = editor.viewports.views(list_id)[0];
+        assert_eq!(editor.viewports.values[list_index].current_line, Some(1));
+
+        Action::ListGoto(ListKind::Quickfix, ListMove::First).execute(&mut editor)?;
+        assert_eq!(editor.v_cursor(), (1, 1));
+        Action::ListClose(ListKind::Quickfix).execute(&mut editor)?;
+        assert!(editor.list_buffer_id(ListKind::Quickfix).is_none());
+        assert_eq!(editor.viewports.c_viewport().buffer.borrow().name(), path);
+        Ok(())
+    }
+
+    #[test]
+    fn test_location_list_is_per_viewport() -> anyhow::Result<()> {
+        let mut editor: Editor<Cursor<Vec<u8>>> = Editor::default();
+        let entries = lua_entries(&Lua::new(), "{ 'a.rs:1:1: one' }")?;
+        editor.set_list(ListKind::Location, QuickfixList::new("l".into(), entries));
+        assert_eq!(editor.viewports.c_viewport().loclist.entries.len(), 1);
+        assert!(editor.quickfix.entries.is_empty());
+
+        // the location viewport shows the list of the viewport it was opened from
+        Action::ListOpen(ListKind::Location).execute(&mut editor)?;
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().lines,
+            vec!["a.rs:1:1: one"]
+        );
+        assert_eq!(editor.list_mut(ListKind::Location).entries.len(), 1);
+        Ok(())
+    }
+}
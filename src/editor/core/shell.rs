@@ -0,0 +1,161 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+    thread,
+};
+
+use crate::{
+    buff::Buffer,
+    editor::{Editor, TERMINAL_SIZE_MINUS},
+    viewport::Viewport,
+};
+
+use super::ex::ExError;
+
+// the scratch viewport of :!cmd is named by the command and reused when it is run again
+const SHELL_VIEWPORT: &str = "[shell]";
+
+// run the command with sh, the lines are written to its stdin
+fn run(cmd: &str, input: Option<Vec<String>>) -> Result<Output, ExError> {
+    let stdin = match input.is_some() {
+        true => Stdio::piped(),
+        false => Stdio::null(),
+    };
+    let mut child = Command::new("sh")
+        .args(["-c", cmd])
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| ExError::CantRunShell(err.to_string()))?;
+    // written from another thread or a big input could block while stdout is full
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        thread::spawn(move || {
+            let mut text = input.join("\n");
+            text.push('\n');
+            let _ = stdin.write_all(text.as_bytes());
+        });
+    }
+    child
+        .wait_with_output()
+        .map_err(|err| ExError::CantRunShell(err.to_string()))
+}
+
+fn output_lines(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// a command which failed gives its exit code and the first line of its errors
+fn check_status(output: &Output) -> Result<(), ExError> {
+    match output.status.code() {
+        Some(0) => Ok(()),
+        code => {
+            let message = output_lines(&output.stderr)
+                .into_iter()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or_default();
+            Err(ExError::ShellReturned(code.unwrap_or(-1), message))
+        }
+    }
+}
+
+// the output of a command which succeeded, the lines are given to its stdin
+pub fn shell_lines(cmd: &str, input: Option<Vec<String>>) -> Result<Vec<String>, ExError> {
+    let output = run(cmd, input)?;
+    check_status(&output)?;
+    Ok(output_lines(&output.stdout))
+}
+
+impl<W: Write> Editor<W> {
+    // :!cmd show the output and the errors of the command in a scratch viewport, it is shown even
+    // when the command fails
+    pub fn run_shell(&mut self, cmd: &str) -> Result<(), ExError> {
+        let output = run(cmd, None)?;
+        let mut lines = output_lines(&output.stdout);
+        lines.extend(output_lines(&output.stderr));
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        let path = format!("{SHELL_VIEWPORT} {cmd}");
+        match self.viewports.find_buffer(&path) {
+            Some(id) => self.viewports.open(id),
+            None => {
+                let viewport = Viewport::new(
+                    Buffer::new_tmp(vec![String::new()], path),
+                    self.size.0,
+                    self.size.1 - TERMINAL_SIZE_MINUS,
+                    0,
+                    false,
+                );
+                let index = self.viewports.push(viewport);
+                self.viewports.show(index);
+            }
+        }
+        self.viewports
+            .c_viewport()
+            .buffer
+            .borrow_mut()
+            .replace_lines(0..usize::MAX, lines);
+        self.reset_cursor();
+        check_status(&output)
+    }
+}
+
+#[cfg(test)]
+mod tests_shell {
+    use crate::editor::{
+        core::{actions::action::Action, mode::Mode},
+        test_helpers::{editor_with, lines, run},
+    };
+
+    #[test]
+    fn test_filter_and_read() {
+        let mut editor = editor_with(&["c", "b", "a", "end"]);
+        run(&mut editor, "1,3!sort");
+        assert_eq!(lines(&editor), vec!["a", "b", "c", "end"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["c", "b", "a", "end"]);
+
+        // the lines are kept when the command fails
+        run(&mut editor, "%!echo oops >&2; exit 3");
+        assert_eq!(editor.toast._last_message(), Some("shell returned 3: oops"));
+        assert_eq!(lines(&editor), vec!["c", "b", "a", "end"]);
+
+        run(&mut editor, "2r !printf 'x\\ny'");
+        assert_eq!(lines(&editor), vec!["c", "b", "x", "y", "a", "end"]);
+        assert_eq!(editor.v_cursor().1, 2);
+    }
+
+    #[test]
+    fn test_shell_viewport() {
+        let mut editor = editor_with(&["a"]);
+        run(&mut editor, "!echo hello; echo world");
+        assert_eq!(lines(&editor), vec!["hello", "world"]);
+        assert!(!editor.viewports.c_viewport().modifiable);
+        assert_eq!(editor.viewports.values.len(), 2);
+
+        // the same command reuse its viewport
+        editor.viewports.index = 0;
+        run(&mut editor, "!echo hello; echo world");
+        assert_eq!(editor.viewports.values.len(), 2);
+        assert_eq!(editor.viewports.index, 1);
+    }
+
+    #[test]
+    fn test_filter_visual_selection() {
+        let mut editor = editor_with(&["a", "b", "c"]);
+        Action::EnterMode(Mode::Visual)
+            .execute(&mut editor)
+            .unwrap();
+        Action::MoveDown.execute(&mut editor).unwrap();
+        Action::FilterSelection.execute(&mut editor).unwrap();
+        assert_eq!(editor.command, "'<,'>!");
+        editor.command.push_str("sort -r");
+        Action::ExecuteCommand.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["b", "a", "c"]);
+    }
+}
@@ -0,0 +1,449 @@
+use std::{collections::HashMap, io::Write};
+
+use crossterm::event::KeyCode;
+
+use crate::editor::Editor;
+
+use super::actions::action::Action;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    Stop(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pieces: Vec<Piece>,
+    placeholders: HashMap<u32, String>,
+}
+
+impl Snippet {
+    // tab stops are written $1, ${1} or ${1:placeholder}, a \ escape the next char
+    pub fn parse(body: &str) -> Self {
+        let mut pieces = vec![];
+        let mut placeholders = HashMap::new();
+        let mut text = String::new();
+        let mut chars = body.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => text.extend(chars.next()),
+                '$' => {
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    let mut number = String::new();
+                    while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                        number.push(digit);
+                    }
+                    let Ok(n) = number.parse::<u32>() else {
+                        text.push('$');
+                        text.push_str(if braced { "{" } else { "" });
+                        text.push_str(&number);
+                        continue;
+                    };
+                    if braced {
+                        let placeholder = match chars.next_if_eq(&':') {
+                            Some(_) => chars.by_ref().take_while(|c| *c != '}').collect(),
+                            None => {
+                                chars.next_if_eq(&'}');
+                                String::new()
+                            }
+                        };
+                        // the first placeholder of a stop is used by all its mirrors
+                        let current = placeholders.entry(n).or_insert_with(String::new);
+                        if current.is_empty() {
+                            *current = placeholder;
+                        }
+                    }
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Stop(n));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Self {
+            pieces,
+            placeholders,
+        }
+    }
+
+    // the lines of the snippet keep the indentation of the line where it is expanded
+    fn indent(mut self, indentation: &str) -> Self {
+        for piece in self.pieces.iter_mut() {
+            if let Piece::Text(text) = piece {
+                *text = text.replace('\n', &format!("\n{indentation}"));
+            }
+        }
+        self
+    }
+
+    // the numbers of the stops in the jump order, $0 is always the last one
+    fn stops(&self) -> Vec<u32> {
+        let mut stops: Vec<u32> = self
+            .pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Stop(n) => Some(*n),
+                _ => None,
+            })
+            .collect();
+        stops.sort_by_key(|n| (*n == 0, *n));
+        stops.dedup();
+        stops
+    }
+
+    fn is_mirrored(&self, stop: u32) -> bool {
+        self.pieces
+            .iter()
+            .filter(|p| **p == Piece::Stop(stop))
+            .count()
+            > 1
+    }
+
+    // the text with the values of the stops and the char offset of the first occurrence of each
+    fn render(&self, values: &HashMap<u32, String>) -> (String, HashMap<u32, usize>) {
+        let mut text = String::new();
+        let mut offsets = HashMap::new();
+        let mut len = 0;
+        for piece in &self.pieces {
+            let value = match piece {
+                Piece::Text(value) => value,
+                Piece::Stop(n) => {
+                    offsets.entry(*n).or_insert(len);
+                    values.get(n).map(|v| v.as_str()).unwrap_or_default()
+                }
+            };
+            text.push_str(value);
+            len += value.chars().count();
+        }
+        (text, offsets)
+    }
+}
+
+// buffer position of a char offset in a text starting at the line y
+fn offset_to_pos(text: &str, offset: usize, y: usize) -> (u16, u16) {
+    let before: Vec<char> = text.chars().take(offset).collect();
+    let lines = before.iter().filter(|c| **c == '\n').count();
+    let x = before.iter().rev().take_while(|c| **c != '\n').count();
+    (x as u16, (y + lines) as u16)
+}
+
+// a snippet being filled, the lines it uses are rendered again when a mirrored stop change
+#[derive(Debug, Clone)]
+pub struct ActiveSnippet {
+    snippet: Snippet,
+    values: HashMap<u32, String>,
+    before: String, // text of the line before and after the snippet
+    after: String,
+    start_y: usize,
+    lines_len: usize,  // buffer lines used by the snippet
+    buffer_len: usize, // buffer len after the last render, a new line stop the snippet
+    stops: Vec<u32>,
+    current: usize,
+    pristine: bool, // the placeholder of the current stop is replaced by the first char typed
+}
+
+impl ActiveSnippet {
+    fn new(snippet: Snippet, before: String, after: String, start_y: usize) -> Self {
+        let stops = snippet.stops();
+        let values = stops
+            .iter()
+            .map(|n| (*n, snippet.placeholders.get(n).cloned().unwrap_or_default()))
+            .collect();
+        Self {
+            values,
+            stops,
+            snippet,
+            before,
+            after,
+            start_y,
+            lines_len: 1,
+            buffer_len: 0,
+            current: 0,
+            pristine: false,
+        }
+    }
+
+    fn full_text(&self) -> (String, HashMap<u32, usize>) {
+        let (text, offsets) = self.snippet.render(&self.values);
+        let before_len = self.before.chars().count();
+        let offsets = offsets
+            .into_iter()
+            .map(|(n, offset)| (n, before_len + offset))
+            .collect();
+        (format!("{}{text}{}", self.before, self.after), offsets)
+    }
+
+    fn stop_pos(&self, stop: u32) -> Option<(u16, u16)> {
+        let (text, offsets) = self.full_text();
+        Some(offset_to_pos(&text, *offsets.get(&stop)?, self.start_y))
+    }
+
+    fn end_pos(&self) -> (u16, u16) {
+        let (text, _) = self.full_text();
+        let len = text.chars().count() - self.after.chars().count();
+        offset_to_pos(&text, len, self.start_y)
+    }
+
+    fn current_stop(&self) -> Option<u32> {
+        self.stops.get(self.current).copied()
+    }
+
+    // read the value of the current stop in the buffer, none if the text around it changed
+    fn read_value(&self, lines: &[String]) -> Option<String> {
+        let stop = self.current_stop()?;
+        if lines.len() != self.buffer_len {
+            return None;
+        }
+        let (text, offsets) = self.full_text();
+        let offset = *offsets.get(&stop)?;
+        let old_len = self.values.get(&stop).map(|v| v.chars().count())?;
+        let text: Vec<char> = text.chars().collect();
+        let buffer: Vec<char> = lines
+            .get(self.start_y..self.start_y + self.lines_len)?
+            .join("\n")
+            .chars()
+            .collect();
+
+        let new_len = (old_len + buffer.len()).checked_sub(text.len())?;
+        let is_same_around = buffer.get(..offset)? == &text[..offset]
+            && buffer.get(offset + new_len..)? == &text[offset + old_len..];
+        is_same_around.then(|| buffer[offset..offset + new_len].iter().collect())
+    }
+}
+
+impl<W: Write> Editor<W> {
+    // replace the lines of the active snippet with its new render
+    fn render_snippet(&mut self) {
+        let Some(snippet) = self.snippet.as_mut() else {
+            return;
+        };
+        let (text, _) = snippet.full_text();
+        let lines: Vec<String> = text.split('\n').map(|l| l.to_string()).collect();
+        let (start, old_len) = (snippet.start_y, snippet.lines_len);
+        snippet.lines_len = lines.len();
+        self.replace_buffer_lines(start, old_len, lines);
+
+        let buffer_len = self.viewports.c_viewport().buffer.borrow().lines.len();
+        if let Some(snippet) = self.snippet.as_mut() {
+            snippet.buffer_len = buffer_len;
+        }
+    }
+
+    // expand the snippet of the word before the cursor or insert the fallback text
+    // without snippet the fallback is added, softtabstop spaces when it is empty
+    pub fn expand_snippet(&mut self, fallback: &str) {
+        let fallback = match fallback.is_empty() {
+            true => " ".repeat(self.option_number("softtabstop")),
+            false => fallback.to_string(),
+        };
+        let (trigger, (start_x, y)) = self.word_before_cursor();
+        let viewport = self.viewports.c_viewport();
+        let body = match trigger.is_empty() {
+            true => None,
+            false => viewport
+                .languages
+                .snippet(viewport.buffer.borrow().name(), &trigger),
+        };
+        let Some(body) = body else {
+            if !fallback.is_empty() {
+                self.buffer_actions.push(Action::AddStr(fallback));
+            }
+            return;
+        };
+
+        let (x, _) = self.v_cursor();
+        let line: Vec<char> = viewport.buffer.borrow().lines[y as usize].chars().collect();
+        let indentation: String = line.iter().take_while(|c| c.is_whitespace()).collect();
+        let snippet = Snippet::parse(body).indent(&indentation);
+        let before = line[..start_x as usize].iter().collect();
+        let after = line[(x as usize).min(line.len())..].iter().collect();
+
+        self.snippet = Some(ActiveSnippet::new(snippet, before, after, y as usize));
+        self.render_snippet();
+        self.goto_snippet_stop(0);
+    }
+
+    // put the cursor on the stop at this index, the snippet ends after the last one
+    fn goto_snippet_stop(&mut self, index: usize) {
+        let Some(snippet) = self.snippet.as_mut() else {
+            return;
+        };
+        snippet.current = index;
+        let (pos, is_last) = match snippet.current_stop() {
+            Some(stop) => {
+                snippet.pristine = snippet.values.get(&stop).is_some_and(|v| !v.is_empty());
+                (snippet.stop_pos(stop), stop == 0)
+            }
+            None => (Some(snippet.end_pos()), true),
+        };
+        if is_last {
+            self.snippet = None;
+        }
+        if let Some(pos) = pos {
+            self.clear_buffer_x_cursor();
+            self.goto_buffer_pos(pos);
+        }
+    }
+
+    pub fn jump_snippet_stop(&mut self, forward: bool) {
+        self.sync_snippet();
+        let Some(snippet) = &self.snippet else {
+            return;
+        };
+        let index = match forward {
+            true => snippet.current + 1,
+            false => snippet.current.saturating_sub(1),
+        };
+        self.goto_snippet_stop(index);
+    }
+
+    // keep the value of the current stop after an edit and update its mirrors
+    pub fn sync_snippet(&mut self) {
+        let Some(snippet) = self.snippet.as_mut() else {
+            return;
+        };
+        let value = snippet.read_value(&self.viewports.c_viewport().buffer.borrow().lines);
+        let (Some(stop), Some(value)) = (snippet.current_stop(), value) else {
+            // the edit was outside of the stop
+            self.snippet = None;
+            return;
+        };
+        if snippet.values.get(&stop) == Some(&value) {
+            return;
+        }
+        snippet.values.insert(stop, value);
+        snippet.pristine = false;
+        if snippet.snippet.is_mirrored(stop) {
+            let cursor = self.cursor;
+            self.render_snippet();
+            self.cursor = cursor;
+        }
+    }
+
+    // remove the placeholder of the current stop before the first edit, return true if removed
+    pub fn clear_snippet_placeholder(&mut self) -> bool {
+        let Some(snippet) = self.snippet.as_mut().filter(|s| s.pristine) else {
+            return false;
+        };
+        let Some(stop) = snippet.current_stop() else {
+            return false;
+        };
+        snippet.pristine = false;
+        snippet.values.insert(stop, String::new());
+        let pos = snippet.stop_pos(stop);
+        self.render_snippet();
+        if let Some(pos) = pos {
+            self.goto_buffer_pos(pos);
+        }
+        true
+    }
+
+    pub fn handle_snippet_key(&mut self, code: &KeyCode) -> Option<Action> {
+        match code {
+            KeyCode::Tab => Some(Action::JumpSnippetStop(true)),
+            KeyCode::BackTab => Some(Action::JumpSnippetStop(false)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_snippet {
+    use super::*;
+    use crate::editor::{
+        core::mode::Mode,
+        test_helpers::{editor_with_path, lines, TestEditor},
+    };
+
+    fn insert_editor(line: &str, x: u16) -> TestEditor {
+        let mut editor = editor_with_path(&[line], "a.rs");
+        editor.mode = Mode::Insert;
+        editor.cursor = (x, 0);
+        editor
+    }
+
+    fn type_str(editor: &mut TestEditor, s: &str) -> anyhow::Result<()> {
+        for c in s.chars() {
+            Action::AddChar(c).execute(editor)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_snippet() {
+        let snippet = Snippet::parse("fn ${1:name}($2) -> \\$ {\n$0 $1\n}");
+        assert_eq!(snippet.stops(), vec![1, 2, 0]);
+        assert!(snippet.is_mirrored(1));
+        assert_eq!(snippet.placeholders.get(&1), Some(&"name".to_string()));
+
+        let (text, offsets) = snippet.render(&snippet.placeholders);
+        assert_eq!(text, "fn name() -> $ {\n name\n}");
+        assert_eq!(offsets.get(&2), Some(&8));
+    }
+
+    #[test]
+    fn test_expand_and_jump_stops() -> anyhow::Result<()> {
+        let mut editor = insert_editor("    fn", 6);
+        Action::ExpandSnippet("  ".to_string()).execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["    fn name() {", "        ", "    }"]);
+        assert_eq!(editor.cursor, (7, 0));
+
+        // the placeholder is replaced by what we type
+        type_str(&mut editor, "add")?;
+        assert_eq!(lines(&editor)[0], "    fn add() {");
+        Action::JumpSnippetStop(true).execute(&mut editor)?;
+        assert_eq!(editor.cursor, (11, 0));
+        type_str(&mut editor, "a: u8")?;
+
+        Action::JumpSnippetStop(false).execute(&mut editor)?;
+        assert_eq!(editor.cursor, (7, 0));
+        Action::JumpSnippetStop(true).execute(&mut editor)?;
+        Action::JumpSnippetStop(true).execute(&mut editor)?;
+        assert_eq!(editor.cursor, (8, 1));
+        assert!(editor.snippet.is_none());
+
+        // the whole expansion is undone at once
+        Action::EnterMode(Mode::Normal).execute(&mut editor)?;
+        Action::Undo.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["    fn"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirrored_stop() -> anyhow::Result<()> {
+        let mut editor = insert_editor("", 0);
+        let mut snippet = ActiveSnippet::new(
+            Snippet::parse("let ${1:a} = 1; $1 + $1"),
+            String::new(),
+            String::new(),
+            0,
+        );
+        snippet.pristine = true;
+        editor.snippet = Some(snippet);
+        editor.render_snippet();
+        editor.goto_snippet_stop(0);
+        assert_eq!(lines(&editor), vec!["let a = 1; a + a"]);
+
+        type_str(&mut editor, "xy")?;
+        assert_eq!(lines(&editor), vec!["let xy = 1; xy + xy"]);
+        Action::RemoveChar.execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["let x = 1; x + x"]);
+        assert_eq!(editor.cursor, (5, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_snippet_uses_fallback() -> anyhow::Result<()> {
+        let mut editor = insert_editor("abc", 3);
+        Action::ExpandSnippet("  ".to_string()).execute(&mut editor)?;
+        assert_eq!(lines(&editor), vec!["abc  "]);
+        assert!(editor.snippet.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,428 @@
+use std::io::Write;
+
+use regex::Regex;
+
+use crate::{
+    buff::{search_regex, Buffer},
+    editor::{ui::modal::confirm::ModalConfirmSubstitute, Editor},
+};
+
+use super::{
+    actions::action::{Action, OldCursorPosition},
+    ex::ExRange,
+};
+
+// a parsed [range]s/pattern/replacement/flags command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substitute {
+    pub range: Option<ExRange>,
+    pub pattern: String,
+    pub replacement: String,
+    pub global: bool,
+    pub ignore_case: Option<bool>, // i or I flag
+    pub confirm: bool,
+}
+
+// split on the separator, an escaped separator is kept without its backslash and the last
+// part takes the rest of the command
+fn split_unescaped(command: &str, sep: char, max: usize) -> Vec<String> {
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == sep => part.push(next),
+                Some(next) => {
+                    part.push('\\');
+                    part.push(next);
+                }
+                None => part.push('\\'),
+            },
+            c if c == sep && parts.len() + 1 < max => parts.push(std::mem::take(&mut part)),
+            c => part.push(c),
+        }
+    }
+    parts.push(part);
+    parts
+}
+
+// the vim replacement to the regex crate syntax, \1 is a capture group like $1 and \n a new line
+fn replacement_syntax(replacement: &str) -> String {
+    let mut translated = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some(n) if n.is_ascii_digit() => translated.push_str(&format!("${{{n}}}")),
+                Some('n' | 'r') => translated.push('\n'),
+                Some('$') => translated.push_str("$$"),
+                Some(n) => translated.push(n),
+                None => translated.push('\\'),
+            },
+            (c, false) => translated.push(c),
+        }
+    }
+    translated
+}
+
+impl Substitute {
+    // the /pattern/replacement/flags following the s of the command
+    pub fn parse_args(range: Option<ExRange>, rest: &str) -> Option<Self> {
+        let sep = rest.chars().next()?;
+        if sep.is_alphanumeric() || sep.is_whitespace() || sep == '\\' {
+            return None;
+        }
+
+        let parts = split_unescaped(&rest[sep.len_utf8()..], sep, 3);
+        let flags = parts.get(2).map(|f| f.as_str()).unwrap_or("");
+        if !flags.chars().all(|f| "gciI".contains(f)) {
+            return None;
+        }
+        Some(Self {
+            range,
+            pattern: parts[0].clone(),
+            replacement: parts.get(1).cloned().unwrap_or_default(),
+            global: flags.contains('g'),
+            ignore_case: match (flags.contains('i'), flags.contains('I')) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            confirm: flags.contains('c'),
+        })
+    }
+}
+
+// a substitution in progress, the confirm mode stops at each match until it is answered
+#[derive(Debug)]
+pub struct Substitution {
+    regex: Regex,
+    replacement: String,
+    global: bool,
+    start: usize,
+    end: usize, // first line after the range, it moves when a replacement adds lines
+    next: (usize, usize), // line and byte offset where the next match is searched
+    old_lines: Vec<String>,
+    old_cursor: OldCursorPosition,
+    count: usize,
+    skipped: usize,
+    lines: usize,
+    last_y: Option<usize>,
+    search_index: Option<usize>, // the / search highlighted before the preview of the matches
+}
+
+impl Substitution {
+    // the next match as its line and byte range
+    fn find_next(&self, buffer: &Buffer) -> Option<(usize, usize, usize)> {
+        (self.next.0..self.end.min(buffer.lines.len())).find_map(|y| {
+            let line = &buffer.lines[y];
+            let offset = if y == self.next.0 { self.next.1 } else { 0 };
+            if offset > line.len() {
+                return None;
+            }
+            self.regex
+                .find_at(line, offset)
+                .map(|m| (y, m.start(), m.end()))
+        })
+    }
+
+    // where to search after a match, an empty match skip a char to not be found again
+    fn after(&mut self, buffer: &Buffer, (y, offset): (usize, usize), empty: bool) {
+        let line = &buffer.lines[y];
+        let skip = match empty {
+            true => line[offset..].chars().next().map(|c| c.len_utf8()),
+            false => Some(0),
+        };
+        self.next = match (self.global, skip) {
+            (true, Some(skip)) => (y, offset + skip),
+            _ => (y + 1, 0),
+        };
+    }
+
+    fn skip(&mut self, buffer: &Buffer, (y, start, end): (usize, usize, usize)) {
+        self.skipped += 1;
+        self.after(buffer, (y, end), start == end);
+    }
+
+    fn replace(&mut self, buffer: &mut Buffer, (y, start, end): (usize, usize, usize)) {
+        let line = buffer.lines[y].clone();
+        let mut replaced = line[..start].to_string();
+        if let Some(captures) = self.regex.captures_at(&line, start) {
+            captures.expand(&self.replacement, &mut replaced);
+        }
+        replaced.push_str(&line[end..]);
+
+        let new_lines: Vec<String> = replaced.split('\n').map(String::from).collect();
+        let added = new_lines.len() - 1;
+        let offset = new_lines[added].len() - (line.len() - end);
+        buffer.replace_lines(y..y + 1, new_lines);
+
+        if self.last_y != Some(y) {
+            self.lines += 1;
+        }
+        self.count += 1;
+        self.end += added;
+        self.last_y = Some(y + added);
+        self.after(buffer, (y + added, offset), start == end);
+    }
+}
+
+impl<W: Write> Editor<W> {
+    pub fn start_substitution(&mut self, substitute: &Substitute) {
+        if !self.is_viewport_modifiable() {
+            return;
+        }
+        let (start, end) = match self.resolve_range(&substitute.range, ExRange::current()) {
+            Ok(range) => range,
+            Err(err) => return self.toast.error(err.to_string()),
+        };
+
+        // an empty pattern use the last search
+        let mut pattern = match substitute.pattern.is_empty() {
+            true => self.search.clone(),
+            false => substitute.pattern.clone(),
+        };
+        if pattern.is_empty() {
+            self.toast.error("no previous pattern".to_string());
+            return;
+        }
+        match substitute.ignore_case {
+            Some(true) => pattern.push_str("\\c"),
+            Some(false) => pattern.push_str("\\C"),
+            None => {}
+        }
+
+        let viewport = self.viewports.c_viewport();
+        self.substitution = Some(Substitution {
+            regex: search_regex(&pattern, self.search_case()),
+            replacement: replacement_syntax(&substitute.replacement),
+            global: substitute.global,
+            start,
+            end,
+            next: (start, 0),
+            old_lines: viewport.buffer.borrow().lines[start..end].to_vec(),
+            old_cursor: OldCursorPosition::new(self.cursor, viewport.top),
+            count: 0,
+            skipped: 0,
+            lines: 0,
+            last_y: None,
+            search_index: (!viewport.search_pos.is_empty()).then_some(viewport.search_index),
+        });
+        match substitute.confirm {
+            true => self.next_substitution(),
+            false => self.answer_substitution('a'),
+        }
+    }
+
+    // highlight the next match and ask what to do with it, or finish when there is none
+    fn next_substitution(&mut self) {
+        let Some(substitution) = self.substitution.as_ref() else {
+            return;
+        };
+        let title = format!("replace with {} ?", substitution.replacement);
+        let viewport = self.viewports.c_mut_viewport();
+        let Some((y, start, end)) = substitution.find_next(&viewport.buffer.borrow()) else {
+            return self.finish_substitution();
+        };
+        let line = viewport.buffer.borrow().lines[y].clone();
+        let x = line[..start].chars().count() as u16;
+        let len = line[start..end].chars().count().max(1) as u16;
+        viewport.search_pos = vec![(x, y as u16 + viewport.min_vheight, len)];
+        viewport.search_index = 0;
+        self.goto_buffer_pos((x, y as u16));
+        self.set_modal(Box::new(ModalConfirmSubstitute::new(title)));
+    }
+
+    // y: replace, n: skip, a: replace all the remaining, q: stop, l: replace and stop
+    pub fn answer_substitution(&mut self, answer: char) {
+        let Some(mut substitution) = self.substitution.take() else {
+            return;
+        };
+        let mut guard = self.viewports.c_viewport().buffer.borrow_mut();
+        let buffer = &mut *guard;
+        match answer {
+            'y' | 'l' => {
+                if let Some(found) = substitution.find_next(buffer) {
+                    substitution.replace(buffer, found);
+                }
+            }
+            'n' => {
+                if let Some(found) = substitution.find_next(buffer) {
+                    substitution.skip(buffer, found);
+                }
+            }
+            'a' => {
+                while let Some(found) = substitution.find_next(buffer) {
+                    substitution.replace(buffer, found);
+                }
+            }
+            _ => {}
+        }
+        drop(guard);
+        self.substitution = Some(substitution);
+        match answer {
+            'y' | 'n' => self.next_substitution(),
+            _ => self.finish_substitution(),
+        }
+    }
+
+    // the whole substitution is undone at once
+    fn finish_substitution(&mut self) {
+        let Some(substitution) = self.substitution.take() else {
+            return;
+        };
+        self.modal = None;
+        // the preview of the matches gives back its place to the / search
+        let regex = search_regex(&self.search, self.search_case());
+        let viewport = self.viewports.c_mut_viewport();
+        match substitution.search_index {
+            Some(index) => {
+                viewport.find_occurence(&regex);
+                viewport.search_index = index.min(viewport.search_pos.len().saturating_sub(1));
+            }
+            None => viewport.clear_search(),
+        }
+        if substitution.count == 0 {
+            if substitution.skipped == 0 {
+                self.toast.error("pattern not found".to_string());
+            }
+            return;
+        }
+
+        self.undo_actions.push(Action::UndoLines(
+            substitution.old_cursor,
+            substitution.start,
+            substitution.old_lines,
+            substitution.end - substitution.start,
+        ));
+        if let Some(y) = substitution.last_y {
+            self.goto_buffer_pos((0, y as u16));
+        }
+        self.toast.indication(format!(
+            "{} substitutions on {} lines",
+            substitution.count, substitution.lines
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests_substitute {
+    use super::*;
+    use crate::editor::{
+        core::{
+            command::Command,
+            ex::{Address, ExError},
+            mode::Mode,
+            user_command::UserCommands,
+        },
+        test_helpers::{editor_with, lines, TestEditor},
+    };
+
+    fn parse(command: &str) -> Result<Substitute, ExError> {
+        match Command::execute(command, &UserCommands::default())? {
+            Action::Substitute(substitute) => Ok(substitute),
+            action => panic!("{command} is not a substitution but {action:?}"),
+        }
+    }
+
+    fn substitute(editor: &mut TestEditor, command: &str) {
+        let substitute = parse(command).unwrap();
+        Action::Substitute(substitute).execute(editor).unwrap();
+    }
+
+    #[test]
+    fn test_parse_substitute() {
+        let parsed = parse("%s#a/b#\\#c#gi").unwrap();
+        assert_eq!(parsed.range, Some(ExRange::all()));
+        assert_eq!(parsed.pattern, "a/b");
+        assert_eq!(parsed.replacement, "#c");
+        assert!(parsed.global && !parsed.confirm);
+        assert_eq!(parsed.ignore_case, Some(true));
+
+        let parsed = parse("2,4s/a").unwrap();
+        let range = ExRange {
+            start: Address::Line(2),
+            end: Address::Line(4),
+        };
+        assert_eq!(parsed.range, Some(range));
+        assert_eq!(parsed.replacement, "");
+        assert!(parse("'<,'>s/a/b/c").unwrap().confirm);
+        // a letter isnt a delimiter, :set is another command
+        assert!(parse("sxax").is_err());
+        assert!(parse("s/a/b/x").is_err());
+    }
+
+    #[test]
+    fn test_substitute_groups_and_undo() {
+        let mut editor = editor_with(&["let a = 1;", "let b = 2;", "a a"]);
+        substitute(&mut editor, "%s/let (\\w)/const \\1/");
+        assert_eq!(lines(&editor), vec!["const a = 1;", "const b = 2;", "a a"]);
+
+        // without g only the first match of the line
+        substitute(&mut editor, "3s/a/$0$0/");
+        assert_eq!(lines(&editor)[2], "aa a");
+
+        Action::Undo.execute(&mut editor).unwrap();
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["let a = 1;", "let b = 2;", "a a"]);
+    }
+
+    #[test]
+    fn test_substitute_global_empty_match_and_new_line() {
+        let mut editor = editor_with(&["abc", "a,b"]);
+        substitute(&mut editor, "s/x*/-/g");
+        assert_eq!(lines(&editor)[0], "-a-b-c-");
+
+        substitute(&mut editor, "2s/,/\\n/g");
+        assert_eq!(lines(&editor), vec!["-a-b-c-", "a", "b"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["-a-b-c-", "a,b"]);
+    }
+
+    #[test]
+    fn test_substitute_confirm_and_visual_range() {
+        let mut editor = editor_with(&["x x", "x", "x"]);
+        editor.cursor = (0, 0);
+        Action::EnterMode(Mode::Visual)
+            .execute(&mut editor)
+            .unwrap();
+        editor.cursor = (0, 1);
+        Action::EnterMode(Mode::Command)
+            .execute(&mut editor)
+            .unwrap();
+        assert_eq!(editor.command, "'<,'>");
+
+        substitute(&mut editor, "'<,'>s/x/y/gc");
+        assert!(editor.modal.is_some());
+        Action::SubstituteAnswer('y').execute(&mut editor).unwrap();
+        Action::SubstituteAnswer('n').execute(&mut editor).unwrap();
+        assert_eq!(editor.viewports.c_viewport().search_pos.len(), 1);
+        Action::SubstituteAnswer('a').execute(&mut editor).unwrap();
+        assert!(editor.modal.is_none());
+        // the last line is out of the visual range
+        assert_eq!(lines(&editor), vec!["y x", "y", "x"]);
+
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["x x", "x", "x"]);
+    }
+
+    #[test]
+    fn test_substitute_keeps_the_search() {
+        let mut editor = editor_with(&["a b", "b a"]);
+        editor.search = "b".to_string();
+        let regex = search_regex("b", editor.search_case());
+        editor.viewports.c_mut_viewport().find_occurence(&regex);
+
+        substitute(&mut editor, "%s/a/b/c");
+        Action::SubstituteAnswer('a').execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["b b", "b b"]);
+        // the matches of /b follow the replacements
+        assert_eq!(editor.viewports.c_viewport().search_pos.len(), 4);
+
+        editor.viewports.c_mut_viewport().clear_search();
+        substitute(&mut editor, "%s/b/c/c");
+        Action::SubstituteAnswer('q').execute(&mut editor).unwrap();
+        assert!(editor.viewports.c_viewport().search_pos.is_empty());
+    }
+}
@@ -0,0 +1,526 @@
+use std::{cell::RefCell, io::Write};
+
+use mlua::{Function, Lua, Table, Value};
+
+use crate::{editor::Editor, helper::lua_handler::load_configs};
+
+use super::{
+    actions::action::{Action, OldCursorPosition},
+    ex::{ExError, ExRange},
+    quickfix::{entries_from_lua, ListKind, QuickfixList},
+    wildmenu::Complete,
+};
+
+// the number of arguments of a command like the -nargs of vim
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Nargs {
+    Zero,     // "0"
+    One,      // "1", everything typed is the argument
+    Optional, // "?"
+    Any,      // "*", the arguments are separated by spaces
+    Many,     // "+"
+}
+
+impl Nargs {
+    fn parse(nargs: &str) -> Option<Self> {
+        match nargs {
+            "0" => Some(Nargs::Zero),
+            "1" => Some(Nargs::One),
+            "?" => Some(Nargs::Optional),
+            "*" => Some(Nargs::Any),
+            "+" => Some(Nargs::Many),
+            _ => None,
+        }
+    }
+}
+
+// a command of the commands table of config.lua
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserCommandDef {
+    pub name: String,
+    pub nargs: Nargs,
+    pub range: Option<ExRange>, // the range used when none is typed, none when it takes no range
+    pub bang: bool,
+    pub complete: Complete,
+}
+
+// a user command typed on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserCommand {
+    pub name: String,
+    pub range: Option<ExRange>,
+    pub bang: bool,
+    pub args: String,
+}
+
+// the commands of the configs evaluated once in a lua kept by the editor, their functions keep
+// their upvalues from a call to the next one
+pub struct UserCommands {
+    pub lua: Lua,
+    commands: Vec<(String, Table)>, // the user ones hide the default ones
+}
+
+// no command, for what doesnt read the configs
+impl Default for UserCommands {
+    fn default() -> Self {
+        Self::new(Lua::new(), vec![])
+    }
+}
+
+impl UserCommands {
+    pub fn load() -> Self {
+        let lua = Lua::new();
+        let configs = load_configs(&lua);
+        Self::new(lua, configs)
+    }
+
+    fn new(lua: Lua, configs: Vec<Table>) -> Self {
+        let mut commands: Vec<(String, Table)> = vec![];
+        for config in configs {
+            let Ok(table) = config.get::<Table>("commands") else {
+                continue;
+            };
+            for (name, command) in table.pairs::<String, Table>().flatten() {
+                if !commands.iter().any(|(n, _)| *n == name) {
+                    commands.push((name, command));
+                }
+            }
+        }
+        Self { lua, commands }
+    }
+
+    // the command named like this or the only one starting with it
+    pub fn find(&self, name: &str) -> Option<UserCommandDef> {
+        let exact = self.commands.iter().find(|(n, _)| n == name);
+        let mut starting = self.commands.iter().filter(|(n, _)| n.starts_with(name));
+        let (name, table) = match (exact, starting.next(), starting.next()) {
+            (Some(command), _, _) | (None, Some(command), None) => command,
+            _ => return None,
+        };
+        UserCommandDef::from_table(name, table).ok()
+    }
+
+    // the user commands starting like the typed name for the completion
+    pub fn names(&self, typed: &str) -> Vec<String> {
+        (self.commands.iter())
+            .map(|(name, _)| name.clone())
+            .filter(|name| name.starts_with(typed))
+            .collect()
+    }
+}
+
+// the innermost message of a lua error without its traceback
+fn lua_message(err: &mlua::Error) -> String {
+    match err {
+        mlua::Error::CallbackError { cause, .. } => lua_message(cause),
+        mlua::Error::RuntimeError(message) => message.lines().next().unwrap_or("").to_string(),
+        err => err.to_string(),
+    }
+}
+
+fn lua_error(err: mlua::Error) -> ExError {
+    ExError::LuaError(lua_message(&err))
+}
+
+impl UserCommandDef {
+    // range is true for the current line or "%" for the whole file
+    fn from_table(name: &str, table: &Table) -> mlua::Result<Self> {
+        let nargs = match table.get::<Value>("nargs")? {
+            Value::Nil => Some(Nargs::Zero),
+            Value::Integer(n) => Nargs::parse(&n.to_string()),
+            Value::String(nargs) => Nargs::parse(&nargs.to_str()?),
+            _ => None,
+        };
+        let range = match table.get::<Value>("range")? {
+            Value::Nil | Value::Boolean(false) => None,
+            Value::Boolean(true) => Some(ExRange::current()),
+            Value::String(range) if range.to_str()? == "%" => Some(ExRange::all()),
+            _ => return Err(mlua::Error::runtime(format!("{name}: invalid range"))),
+        };
+        let complete = match table.get::<Option<String>>("complete")?.as_deref() {
+            Some("file") => Complete::File,
+            Some("option") => Complete::Option,
+            Some("buffer") => Complete::Buffer,
+            _ => Complete::Nothing,
+        };
+        Ok(Self {
+            name: name.to_string(),
+            nargs: nargs.ok_or_else(|| mlua::Error::runtime(format!("{name}: invalid nargs")))?,
+            range,
+            bang: table.get::<Option<bool>>("bang")?.unwrap_or(false),
+            complete,
+        })
+    }
+
+    // the arguments after the name like for the built-in commands
+    pub fn parse(&self, range: Option<ExRange>, rest: &str) -> Result<UserCommand, ExError> {
+        let (bang, args) = match rest.strip_prefix('!') {
+            Some(args) => (true, args),
+            None => (false, rest),
+        };
+        if bang && !self.bang {
+            return Err(ExError::NoBang);
+        }
+        if range.is_some() && self.range.is_none() {
+            return Err(ExError::NoRange);
+        }
+        let args = args.trim();
+        match self.nargs {
+            Nargs::Zero if !args.is_empty() => {
+                return Err(ExError::TrailingCharacters(args.to_string()));
+            }
+            Nargs::One | Nargs::Many if args.is_empty() => return Err(ExError::ArgumentRequired),
+            _ => {}
+        }
+        Ok(UserCommand {
+            name: self.name.clone(),
+            range,
+            bang,
+            args: args.to_string(),
+        })
+    }
+
+    // the args as a single string and fargs split like vim does for the command
+    fn fargs(&self, args: &str) -> Vec<String> {
+        match self.nargs {
+            Nargs::Zero => vec![],
+            _ if args.is_empty() => vec![],
+            Nargs::One | Nargs::Optional => vec![args.to_string()],
+            Nargs::Any | Nargs::Many => args.split_whitespace().map(String::from).collect(),
+        }
+    }
+}
+
+// the functions given to the command, the lines and the cursor start at 1
+fn editor_api<'scope, W: Write>(
+    lua: &Lua,
+    scope: &'scope mlua::Scope<'scope, '_>,
+    editor: &'scope RefCell<&mut Editor<W>>,
+) -> mlua::Result<Table> {
+    let api = lua.create_table()?;
+    // a line of the buffer or the one after the last line when it is inserted
+    let check_line = |line: usize, after_last: bool| {
+        let len = editor.borrow().buffer_len() + after_last as usize;
+        match (1..=len).contains(&line) {
+            true => Ok(line - 1),
+            false => Err(mlua::Error::runtime(format!("invalid line {line}"))),
+        }
+    };
+
+    api.set(
+        "line_count",
+        scope.create_function(|_, ()| Ok(editor.borrow().buffer_len()))?,
+    )?;
+    api.set(
+        "path",
+        scope.create_function(|_, ()| {
+            Ok(editor
+                .borrow()
+                .viewports
+                .c_viewport()
+                .buffer
+                .borrow()
+                .path
+                .clone())
+        })?,
+    )?;
+    // the lines first to last included
+    api.set(
+        "get_lines",
+        scope.create_function(move |_, (first, last): (usize, Option<usize>)| {
+            let start = check_line(first, false)?;
+            let end = check_line(last.unwrap_or(first), false)? + 1;
+            let editor = editor.borrow();
+            let lines = &editor.viewports.c_viewport().buffer.borrow().lines;
+            Ok(lines[start..end.max(start)].to_vec())
+        })?,
+    )?;
+    // replace the lines first to last with the new ones, last at first - 1 only insert them
+    api.set(
+        "set_lines",
+        scope.create_function(
+            move |_, (first, last, lines): (usize, usize, Vec<String>)| {
+                let start = check_line(first, true)?;
+                let end = match last + 1 == first {
+                    true => start,
+                    false => check_line(last, false)? + 1,
+                };
+                if end < start {
+                    return Err(mlua::Error::runtime(format!(
+                        "invalid range {first},{last}"
+                    )));
+                }
+                let mut editor = editor.borrow_mut();
+                if !editor.viewports.c_viewport().modifiable {
+                    return Err(mlua::Error::runtime("the viewport is not modifiable"));
+                }
+                editor.edit_lines(start, end - start, lines);
+                Ok(())
+            },
+        )?,
+    )?;
+    api.set(
+        "get_cursor",
+        scope.create_function(|_, ()| {
+            let (x, y) = editor.borrow().v_cursor();
+            Ok((y as usize + 1, x as usize + 1))
+        })?,
+    )?;
+    api.set(
+        "set_cursor",
+        scope.create_function(move |_, (line, col): (usize, Option<usize>)| {
+            let y = check_line(line, false)?;
+            let x = col.unwrap_or(1).saturating_sub(1);
+            editor
+                .borrow_mut()
+                .goto_pos((x, y))
+                .map_err(|err| mlua::Error::runtime(err.to_string()))
+        })?,
+    )?;
+    api.set(
+        "toast",
+        scope.create_function(|_, message: String| {
+            editor.borrow_mut().toast.indication(message);
+            Ok(())
+        })?,
+    )?;
+    api.set(
+        "error",
+        scope.create_function(|_, message: String| {
+            editor.borrow_mut().toast.error(message);
+            Ok(())
+        })?,
+    )?;
+    // the lists take and give { filename, lnum, col, text } tables like setqflist of vim
+    for (name, kind) in [("qf", ListKind::Quickfix), ("loc", ListKind::Location)] {
+        api.set(
+            format!("set{name}list"),
+            scope.create_function(move |_, (entries, title): (Table, Option<String>)| {
+                let title = title.unwrap_or_else(|| format!("set{name}list"));
+                let list = QuickfixList::new(title, entries_from_lua(entries));
+                editor.borrow_mut().set_list(kind, list);
+                Ok(())
+            })?,
+        )?;
+        api.set(
+            format!("get{name}list"),
+            scope.create_function(move |lua, ()| {
+                let mut editor = editor.borrow_mut();
+                (editor.list_mut(kind).entries.iter())
+                    .map(|entry| entry.to_lua(lua))
+                    .collect::<mlua::Result<Vec<Table>>>()
+            })?,
+        )?;
+    }
+    Ok(api)
+}
+
+impl<W: Write> Editor<W> {
+    // run the function of a command of config.lua with its arguments and range
+    pub fn run_user_command(&mut self, command: &UserCommand) -> Result<(), ExError> {
+        let lua = self.user_commands.lua.clone();
+        let not_a_command = || ExError::NotACommand(command.name.clone());
+        let (name, table) = (self.user_commands.commands.iter())
+            .find(|(name, _)| *name == command.name)
+            .cloned()
+            .ok_or_else(not_a_command)?;
+        let def = UserCommandDef::from_table(&name, &table).map_err(lua_error)?;
+        let run = table.get::<Function>("run").map_err(lua_error)?;
+
+        let default = def.range.clone().unwrap_or_else(ExRange::current);
+        let (start, end) = self.resolve_range(&command.range, default)?;
+        let opts = lua.create_table().map_err(lua_error)?;
+        let fields = [
+            (
+                "name",
+                Value::String(lua.create_string(&name).map_err(lua_error)?),
+            ),
+            (
+                "args",
+                Value::String(lua.create_string(&command.args).map_err(lua_error)?),
+            ),
+            ("bang", Value::Boolean(command.bang)),
+            ("range", Value::Boolean(command.range.is_some())),
+            ("line1", Value::Integer(start as i64 + 1)),
+            ("line2", Value::Integer(end as i64)),
+        ];
+        for (key, value) in fields {
+            opts.set(key, value).map_err(lua_error)?;
+        }
+        opts.set("fargs", def.fargs(&command.args))
+            .map_err(lua_error)?;
+        self.call_lua_command(&lua, run, opts)
+    }
+
+    // everything the command changes is undone at once
+    fn call_lua_command(&mut self, lua: &Lua, run: Function, opts: Table) -> Result<(), ExError> {
+        let viewport = self.viewports.c_viewport();
+        let old_lines = viewport.buffer.borrow().lines.clone();
+        let old_cursor = OldCursorPosition::new(self.cursor, viewport.top);
+        let undo_len = self.undo_actions.len();
+
+        let editor = RefCell::new(&mut *self);
+        let result = lua.scope(|scope| {
+            let api = editor_api(lua, scope, &editor)?;
+            run.call::<()>((opts, api))
+        });
+
+        self.undo_actions.truncate(undo_len);
+        let new_len = self.buffer_len();
+        if self.viewports.c_viewport().buffer.borrow().lines != old_lines {
+            self.undo_actions
+                .push(Action::UndoLines(old_cursor, 0, old_lines, new_len));
+            // the cursor could be after the last line
+            let (x, y) = self.v_cursor();
+            if y as usize >= new_len {
+                let _ = self.goto_pos((x as usize, y as usize));
+            }
+        }
+        result.map_err(lua_error)
+    }
+}
+
+#[cfg(test)]
+mod tests_user_command {
+    use super::*;
+    use crate::editor::{
+        core::command::Command,
+        test_helpers::{editor_with, lines},
+    };
+
+    fn def(lua_code: &str) -> UserCommandDef {
+        let lua = Lua::new();
+        let table = lua.load(lua_code).eval::<Table>().unwrap();
+        UserCommandDef::from_table("Cmd", &table).unwrap()
+    }
+
+    #[test]
+    fn test_parse_user_command() {
+        let one = def(r#"{ nargs = "1", range = true, bang = true }"#);
+        let command = one.parse(None, "! a b ").unwrap();
+        assert!(command.bang);
+        assert_eq!(command.args, "a b");
+        assert_eq!(one.fargs(&command.args), vec!["a b"]);
+        assert_eq!(one.parse(None, ""), Err(ExError::ArgumentRequired));
+
+        let zero = def(r#"{ range = "%" }"#);
+        assert_eq!(zero.range, Some(ExRange::all()));
+        assert_eq!(
+            zero.parse(None, " x"),
+            Err(ExError::TrailingCharacters("x".to_string()))
+        );
+        assert_eq!(zero.parse(None, "!"), Err(ExError::NoBang));
+
+        let any = def(r#"{ nargs = "*" }"#);
+        assert_eq!(any.fargs("a  b"), vec!["a", "b"]);
+        assert_eq!(any.parse(Some(ExRange::all()), ""), Err(ExError::NoRange));
+    }
+
+    #[test]
+    fn test_default_trim_command() {
+        let mut editor = editor_with(&["a  ", "b\t", "c"]);
+        editor.command = "Trim".to_string();
+        Action::ExecuteCommand.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a", "b", "c"]);
+        Action::Undo.execute(&mut editor).unwrap();
+        assert_eq!(lines(&editor), vec!["a  ", "b\t", "c"]);
+
+        let commands = &editor.user_commands;
+        assert!(commands.names("Tr").contains(&"Trim".to_string()));
+        assert!(matches!(
+            Command::execute("1,2Tri", commands),
+            Ok(Action::UserCommand(UserCommand { ref name, .. })) if name == "Trim"
+        ));
+    }
+
+    #[test]
+    fn test_commands_keep_their_state() -> mlua::Result<()> {
+        let mut editor = editor_with(&["a"]);
+        let lua = Lua::new();
+        let config = lua
+            .load(
+                r#"local count = 0
+                return { commands = { Count = { run = function(_, editor)
+                    count = count + 1
+                    editor.toast("run " .. count)
+                end } } }"#,
+            )
+            .eval::<Table>()?;
+        editor.user_commands = UserCommands::new(lua, vec![config]);
+        for _ in 0..2 {
+            editor.command = "Count".to_string();
+            Action::ExecuteCommand.execute(&mut editor).unwrap();
+            while let Some(action) = editor.buffer_actions.pop() {
+                action.execute(&mut editor).unwrap();
+            }
+        }
+        assert_eq!(editor.toast._last_message(), Some("run 2"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_editor_api() {
+        let mut editor = editor_with(&["one", "two", "three"]);
+        let lua = Lua::new();
+        let run = lua
+            .load(
+                r#"function(opts, editor)
+                    local lines = editor.get_lines(opts.line1, opts.line2)
+                    for i, line in ipairs(lines) do lines[i] = opts.fargs[1] .. line end
+                    editor.set_lines(opts.line1, opts.line2, lines)
+                    editor.set_lines(editor.line_count() + 1, editor.line_count(), { "end" })
+                    editor.set_cursor(2, 3)
+                    editor.toast(editor.path() .. " " .. editor.line_count())
+                end"#,
+            )
+            .eval::<Function>()
+            .unwrap();
+        let opts = lua.create_table().unwrap();
+        opts.set("line1", 1).unwrap();
+        opts.set("line2", 2).unwrap();
+        opts.set("fargs", vec!["- "]).unwrap();
+        editor.call_lua_command(&lua, run, opts).unwrap();
+        assert_eq!(lines(&editor), vec!["- one", "- two", "three", "end"]);
+        assert_eq!(editor.v_cursor(), (2, 1));
+        assert_eq!(editor.toast._last_message(), Some("a.txt 4"));
+
+        // the error of the function is shown and its changes are kept
+        let run = lua
+            .load(r#"function(_, editor) editor.set_lines(1, 1, {}) editor.get_lines(9) end"#)
+            .eval::<Function>()
+            .unwrap();
+        let err = editor.call_lua_command(&lua, run, lua.create_table().unwrap());
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "E5108: Error executing lua: invalid line 9"
+        );
+        assert_eq!(lines(&editor), vec!["- two", "three", "end"]);
+    }
+
+    #[test]
+    fn test_list_api() {
+        let mut editor = editor_with(&["one"]);
+        let lua = Lua::new();
+        let run = lua
+            .load(
+                r#"function(_, editor)
+                    editor.setqflist({ { filename = "a.rs", lnum = 2, col = 3, text = "todo" } })
+                    editor.setloclist({ "b.rs:4:1: fixme", 42 }, "mine")
+                    local entry = editor.getqflist()[1]
+                    editor.toast(entry.filename .. " " .. entry.lnum .. " " .. entry.col)
+                end"#,
+            )
+            .eval::<Function>()
+            .unwrap();
+        editor
+            .call_lua_command(&lua, run, lua.create_table().unwrap())
+            .unwrap();
+        assert_eq!(editor.quickfix.title, "setqflist");
+        assert_eq!(editor.quickfix.entries[0].display(), "a.rs:2:3: todo");
+        let loclist = &editor.viewports.c_viewport().loclist;
+        assert_eq!(loclist.title, "mine");
+        assert_eq!(loclist.entries.len(), 1);
+        assert_eq!(editor.toast._last_message(), Some("a.rs 2 3"));
+        assert_eq!(lines(&editor), vec!["one"]);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{io::Write, path::Path};
+
+use crate::editor::Editor;
+
+use super::{
+    command::{split_name, Command},
+    ex::ExRange,
+    options::{find_option, option_names},
+};
+
+// the modes of :map
+const KEYBIND_MODES: [&str; 6] = [
+    "command", "explorer", "insert", "normal", "search", "visual",
+];
+
+// what the argument of a command is completed with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Complete {
+    Nothing,
+    File,
+    Mode,
+    Option,
+    Buffer,
+}
+
+// the candidates of a completion shown above the command line, tab goes through them and comes
+// back to what was typed after the last one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wildmenu {
+    pub candidates: Vec<String>,
+    pub index: Option<usize>,
+    start: usize,  // byte index of the completed word in the command
+    typed: String, // the word before it was completed
+    rest: String,  // what follows the cursor
+}
+
+impl Wildmenu {
+    fn select(&mut self, backward: bool) {
+        let len = self.candidates.len();
+        self.index = match (self.index, backward) {
+            (None, false) => Some(0),
+            (None, true) => Some(len - 1),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            (Some(i), true) if i > 0 => Some(i - 1),
+            _ => None,
+        };
+    }
+
+    fn word(&self) -> &str {
+        match self.index {
+            Some(i) => &self.candidates[i],
+            None => &self.typed,
+        }
+    }
+}
+
+// the entries of the directory of the word which starts like its file name, the dirs end with /
+fn file_candidates(word: &str) -> Vec<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(i) => (&word[..=i], &word[i + 1..]),
+        None => ("", word),
+    };
+    let read_dir = match dir.is_empty() {
+        true => Path::new("."),
+        false => Path::new(dir),
+    };
+    let Ok(entries) = std::fs::read_dir(read_dir) else {
+        return vec![];
+    };
+    let mut candidates: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            // the hidden files only when a dot is typed
+            let hidden = name.starts_with('.') && !prefix.starts_with('.');
+            if hidden || !name.starts_with(prefix) {
+                return None;
+            }
+            let slash = match entry.path().is_dir() {
+                true => "/",
+                false => "",
+            };
+            Some(format!("{dir}{name}{slash}"))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+impl<W: Write> Editor<W> {
+    // the start of the word to complete in the line and what it can be replaced with
+    fn completion_candidates(&self, line: &str) -> Option<(usize, Vec<String>)> {
+        let command = line.trim_start_matches([' ', ':']);
+        let (_, rest) = ExRange::parse_prefix(command).ok()?;
+        let rest = rest.trim_start();
+        let (name, args) = split_name(rest);
+        if args.is_empty() {
+            return Some((
+                line.len() - rest.len(),
+                Command::names(name, &self.user_commands),
+            ));
+        }
+
+        let args = args.strip_prefix('!').unwrap_or(args);
+        if !args.starts_with(' ') {
+            return None;
+        }
+        let start = line.rfind(' ').map(|i| i + 1).unwrap_or(line.len());
+        let word = &line[start..];
+        let candidates = match Command::completion(name, &self.user_commands) {
+            Complete::Nothing => vec![],
+            Complete::File => file_candidates(word),
+            Complete::Mode => KEYBIND_MODES
+                .iter()
+                .filter(|mode| mode.starts_with(word))
+                .map(|mode| mode.to_string())
+                .collect(),
+            // the value after = isnt completed
+            Complete::Option if word.contains(['=', ':']) => vec![],
+            Complete::Option => {
+                let prefix = ["no", "inv"]
+                    .into_iter()
+                    .find(|prefix| word.starts_with(prefix) && find_option(word).is_none())
+                    .unwrap_or("");
+                option_names(&word[prefix.len()..])
+                    .into_iter()
+                    .map(|name| format!("{prefix}{name}"))
+                    .collect()
+            }
+            Complete::Buffer => self
+                .viewports
+                .values
+                .iter()
+                .map(|v| v.buffer.borrow().name().to_string())
+                .filter(|path| path.contains(word))
+                .collect(),
+        };
+        Some((start, candidates))
+    }
+
+    fn apply_wildmenu(&mut self, wildmenu: Wildmenu) {
+        self.command.truncate(wildmenu.start);
+        self.command.push_str(wildmenu.word());
+        self.command.push_str(&wildmenu.rest);
+        self.cmdline_back = wildmenu.rest.chars().count();
+        // a single candidate is completed without showing the menu
+        self.wildmenu = match wildmenu.candidates.len() {
+            1 => None,
+            _ => Some(wildmenu),
+        };
+    }
+
+    // tab and shift tab on the command line
+    pub fn cmdline_complete(&mut self, backward: bool) {
+        if let Some(mut wildmenu) = self.wildmenu.take() {
+            wildmenu.select(backward);
+            return self.apply_wildmenu(wildmenu);
+        }
+
+        let len = self.command.chars().count();
+        let cursor = self
+            .command
+            .char_indices()
+            .nth(len - self.cmdline_back.min(len))
+            .map(|(i, _)| i)
+            .unwrap_or(self.command.len());
+        let (line, rest) = self.command.split_at(cursor);
+        let Some((start, candidates)) = self.completion_candidates(line) else {
+            return;
+        };
+        if candidates.is_empty() {
+            return;
+        }
+        let mut wildmenu = Wildmenu {
+            candidates,
+            index: None,
+            typed: line[start..].to_string(),
+            rest: rest.to_string(),
+            start,
+        };
+        wildmenu.select(backward);
+        self.apply_wildmenu(wildmenu);
+    }
+}
+
+#[cfg(test)]
+mod tests_wildmenu {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::editor::core::{actions::action::Action, mode::Mode};
+
+    fn editor_with_command(command: &str) -> Editor<Cursor<Vec<u8>>> {
+        let mut editor = Editor::default();
+        Action::EnterMode(Mode::Command)
+            .execute(&mut editor)
+            .unwrap();
+        editor.command = command.to_string();
+        editor
+    }
+
+    #[test]
+    fn test_complete_command_names() -> anyhow::Result<()> {
+        let mut editor = editor_with_command("1,2no");
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "1,2nohlsearch");
+        let candidates = &editor.wildmenu.as_ref().unwrap().candidates;
+        assert_eq!(candidates, &vec!["nohlsearch", "normal"]);
+
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "1,2normal");
+        // after the last one it comes back to what was typed
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "1,2no");
+        Action::CmdlineComplete(true).execute(&mut editor)?;
+        assert_eq!(editor.command, "1,2normal");
+
+        // typing closes the menu
+        Action::AddCommandChar(' ').execute(&mut editor)?;
+        assert!(editor.wildmenu.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_complete_arguments() -> anyhow::Result<()> {
+        let mut editor = editor_with_command("map no");
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "map normal");
+        assert!(editor.wildmenu.is_none());
+        let mut editor = editor_with_command("set noignor");
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "set noignorecase");
+
+        // the file of the crate, the directories end with a /
+        let mut editor = editor_with_command("w src/editor/core/wildm");
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "w src/editor/core/wildmenu.rs");
+        let mut editor = editor_with_command("w sr");
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "w src/");
+
+        // the text after the cursor is kept
+        let mut editor = editor_with_command("cop x");
+        editor.cmdline_back = 2;
+        Action::CmdlineComplete(false).execute(&mut editor)?;
+        assert_eq!(editor.command, "copen x");
+        assert_eq!(editor.cmdline_back, 2);
+        Ok(())
+    }
+}
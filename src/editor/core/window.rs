@@ -0,0 +1,368 @@
+use std::io::Write;
+
+use crate::{
+    editor::{Editor, TERMINAL_SIZE_MINUS},
+    viewports::layout::{Direction, Layout, Rect, Window},
+};
+
+use super::{actions::action::Action, operator::Parsed};
+
+impl From<&str> for Direction {
+    fn from(value: &str) -> Self {
+        match value {
+            "left" => Direction::Left,
+            "down" => Direction::Down,
+            "up" => Direction::Up,
+            "right" => Direction::Right,
+            _ => panic!("Invalid Direction string: {}", value),
+        }
+    }
+}
+
+// the keys after Ctrl-w with an optional count like Ctrl-w 5+
+pub fn window_command(keys: &str) -> Parsed<Action> {
+    let count_len = keys
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(keys.len());
+    let (count, key) = keys.split_at(count_len);
+    if count.starts_with('0') {
+        return Parsed::Invalid;
+    }
+    let count = count.parse::<i32>().unwrap_or(1);
+    let Some(key) = key.chars().next() else {
+        return Parsed::Pending;
+    };
+    let action = match key {
+        's' | 'S' => Action::Split(false, None),
+        'v' => Action::Split(true, None),
+        'h' => Action::FocusWindow(Direction::Left),
+        'j' => Action::FocusWindow(Direction::Down),
+        'k' => Action::FocusWindow(Direction::Up),
+        'l' => Action::FocusWindow(Direction::Right),
+        'w' => Action::NextWindow,
+        'c' | 'q' => Action::CloseWindow,
+        'o' => Action::OnlyWindow,
+        '=' => Action::EqualizeWindows,
+        '+' => Action::ResizeWindow(false, count),
+        '-' => Action::ResizeWindow(false, -count),
+        '>' => Action::ResizeWindow(true, count),
+        '<' => Action::ResizeWindow(true, -count),
+        _ => return Parsed::Invalid,
+    };
+    Parsed::Done(action)
+}
+
+impl<W: Write> Editor<W> {
+    // the screen without the names of the viewports at the top and the status and command lines
+    fn screen_rect(&self) -> Rect {
+        let height = self.size.1.saturating_sub(TERMINAL_SIZE_MINUS + 1);
+        Rect::new(0, 1, self.size.0, height)
+    }
+
+    pub fn window_count(&self) -> usize {
+        self.viewports.layout.windows().len()
+    }
+
+    // the focused viewport takes the place of its window and the cursor stays in it
+    pub fn layout_windows(&mut self) {
+        let rect = self.screen_rect();
+        let viewports = &mut self.viewports;
+        viewports.layout.arrange(rect);
+        let Some(rect) = viewports.layout.window(viewports.window).map(|w| w.rect) else {
+            return;
+        };
+        let Some(viewport) = viewports.values.get_mut(viewports.index) else {
+            return;
+        };
+        if viewport.is_popup || viewport.rect() == rect {
+            return;
+        }
+        viewport.set_rect(rect);
+        if viewports.is_explorer {
+            return;
+        }
+        let max_x = viewport.max_vwidth().saturating_sub(1);
+        if self.cursor.0 > max_x {
+            viewport.left += self.cursor.0 - max_x;
+            self.cursor.0 = max_x;
+        }
+        let max_y = viewport.max_vheight().saturating_sub(1);
+        if self.cursor.1 > max_y {
+            viewport.top += self.cursor.1 - max_y;
+            self.cursor.1 = max_y;
+        }
+    }
+
+    // the window keeps the scroll and the cursor of its viewport while another one is focused
+    fn save_window(&mut self) {
+        let viewports = &mut self.viewports;
+        let viewport = &viewports.values[viewports.index];
+        let (top, left) = (viewport.top, viewport.left);
+        if let Some(window) = viewports.layout.window_mut(viewports.window) {
+            window.viewport = viewports.index;
+            window.top = top;
+            window.left = left;
+            window.cursor = self.cursor;
+        }
+    }
+
+    fn focus_window(&mut self, id: usize) {
+        self.save_window();
+        let Some(window) = self.viewports.layout.window(id).cloned() else {
+            return;
+        };
+        let viewports = &mut self.viewports;
+        viewports.window = id;
+        viewports.index = window.viewport.min(viewports.values.len() - 1);
+        viewports.is_explorer = false;
+        let viewport = &mut viewports.values[viewports.index];
+        viewport.top = window.top;
+        viewport.left = window.left;
+        self.cursor = window.cursor;
+        // another window may have removed the line of the cursor
+        if self.v_cursor().1 as usize >= self.viewports.c_viewport().get_buffer_len() {
+            self.reset_cursor();
+        }
+        self.layout_windows();
+    }
+
+    // the new window shows the same buffer in its own viewport and is focused, above or on the
+    // left like in vim
+    pub fn split_window(&mut self, vertical: bool) {
+        self.save_window();
+        let viewports = &mut self.viewports;
+        let id = viewports
+            .layout
+            .windows()
+            .iter()
+            .map(|w| w.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let viewport = viewports.values[viewports.index].split();
+        viewports.values.push(viewport);
+        let window = Window {
+            id,
+            viewport: viewports.values.len() - 1,
+            ..viewports.layout.window(viewports.window).unwrap().clone()
+        };
+        viewports.layout.split(viewports.window, vertical, window);
+        self.focus_window(id);
+    }
+
+    // the window at the cursor in this direction
+    pub fn focus_direction(&mut self, direction: Direction) {
+        let viewports = &self.viewports;
+        if let Some(id) = viewports
+            .layout
+            .neighbor(viewports.window, direction, self.cursor)
+        {
+            self.focus_window(id);
+        }
+    }
+
+    pub fn focus_next_window(&mut self) {
+        let windows = self.viewports.layout.windows();
+        let index = windows
+            .iter()
+            .position(|w| w.id == self.viewports.window)
+            .unwrap_or(0);
+        let id = windows[(index + 1) % windows.len()].id;
+        self.focus_window(id);
+    }
+
+    // the next window is focused, the viewport of the closed one stays open unless it was a copy
+    pub fn close_window(&mut self) {
+        if self.window_count() == 1 {
+            self.toast
+                .error("E444: Cannot close last window".to_string());
+            return;
+        }
+        let closed = self.viewports.window;
+        self.focus_next_window();
+        self.viewports.layout.remove(closed);
+        self.viewports.drop_copies();
+        self.layout_windows();
+    }
+
+    pub fn only_window(&mut self) {
+        self.save_window();
+        let viewports = &mut self.viewports;
+        if let Some(window) = viewports.layout.window(viewports.window).cloned() {
+            viewports.layout = Layout::Window(window);
+        }
+        viewports.drop_copies();
+        self.layout_windows();
+    }
+
+    pub fn equalize_windows(&mut self) {
+        self.viewports.layout.equalize();
+        self.layout_windows();
+    }
+
+    // Ctrl-w + and - change the height, > and < the width
+    pub fn resize_window(&mut self, vertical: bool, delta: i32) {
+        let viewports = &mut self.viewports;
+        viewports.layout.resize(viewports.window, vertical, delta);
+        self.layout_windows();
+    }
+}
+
+#[cfg(test)]
+mod tests_window {
+    use std::{io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{
+        buff::Buffer,
+        editor::core::{command::Command, user_command::UserCommands},
+    };
+
+    fn editor_with(lines: usize) -> Editor<Cursor<Vec<u8>>> {
+        let mut editor = Editor::default();
+        editor.size = (81, 25);
+        *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new_tmp(
+            (0..lines).map(|i| i.to_string()).collect(),
+            "a.txt".to_string(),
+        );
+        editor.layout_windows();
+        editor
+    }
+
+    fn run(editor: &mut Editor<Cursor<Vec<u8>>>, action: Action) {
+        action.execute(editor).unwrap();
+    }
+
+    #[test]
+    fn test_window_keys() {
+        assert_eq!(window_command("v"), Parsed::Done(Action::Split(true, None)));
+        assert_eq!(window_command("1"), Parsed::Pending);
+        assert_eq!(
+            window_command("12<"),
+            Parsed::Done(Action::ResizeWindow(true, -12))
+        );
+        assert_eq!(window_command("0+"), Parsed::Invalid);
+        assert_eq!(window_command("x"), Parsed::Invalid);
+    }
+
+    #[test]
+    fn test_split_keeps_the_scroll_of_each_window() {
+        let mut editor = editor_with(100);
+        run(&mut editor, Action::Split(false, None));
+        assert_eq!(editor.window_count(), 2);
+        let viewport = editor.viewports.c_viewport();
+        assert_eq!((viewport.min_vheight, viewport.vheight), (1, 12));
+
+        // the bottom window is scrolled and the top one keeps its place
+        run(&mut editor, Action::FocusWindow(Direction::Down));
+        assert_eq!(editor.viewports.c_viewport().min_vheight, 13);
+        for _ in 0..20 {
+            run(&mut editor, Action::MoveDown);
+        }
+        assert_eq!(editor.v_cursor().1, 20);
+        let top = editor.viewports.c_viewport().top;
+        assert!(top > 0);
+
+        run(&mut editor, Action::FocusWindow(Direction::Up));
+        assert_eq!(editor.v_cursor().1, 0);
+        assert_eq!(editor.viewports.c_viewport().top, 0);
+        run(&mut editor, Action::NextWindow);
+        assert_eq!(editor.v_cursor().1, 20);
+        assert_eq!(editor.viewports.c_viewport().top, top);
+
+        run(&mut editor, Action::CloseWindow);
+        assert_eq!(editor.window_count(), 1);
+        assert_eq!(editor.viewports.c_viewport().vheight, 23);
+        run(&mut editor, Action::CloseWindow);
+        assert_eq!(
+            editor.toast._last_message(),
+            Some("E444: Cannot close last window")
+        );
+    }
+
+    #[test]
+    fn test_split_windows_share_the_buffer() {
+        let mut editor = editor_with(10);
+        run(&mut editor, Action::Split(false, None));
+        let values = &editor.viewports.values;
+        assert_eq!(values.len(), 2);
+        assert!(Rc::ptr_eq(&values[0].buffer, &values[1].buffer));
+
+        // the edits are seen by both windows, the search and the cursors arent
+        editor.viewports.c_mut_viewport().search_pos = vec![(0, 1, 1)];
+        editor.viewports.c_mut_viewport().cursors = vec![(0, 2)];
+        editor
+            .viewports
+            .c_viewport()
+            .buffer
+            .borrow_mut()
+            .insert_str(0, 0, "x");
+        run(&mut editor, Action::NextWindow);
+        let viewport = editor.viewports.c_viewport();
+        assert!(viewport.search_pos.is_empty() && viewport.cursors.is_empty());
+        assert_eq!(viewport.buffer.borrow().get(0), Some("x0".to_string()));
+        run(&mut editor, Action::ListBuffers);
+        assert_eq!(editor.toast._last_message(), Some("1 %a+ \"a.txt\""));
+
+        // the viewport of the closed window is dropped
+        run(&mut editor, Action::CloseWindow);
+        assert_eq!(editor.viewports.values.len(), 1);
+        assert_eq!(editor.viewports.c_viewport().search_pos, vec![(0, 1, 1)]);
+    }
+
+    #[test]
+    fn test_vsplit_resize_and_only() {
+        let mut editor = editor_with(10);
+        editor.command = "vsplit Cargo.toml".to_string();
+        run(&mut editor, Action::ExecuteCommand);
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            "Cargo.toml"
+        );
+        assert_eq!(
+            editor.viewports.c_viewport().rect(),
+            Rect::new(0, 1, 40, 22)
+        );
+
+        run(&mut editor, Action::ResizeWindow(true, 10));
+        assert_eq!(editor.viewports.c_viewport().rect().width, 50);
+        run(&mut editor, Action::EqualizeWindows);
+        assert_eq!(editor.viewports.c_viewport().rect().width, 40);
+
+        run(&mut editor, Action::FocusWindow(Direction::Right));
+        assert_eq!(
+            editor.viewports.c_viewport().buffer.borrow().name(),
+            "a.txt"
+        );
+        assert_eq!(
+            editor.viewports.c_viewport().rect(),
+            Rect::new(41, 1, 40, 22)
+        );
+
+        assert_eq!(
+            Command::execute("on", &UserCommands::default()),
+            Ok(Action::OnlyWindow)
+        );
+        run(&mut editor, Action::OnlyWindow);
+        assert_eq!(editor.window_count(), 1);
+        assert_eq!(
+            editor.viewports.c_viewport().rect(),
+            Rect::new(0, 1, 81, 22)
+        );
+
+        // :q closes the window when there are others
+        run(&mut editor, Action::Split(true, None));
+        run(&mut editor, Action::Quit);
+        assert_eq!(editor.window_count(), 1);
+        assert!(!editor.quit);
+
+        // :qa quits with all the windows
+        run(&mut editor, Action::Split(true, None));
+        assert_eq!(
+            Command::execute("qa", &UserCommands::default()),
+            Ok(Action::QuitAll(false))
+        );
+        run(&mut editor, Action::QuitAll(false));
+        assert!(editor.quit);
+    }
+}
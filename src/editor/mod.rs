@@ -1,14 +1,27 @@
 pub mod core;
+#[cfg(test)]
+pub mod test_helpers;
 pub mod ui;
 
 use crate::editor::fmt::Debug;
 use crate::viewport::Viewport;
+use crate::THEME;
 use crate::{buff::Buffer, viewports::Viewports};
-use crate::{log_message, THEME};
 use anyhow::{Ok, Result};
-use core::actions::action::Action;
+use core::actions::action::{Action, OldCursorPosition};
+use core::autopair::InsertedClosers;
+use core::completion::Completion;
+use core::history::History;
+use core::job::{ResultsJob, JOB_POLL};
 use core::keybind_manager::KeybindManagerV2;
 use core::mode::Mode;
+use core::operator::PendingOperator;
+use core::options::Options;
+use core::quickfix::QuickfixList;
+use core::snippet::ActiveSnippet;
+use core::substitute::Substitution;
+use core::user_command::UserCommands;
+use core::wildmenu::Wildmenu;
 use crossterm::{
     event::{self, read},
     style::Color,
@@ -18,9 +31,6 @@ use std::fmt;
 use std::io::{stdout, Cursor, Stdout, Write};
 use ui::modal::modal_trait::ModalContent;
 use ui::toast::Toast;
-// TERMINAL_LINE_LEN_MINUS if we want the cursor to go behind the last char or stop before,
-// 1: stop on char, 0: stop after the char
-pub const TERMINAL_LINE_LEN_MINUS: u16 = 1;
 pub const TERMINAL_SIZE_MINUS: u16 = 2; // we remove the size of the bottom status, command bar
                                         // are at the end of the line or start move to next or prev line
 
@@ -46,11 +56,30 @@ pub struct Editor<W: Write> {
     pub size: (u16, u16),
     pub cursor: (u16, u16),
     pub visual_cursor: Option<(u16, u16)>,
+    pub cursors: Vec<(u16, u16)>, // buffer position of the other cursors, sorted
 
     pub modal: Option<Box<dyn ModalContent<W>>>,
     pub buffer_x_cursor: u16,
     pub waiting_command: Option<char>,
+    pub pending_operator: Option<PendingOperator>,
+    pub completion: Option<Completion>, // keyword completion list opened in insert mode
+    pub snippet: Option<ActiveSnippet>, // snippet whose tab stops are being filled
+    pub closers: InsertedClosers,       // closing chars inserted by the autopairs
+    pub user_commands: UserCommands,    // the commands of config.lua and their lua
+    pub substitution: Option<Substitution>, // :s waiting for the confirmation of a match
+    pub visual_lines: Option<(u16, u16)>, // buffer lines of the last visual selection
     pub viewports: Viewports,
+    pub options: Options, // the global values of the options, changed by :set
+    pub search_backward: bool, // the last search was started with ?
+    pub search_origin: (u16, u16), // buffer position where the search was started
+    pub search_top: u16,  // and the top of the viewport at that time
+    pub wildmenu: Option<Wildmenu>, // the completions of the command line
+    pub cmdline_back: usize, // chars between the cursor of the command line and its end
+    pub history: History,
+    pub job: Option<ResultsJob>, // command running in the background like :grep
+    pub quickfix: QuickfixList,
+    pub global_marks: Option<Vec<bool>>, // lines left to run a :g command on
+    pub quit: bool,                      // set by :q and :q!, the main loop stops after the action
     pub buffer_actions: Vec<Action>, // allow us to buffer some action to make multiple of them in one time
     pub undo_actions: Vec<Action>,   // create a undo buffer where we put all the action we want
     pub undo_insert_actions: Vec<Action>, // when we are in insert mode all the undo at the same
@@ -100,10 +129,29 @@ impl<W: Write> Editor<W> {
             size,
             cursor: (0, 0),
             visual_cursor: None,
+            cursors: vec![],
             modal: None,
             buffer_x_cursor: 0,
             waiting_command: None,
+            pending_operator: None,
+            completion: None,
+            snippet: None,
+            closers: InsertedClosers::default(),
+            user_commands: UserCommands::load(),
+            substitution: None,
+            visual_lines: None,
+            search_backward: false,
+            search_origin: (0, 0),
+            search_top: 0,
+            cmdline_back: 0,
+            wildmenu: None,
+            job: None,
+            quickfix: QuickfixList::default(),
+            global_marks: None,
+            quit: false,
+            history: History::load(dirs::home_dir().unwrap().join(".rusty/history")),
             viewports,
+            options: Options::from_config(None),
             buffer_actions: vec![],
             undo_actions: vec![],
             undo_insert_actions: vec![],
@@ -164,8 +212,13 @@ impl<W: Write> Editor<W> {
 
     pub fn run(&mut self) -> Result<()> {
         loop {
+            self.poll_job();
             self.check_bounds();
             self.draw()?;
+            // the results of a job are shown while waiting for a key
+            if self.job.is_some() && !event::poll(JOB_POLL)? {
+                continue;
+            }
             let event = read()?;
 
             if let event::Event::Resize(width, height) = event {
@@ -174,13 +227,11 @@ impl<W: Write> Editor<W> {
             }
 
             if let Some(action) = self.handle_action(event)? {
-                if matches!(action, Action::ForceQuit)
-                    || matches!(action, Action::Quit) && self.viewports.viewports_save_status()?
-                {
-                    break;
-                }
                 action.execute(self)?;
             }
+            if self.quit {
+                break;
+            }
         }
         Ok(())
     }
@@ -199,8 +250,16 @@ impl<W: Write> Editor<W> {
         Ok(())
     }
 
+    // scrolloff cannot be more than half of the screen or the cursor couldnt move
+    pub fn c_scrolloff(&self) -> u16 {
+        let max_vheight = self.viewports.c_viewport().max_vheight();
+        let scrolloff = self.option_number("scrolloff") as u16;
+        scrolloff.min(max_vheight.saturating_sub(1) / 2)
+    }
+
     fn move_prev_line(&mut self) {
-        match self.cursor.1 > 0 {
+        let scrolloff = self.c_scrolloff();
+        match self.cursor.1 > scrolloff || self.viewports.c_viewport().top == 0 {
             true => self.cursor.1 = self.cursor.1.saturating_sub(1),
             false => self.viewports.c_mut_viewport().scroll_up(),
         }
@@ -215,9 +274,13 @@ impl<W: Write> Editor<W> {
             .c_viewport()
             .is_under_buffer_len(&self.cursor)
         {
-            match self.cursor.1 >= self.viewports.c_viewport().max_vheight().saturating_sub(1) {
+            let c_viewport = self.viewports.c_viewport();
+            let max_y = c_viewport.max_vheight().saturating_sub(1);
+            let has_hidden_lines =
+                c_viewport.get_buffer_len() > (c_viewport.top + c_viewport.max_vheight()) as usize;
+            match self.cursor.1 >= max_y.saturating_sub(self.c_scrolloff()) && has_hidden_lines {
                 true => self.viewports.c_mut_viewport().scroll_down(),
-                false => self.cursor.1 += 1,
+                false => self.cursor.1 = (self.cursor.1 + 1).min(max_y),
             }
             self.viewports
                 .c_mut_viewport()
@@ -244,6 +307,61 @@ impl<W: Write> Editor<W> {
         }
     }
 
+    // move the cursor on a buffer position and only scroll if it is outside of the screen
+    pub fn goto_buffer_pos(&mut self, pos: (u16, u16)) {
+        let viewport = self.viewports.c_viewport();
+        let is_visible = pos.1 >= viewport.top
+            && pos.1 < viewport.top + viewport.max_vheight()
+            && pos.0 >= viewport.left
+            && pos.0 < viewport.left + viewport.max_vwidth();
+
+        match is_visible {
+            true => self.cursor = (pos.0 - viewport.left, pos.1 - viewport.top),
+            false => self
+                .buffer_actions
+                .push(Action::GotoPos((pos.0, pos.1 + viewport.min_vheight))),
+        }
+    }
+
+    // show the viewport of the file and create it if it is not open yet
+    pub fn open_file(&mut self, path: String) {
+        match self.viewports.find_buffer(&path) {
+            Some(id) => self.viewports.open(id),
+            None => {
+                let viewport = Viewport::new(
+                    Buffer::new(Some(path)),
+                    self.size.0,
+                    self.size.1 - TERMINAL_SIZE_MINUS,
+                    0,
+                    true,
+                );
+                viewport
+                    .buffer
+                    .borrow_mut()
+                    .set_query_language(viewport.languages);
+                let index = self.viewports.push(viewport);
+                self.viewports.show(index);
+            }
+        }
+    }
+
+    // replace old_len lines from start and keep the old ones to be able to undo it
+    pub fn replace_buffer_lines(&mut self, start: usize, old_len: usize, lines: Vec<String>) {
+        let old_cursor = OldCursorPosition::new(self.cursor, self.viewports.c_viewport().top);
+        let mut buffer = self.viewports.c_viewport().buffer.borrow_mut();
+        let len = buffer.lines.len();
+        let old_lines = buffer.replace_lines(start..start + old_len, lines);
+        // a buffer left without lines gets an empty one, it is removed by the undo too
+        let new_len = buffer.lines.len() + old_lines.len() - len;
+        drop(buffer);
+
+        let undo = Action::UndoLines(old_cursor, start, old_lines, new_len);
+        match self.mode {
+            Mode::Insert => self.undo_insert_actions.push(undo),
+            _ => self.undo_actions.push(undo),
+        }
+    }
+
     // allow us to know with of cursor or visual_cursor is the first to come
     fn get_visual_block_pos(&self) -> Option<CursorBlock> {
         if let Some(visual_cursor) = self.visual_cursor {
@@ -268,7 +386,7 @@ impl<W: Write> Editor<W> {
         match self.viewports.c_viewport().get_line_len(&self.v_cursor()) {
             0 => 0,
             ll if matches!(self.mode, Mode::Insert) => ll,
-            ll => ll - TERMINAL_LINE_LEN_MINUS,
+            ll => ll - self.viewports.c_viewport().line_len_minus(),
         }
     }
 
@@ -318,10 +436,29 @@ impl Default for Editor<Stdout> {
             size: (80, 20),
             cursor: (0, 0),
             visual_cursor: None,
+            cursors: vec![],
             modal: None,
             buffer_x_cursor: 0,
             waiting_command: None,
+            pending_operator: None,
+            completion: None,
+            snippet: None,
+            closers: InsertedClosers::default(),
+            user_commands: UserCommands::load(),
+            substitution: None,
+            visual_lines: None,
+            search_backward: false,
+            search_origin: (0, 0),
+            search_top: 0,
+            cmdline_back: 0,
+            wildmenu: None,
+            job: None,
+            quickfix: QuickfixList::default(),
+            global_marks: None,
+            quit: false,
+            history: History::default(),
             viewports: Viewports::default(),
+            options: Options::from_config(None),
             buffer_actions: vec![],
             undo_actions: vec![],
             undo_insert_actions: vec![],
@@ -344,10 +481,29 @@ impl Default for Editor<Cursor<Vec<u8>>> {
             size: (80, 20),
             cursor: (0, 0),
             visual_cursor: None,
+            cursors: vec![],
             modal: None,
             buffer_x_cursor: 0,
             waiting_command: None,
+            pending_operator: None,
+            completion: None,
+            snippet: None,
+            closers: InsertedClosers::default(),
+            user_commands: UserCommands::load(),
+            substitution: None,
+            visual_lines: None,
+            search_backward: false,
+            search_origin: (0, 0),
+            search_top: 0,
+            cmdline_back: 0,
+            wildmenu: None,
+            job: None,
+            quickfix: QuickfixList::default(),
+            global_marks: None,
+            quit: false,
+            history: History::default(),
             viewports: Viewports::default(),
+            options: Options::from_config(None),
             buffer_actions: vec![],
             undo_actions: vec![],
             undo_insert_actions: vec![],
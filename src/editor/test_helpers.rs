@@ -0,0 +1,31 @@
+use std::io::Cursor;
+
+use super::{core::actions::action::Action, Editor};
+use crate::buff::Buffer;
+
+// the helpers shared by the tests of the editor, which draws in a vec instead of the terminal
+pub type TestEditor = Editor<Cursor<Vec<u8>>>;
+
+// an editor on a buffer with these lines, the path gives its language
+pub fn editor_with_path(lines: &[&str], path: &str) -> TestEditor {
+    let mut editor = Editor::default();
+    *editor.viewports.c_mut_viewport().buffer.borrow_mut() = Buffer::new_tmp(
+        lines.iter().map(|l| l.to_string()).collect(),
+        path.to_string(),
+    );
+    editor
+}
+
+pub fn editor_with(lines: &[&str]) -> TestEditor {
+    editor_with_path(lines, "a.txt")
+}
+
+pub fn lines(editor: &TestEditor) -> Vec<String> {
+    editor.viewports.c_viewport().buffer.borrow().lines.clone()
+}
+
+// run a command like it was typed after :
+pub fn run(editor: &mut TestEditor, command: &str) {
+    editor.command = command.to_string();
+    Action::ExecuteCommand.execute(editor).unwrap();
+}
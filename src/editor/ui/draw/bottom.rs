@@ -19,17 +19,28 @@ pub fn draw_bottom<W: Write>(editor: &mut Editor<W>) -> anyhow::Result<()> {
     let cursor_viewport = c_viewport.viewport_cursor(&editor.cursor);
 
     let mode = format!(" {} ", editor.mode);
-    let pos = format!(" {}:{} ", cursor_viewport.0, cursor_viewport.1);
+    // the index of the current match like [3/17] when there is a search
+    let counter = match c_viewport.search_pos.len() {
+        0 => String::new(),
+        len => format!(" [{}/{len}]", c_viewport.search_index + 1),
+    };
+    let pos = format!("{counter} {}:{} ", cursor_viewport.0, cursor_viewport.1);
     let pad_width = editor.size.0 - mode.len() as u16 - pos.len() as u16 - TERMINAL_SIZE_MINUS;
 
     let filename = format!(
         " {:<width$} ",
-        c_viewport.buffer.path,
+        c_viewport.buffer.borrow().name(),
         width = pad_width as usize
     );
 
-    draw_status_line(editor, mode, filename)?;
-    draw_line_counter(editor, pos)?;
+    // the completions of the command line take the place of the status line
+    match editor.wildmenu.is_some() {
+        true => draw_wildmenu(editor)?,
+        false => {
+            draw_status_line(editor, mode, filename)?;
+            draw_line_counter(editor, pos)?;
+        }
+    }
     draw_last_line(editor)?;
 
     Ok(())
@@ -53,10 +64,76 @@ pub fn draw_status_line<W: Write>(
     ))?;
     Ok(())
 }
+// the candidates in a row, scrolled so the selected one is visible and with a < or > when some
+// are hidden
+pub fn draw_wildmenu<W: Write>(editor: &mut Editor<W>) -> Result<()> {
+    let Some(wildmenu) = editor.wildmenu.as_ref() else {
+        return Ok(());
+    };
+    let width = editor.size.0 as usize;
+    let candidates: Vec<String> = wildmenu
+        .candidates
+        .iter()
+        .map(|candidate| format!(" {candidate} "))
+        .collect();
+    let selected = wildmenu.index.unwrap_or(0);
+
+    // the first candidate shown, the row keeps 2 columns for the arrows
+    let mut first = 0;
+    while first < selected
+        && candidates[first..=selected]
+            .iter()
+            .map(|c| c.chars().count())
+            .sum::<usize>()
+            > width.saturating_sub(2)
+    {
+        first += 1;
+    }
+    let mut used = 0;
+    let mut row = vec![];
+    for (i, candidate) in candidates.iter().enumerate().skip(first) {
+        let len = candidate.chars().count();
+        if used + len > width.saturating_sub(2) {
+            break;
+        }
+        used += len;
+        row.push((i, candidate));
+    }
+    let last = row.last().map(|(i, _)| *i).unwrap_or(first);
+
+    let bg = Color::from(THEME.bg1);
+    editor
+        .stdout
+        .queue(cursor::MoveTo(0, editor.size.1 - TERMINAL_SIZE_MINUS))?;
+    let left = if first > 0 { "<" } else { " " };
+    editor.stdout.queue(PrintStyledContent(left.on(bg)))?;
+    for (i, candidate) in row {
+        let content = match Some(i) == wildmenu.index {
+            true => candidate
+                .clone()
+                .with(Color::Black)
+                .on(Color::from(THEME.bright_yellow)),
+            false => candidate.clone().with(Color::from(THEME.default)).on(bg),
+        };
+        editor.stdout.queue(PrintStyledContent(content))?;
+    }
+    let right = if last + 1 < candidates.len() {
+        ">"
+    } else {
+        " "
+    };
+    let pad = width.saturating_sub(used + 1);
+    editor
+        .stdout
+        .queue(PrintStyledContent(format!("{right:<pad$}").on(bg)))?;
+    Ok(())
+}
+
 // this method will draw command or search depending on the mode
 pub fn draw_last_line<W: Write>(editor: &mut Editor<W>) -> Result<()> {
     let (symbol, cmd) = match editor.mode {
         Mode::Command => (':', &editor.command),
+        Mode::Search if editor.search_backward => ('?', &editor.search),
         Mode::Search => ('/', &editor.search),
         _ => (' ', &editor.command), // will print &self.command but will be empty, like that i
                                      // dont need to make String::new()
@@ -212,4 +289,25 @@ mod test_draw_bottom {
             "Output should contain the position"
         );
     }
+
+    #[test]
+    fn test_draw_wildmenu() {
+        use crate::editor::core::actions::action::Action;
+
+        let mut editor = create_mock_editor();
+        editor.mode = Mode::Command;
+        editor.command = "c".to_string();
+        Action::CmdlineComplete(false).execute(&mut editor).unwrap();
+        editor.stdout.get_mut().clear();
+
+        let result = draw_bottom(&mut editor);
+        assert!(result.is_ok(), "draw_bottom should draw the wildmenu");
+        let output_str = String::from_utf8(editor.stdout.get_ref().clone())
+            .expect("Failed to convert stdout to string");
+        assert!(
+            output_str.contains(" cNext ") && output_str.contains(" cclose "),
+            "Output should contain the candidates instead of the status line"
+        );
+        assert!(!output_str.contains(" COMMAND "));
+    }
 }
@@ -0,0 +1,79 @@
+use std::io::Write;
+
+use crossterm::{
+    cursor,
+    style::{Color, PrintStyledContent, Stylize},
+    QueueableCommand,
+};
+
+use crate::{editor::Editor, THEME};
+
+const MAX_ROWS: usize = 10;
+
+// floating list of the completion drawn under the word, or above it if there is no room
+pub fn draw_completion<W: Write>(editor: &mut Editor<W>) -> anyhow::Result<()> {
+    let Some(completion) = &editor.completion else {
+        return Ok(());
+    };
+    let viewport = editor.viewports.c_viewport();
+    let rows = completion.items.len().min(MAX_ROWS);
+    let width = completion
+        .items
+        .iter()
+        .map(|item| item.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 2;
+
+    let line = editor.cursor.1 + viewport.min_vheight;
+    let bottom = viewport.vheight;
+    let top = match line as usize + 1 + rows <= bottom as usize {
+        true => line + 1,
+        false => line.saturating_sub(rows as u16),
+    };
+    let prefix_len = completion.prefix.chars().count() as u16;
+    let left = (editor.cursor.0.saturating_sub(prefix_len) + viewport.min_vwidth)
+        .min(editor.size.0.saturating_sub(width as u16));
+
+    // scroll the list so the selected item is always visible
+    let first = (completion.index + 1).saturating_sub(rows);
+    for (i, item) in completion.items.iter().skip(first).take(rows).enumerate() {
+        let row = format!(" {:<width$}", item, width = width - 1);
+        let bg = match first + i == completion.index {
+            true => THEME.faded_purple,
+            false => THEME.bg1,
+        };
+        editor.stdout.queue(cursor::MoveTo(left, top + i as u16))?;
+        editor.stdout.queue(PrintStyledContent(
+            row.with(Color::from(THEME.default)).on(Color::from(bg)),
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_draw_completion {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::editor::core::completion::Completion;
+
+    #[test]
+    fn test_popup_above_the_cursor_at_the_bottom() {
+        let mut editor = Editor::<Cursor<Vec<u8>>>::default();
+        let viewport = editor.viewports.c_viewport();
+        // the rows of the text end before vheight, three items dont fit under this line
+        editor.cursor = (0, viewport.vheight - viewport.min_vheight - 3);
+        editor.completion = Some(Completion {
+            prefix: String::new(),
+            items: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            index: 0,
+            start: (0, 0),
+        });
+        let top = editor.cursor.1 + editor.viewports.c_viewport().min_vheight - 3;
+        draw_completion(&mut editor).unwrap();
+        let output = String::from_utf8(editor.stdout.get_ref().clone()).unwrap();
+        // crossterm counts the rows from 1
+        assert!(output.contains(&format!("\x1b[{};", top + 1)));
+    }
+}
@@ -1,8 +1,31 @@
 use std::io::Write;
 
-use crate::editor::Editor;
+use crate::editor::{core::mode::Mode, Editor};
+
+// find the bracket under the cursor and its match to highlight both of them
+fn set_matching_brackets<W: Write>(editor: &mut Editor<W>) {
+    let v_cursor = editor.v_cursor();
+    let viewport = editor.viewports.c_mut_viewport();
+    viewport.matching_brackets = match editor.mode {
+        Mode::Normal | Mode::Insert if !viewport.is_file_explorer() => viewport
+            .buffer
+            .borrow()
+            .matching_bracket(v_cursor)
+            .map(|pos| (v_cursor, pos)),
+        _ => None,
+    };
+}
+
 pub fn draw_current_viewport<W: Write>(editor: &mut Editor<W>) -> anyhow::Result<()> {
     let is_explorer = editor.viewports.is_explorer;
+    set_matching_brackets(editor);
+    for viewport in editor.viewports.values.iter_mut() {
+        viewport.cursors.clear();
+    }
+    if !is_explorer {
+        editor.viewports.draw_windows(&mut editor.stdout)?;
+    }
+    editor.viewports.c_mut_viewport().cursors = editor.cursors.clone();
     match editor.is_visual_mode() {
         true => {
             // give us two option of (u16, u16) first is start second is end
@@ -1,9 +1,10 @@
-use crate::editor::Editor;
+use crate::editor::{core::mode::Mode, Editor};
 use anyhow::Result;
 use crossterm::{cursor, QueueableCommand};
 use std::io::Write;
 
 mod bottom;
+mod completion;
 mod current_viewport;
 mod modal;
 
@@ -12,10 +13,12 @@ impl<W: Write> Editor<W> {
         // some terminal line windows default show the cursor when drawing the tui so hide and show
         // it at the end of draw
         self.stdout.queue(cursor::Hide)?;
+        self.layout_windows();
         self.viewports.draw(&mut self.stdout, self.size.0)?;
 
         current_viewport::draw_current_viewport(self)?;
         modal::draw_modal(self)?;
+        completion::draw_completion(self)?;
 
         if !self.toast.is_empty() {
             self.toast.draw(&mut self.stdout, &self.size.0)?;
@@ -23,11 +26,18 @@ impl<W: Write> Editor<W> {
 
         bottom::draw_bottom(self)?;
 
-        let c_viewport = self.viewports.c_viewport();
-        self.stdout.queue(cursor::MoveTo(
-            self.cursor.0 + c_viewport.min_vwidth,
-            self.cursor.1 + c_viewport.min_vheight,
-        ))?;
+        // the cursor is on the command line while it is edited
+        let cursor = match self.mode {
+            Mode::Command | Mode::Search => (self.cmdline_cursor(), self.size.1 - 1),
+            _ => {
+                let c_viewport = self.viewports.c_viewport();
+                (
+                    self.cursor.0 + c_viewport.min_vwidth,
+                    self.cursor.1 + c_viewport.min_vheight,
+                )
+            }
+        };
+        self.stdout.queue(cursor::MoveTo(cursor.0, cursor.1))?;
 
         self.stdout.queue(cursor::Show)?;
         self.stdout.flush()?;
@@ -0,0 +1,55 @@
+use std::io::Write;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::editor::core::actions::action::Action;
+
+use super::modal_trait::ModalContent;
+
+// ask what to do with the highlighted match of a :s with the c flag
+#[derive(Debug, Clone)]
+pub struct ModalConfirmSubstitute {
+    title: String,
+    content: String,
+}
+
+impl ModalConfirmSubstitute {
+    pub fn new(title: String) -> Self {
+        Self {
+            title,
+            content: String::from("(y)es (n)o (a)ll (q)uit (l)ast"),
+        }
+    }
+}
+
+impl<W: Write> ModalContent<W> for ModalConfirmSubstitute {
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn body(&self) -> &str {
+        &self.content
+    }
+
+    fn handle_action(
+        &self,
+        code: &KeyCode,
+        _modifiers: &KeyModifiers,
+    ) -> anyhow::Result<Option<Action>> {
+        let action = match code {
+            KeyCode::Char(c @ ('y' | 'n' | 'a' | 'q' | 'l')) => Some(Action::SubstituteAnswer(*c)),
+            KeyCode::Esc => Some(Action::SubstituteAnswer('q')),
+            _ => None,
+        };
+        Ok(action)
+    }
+
+    // the answer is a single key, nothing is typed in this modal
+    fn push(&mut self, _ch: char) {}
+
+    fn pop(&mut self) {}
+
+    fn draw_modal(&self, editor: &mut crate::editor::Editor<W>) -> anyhow::Result<()> {
+        self.draw_default(editor)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod confirm;
 pub mod create;
 pub mod delete;
 pub mod modal_trait;
@@ -42,6 +42,15 @@ impl Toast {
         self.messages.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    // forget the messages pushed after the first len ones
+    pub fn truncate(&mut self, len: usize) {
+        self.messages.truncate(len);
+    }
+
     pub fn error(&mut self, err: String) {
         self.messages.push(ToastMessage {
             message: err,
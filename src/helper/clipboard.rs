@@ -2,7 +2,7 @@ use std::process::Command;
 #[cfg(target_os = "linux")]
 pub fn copy_to_clipboard(data: &str) {
     let _ = Command::new("xclip")
-        .args(&["-selection", "clipboard"])
+        .args(["-selection", "clipboard"])
         .stdin(std::process::Stdio::piped())
         .spawn()
         .and_then(|mut child| {
@@ -14,7 +14,7 @@ pub fn copy_to_clipboard(data: &str) {
 #[cfg(target_os = "linux")]
 pub fn paste_from_clipboard() -> Option<Vec<String>> {
     let output = Command::new("xclip")
-        .args(&["-selection", "clipboard", "-o"])
+        .args(["-selection", "clipboard", "-o"])
         .output()
         .ok()?;
     let clipboard = String::from_utf8(output.stdout).ok();
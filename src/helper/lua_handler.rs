@@ -1,5 +1,7 @@
+use mlua::{FromLua, Lua, Table};
+
 pub fn get_home_file(path: &str) -> mlua::Result<Option<String>> {
-    let config_path = dirs::home_dir().unwrap().join(&path);
+    let config_path = dirs::home_dir().unwrap().join(path);
 
     // if there is no config.lua skip this part
     if !std::path::Path::new(&config_path).exists() {
@@ -11,3 +13,63 @@ pub fn get_home_file(path: &str) -> mlua::Result<Option<String>> {
         false => Ok(Some(lua_code)),
     }
 }
+
+// the tables of the user config.lua and of the default one
+pub fn load_configs(lua: &Lua) -> Vec<Table> {
+    let user_config = get_home_file(".rusty/config.lua").unwrap_or(None);
+    let default_config = Some(include_str!("../config.lua").to_string());
+
+    [user_config, default_config]
+        .into_iter()
+        .flatten()
+        .filter_map(|lua_code| lua.load(lua_code).eval::<Table>().ok())
+        .collect()
+}
+
+thread_local! {
+    // the configs are evaluated once like the languages, a lua isnt Sync so each thread keeps its
+    // own
+    static CONFIGS: (Lua, Vec<Table>) = {
+        let lua = Lua::new();
+        let configs = load_configs(&lua);
+        (lua, configs)
+    };
+}
+
+// run the getter on the user config.lua and fallback on the default one
+fn find_in_configs<T>(getter: impl Fn(&Table) -> mlua::Result<T>) -> Option<T> {
+    CONFIGS.with(|(_, configs)| configs.iter().find_map(|config| getter(config).ok()))
+}
+
+// look for an option in the user config.lua and fallback on the default one
+pub fn get_config_option<T: FromLua>(name: &str) -> Option<T> {
+    get_config_table_entry("options", name)
+}
+
+// look for an entry of a table of the config like make.command, each one falls back on the
+// default config when the user table doesnt have it
+pub fn get_config_table_entry<T: FromLua>(table: &str, name: &str) -> Option<T> {
+    find_in_configs(|config| config.get::<Table>(table)?.get::<T>(name))
+}
+
+// look for a top level entry like comments in configs which are already loaded, to read
+// several entries with one lua
+pub fn find_config_entry<T: FromLua>(configs: &[Table], name: &str) -> Option<T> {
+    configs.iter().find_map(|config| config.get::<T>(name).ok())
+}
+
+// the lua files of a directory in ~/.rusty with the name of the file without the extension
+pub fn get_home_lua_files(dir: &str) -> Vec<(String, String)> {
+    let Ok(entries) = std::fs::read_dir(dirs::home_dir().unwrap().join(dir)) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some((name, std::fs::read_to_string(&path).ok()?))
+        })
+        .collect()
+}
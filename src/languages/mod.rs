@@ -1,36 +1,170 @@
 use std::collections::HashMap;
 
+use mlua::{FromLua, Lua, Table, Value};
+use once_cell::sync::Lazy;
 use tree_sitter::Language;
 
+use crate::helper::lua_handler::{find_config_entry, get_home_lua_files, load_configs};
+
+// the languages with what config.lua sets for them, loaded once and shared by the viewports
+pub static LANGUAGES: Lazy<Languages> = Lazy::new(Languages::new);
+
+// tokens used to toggle comments, a language can have a line comment, a block comment or both
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommentTokens {
+    pub line: Option<String>,
+    pub block: Option<(String, String)>,
+}
+
+impl CommentTokens {
+    fn line(token: &str) -> Self {
+        Self {
+            line: Some(token.to_string()),
+            block: None,
+        }
+    }
+
+    fn block(open: &str, close: &str) -> Self {
+        Self {
+            line: None,
+            block: Some((open.to_string(), close.to_string())),
+        }
+    }
+
+    fn with_block(mut self, open: &str, close: &str) -> Self {
+        self.block = Some((open.to_string(), close.to_string()));
+        self
+    }
+}
+
+// allow config.lua to override comments like: rs = { line = "//", block = { "/*", "*/" } }
+impl FromLua for CommentTokens {
+    fn from_lua(value: Value, _lua: &Lua) -> mlua::Result<Self> {
+        let table = Table::from_lua(value, _lua)?;
+        let block = table
+            .get::<Option<Vec<String>>>("block")?
+            .and_then(|block| match block.as_slice() {
+                [open, close] => Some((open.clone(), close.clone())),
+                _ => None,
+            });
+        Ok(Self {
+            line: table.get::<Option<String>>("line")?,
+            block,
+        })
+    }
+}
+
+type LanguageEntry = (Language, String, CommentTokens);
+
+// the pairs used when no one are defined for the extension in config.lua
+const DEFAULT_AUTOPAIRS: [(char, char); 5] =
+    [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+
 #[derive(Debug, Clone)]
 pub struct Languages {
-    languages: HashMap<String, (Language, String)>,
+    languages: HashMap<String, LanguageEntry>,
+    autopairs: HashMap<String, Vec<(char, char)>>,
+    snippets: HashMap<String, HashMap<String, String>>, // extension -> trigger -> body
 }
 
 impl Languages {
     pub fn new() -> Self {
+        let lua = Lua::new();
+        let configs = load_configs(&lua);
+        let mut languages = Self::init();
+        Self::load_user_comments(&mut languages, &configs);
         Self {
-            languages: Self::init(),
+            languages,
+            autopairs: Self::load_autopairs(&configs),
+            snippets: Self::load_snippets(&lua, &configs),
+        }
+    }
+
+    // pairs closed automatically in insert mode for this file
+    pub fn autopairs(&self, path: &str) -> Vec<(char, char)> {
+        let name = Self::get_file_extension(path).unwrap_or_default();
+        self.autopairs
+            .get(&name)
+            .or_else(|| self.autopairs.get("default"))
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_AUTOPAIRS.to_vec())
+    }
+
+    // each pair is a string of two chars like "()"
+    fn load_autopairs(configs: &[Table]) -> HashMap<String, Vec<(char, char)>> {
+        let autopairs = find_config_entry::<HashMap<String, Vec<String>>>(configs, "autopairs");
+        autopairs
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(extension, pairs)| {
+                let pairs = pairs
+                    .iter()
+                    .filter_map(|pair| {
+                        let mut chars = pair.chars();
+                        match (chars.next(), chars.next(), chars.next()) {
+                            (Some(open), Some(close), None) => Some((open, close)),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                (extension, pairs)
+            })
+            .collect()
+    }
+
+    // body of the snippet of this file for the trigger, default ones are shared by all files
+    pub fn snippet(&self, path: &str, trigger: &str) -> Option<&String> {
+        let name = Self::get_file_extension(path).unwrap_or_default();
+        [name.as_str(), "default"]
+            .iter()
+            .find_map(|name| self.snippets.get(*name)?.get(trigger))
+    }
+
+    // snippets of config.lua, overridden by the files of ~/.rusty/snippets like rs.lua
+    // which return a table of trigger = body
+    fn load_snippets(lua: &Lua, configs: &[Table]) -> HashMap<String, HashMap<String, String>> {
+        let mut snippets =
+            find_config_entry::<HashMap<String, HashMap<String, String>>>(configs, "snippets")
+                .unwrap_or_default();
+        for (extension, lua_code) in get_home_lua_files(".rusty/snippets") {
+            if let Ok(file_snippets) = lua.load(lua_code).eval::<HashMap<String, String>>() {
+                snippets.entry(extension).or_default().extend(file_snippets);
+            }
         }
+        snippets
     }
 
-    pub fn get(&self, path: &str) -> Option<&(Language, String)> {
+    pub fn get(&self, path: &str) -> Option<&LanguageEntry> {
         let name = Self::get_file_extension(path).unwrap_or_default();
         self.languages.get(&name)
     }
 
+    pub fn comment_tokens(&self, path: &str) -> Option<&CommentTokens> {
+        self.get(path).map(|(_, _, comment)| comment)
+    }
+
+    fn load_user_comments(languages: &mut HashMap<String, LanguageEntry>, configs: &[Table]) {
+        let comments = find_config_entry::<HashMap<String, CommentTokens>>(configs, "comments");
+        for (extension, tokens) in comments.unwrap_or_default() {
+            if let Some((_, _, comment)) = languages.get_mut(&extension) {
+                *comment = tokens;
+            }
+        }
+    }
+
     fn get_file_extension(path: &str) -> Option<String> {
         std::path::Path::new(path)
             .extension()
             .and_then(|ext| ext.to_str().map(|str| str.to_string()))
     }
-    fn init() -> HashMap<String, (Language, String)> {
+    fn init() -> HashMap<String, LanguageEntry> {
         let mut languages = HashMap::new();
         languages.insert(
             "rs".to_string(),
             (
                 tree_sitter_rust::LANGUAGE.into(),
                 tree_sitter_rust::HIGHLIGHTS_QUERY.to_string(),
+                CommentTokens::line("//").with_block("/*", "*/"),
             ),
         );
         languages.insert(
@@ -38,6 +172,7 @@ impl Languages {
             (
                 tree_sitter_lua::LANGUAGE.into(),
                 tree_sitter_lua::HIGHLIGHTS_QUERY.to_string(),
+                CommentTokens::line("--").with_block("--[[", "]]"),
             ),
         );
         languages.insert(
@@ -45,6 +180,7 @@ impl Languages {
             (
                 tree_sitter_html::LANGUAGE.into(),
                 tree_sitter_html::HIGHLIGHTS_QUERY.to_string(),
+                CommentTokens::block("<!--", "-->"),
             ),
         );
         languages.insert(
@@ -52,6 +188,7 @@ impl Languages {
             (
                 tree_sitter_css::LANGUAGE.into(),
                 tree_sitter_css::HIGHLIGHTS_QUERY.to_string(),
+                CommentTokens::block("/*", "*/"),
             ),
         );
         languages.insert(
@@ -59,6 +196,7 @@ impl Languages {
             (
                 tree_sitter_javascript::LANGUAGE.into(),
                 tree_sitter_javascript::HIGHLIGHT_QUERY.to_string(),
+                CommentTokens::line("//").with_block("/*", "*/"),
             ),
         );
         languages.insert(
@@ -66,6 +204,7 @@ impl Languages {
             (
                 tree_sitter_ruby::LANGUAGE.into(),
                 tree_sitter_ruby::HIGHLIGHTS_QUERY.to_string(),
+                CommentTokens::line("#"),
             ),
         );
         languages.insert(
@@ -73,6 +212,7 @@ impl Languages {
             (
                 tree_sitter_python::LANGUAGE.into(),
                 tree_sitter_python::HIGHLIGHTS_QUERY.to_string(),
+                CommentTokens::line("#"),
             ),
         );
 
@@ -12,7 +12,6 @@ use helper::logger::Logger;
 
 mod editor;
 use editor::Editor;
-pub const LINE_NUMBERS_WIDTH: u16 = 5;
 pub static INSTANCE: OnceLock<Mutex<Logger>> = OnceLock::new();
 
 use anyhow::Ok;
@@ -53,14 +52,14 @@ mod main_tests {
         let file_path = Some(path.clone());
         let buffer = Buffer::new(file_path);
         assert!(!buffer.is_directory);
-        assert_eq!(buffer.path, path);
+        assert_eq!(buffer.name(), path);
     }
     #[test]
     fn check_folder_path_buffer() {
         let file_path = Some("./".to_string());
         let buffer = Buffer::new(file_path);
         assert!(buffer.is_directory);
-        assert_eq!(buffer.path, "./".to_string());
+        assert_eq!(buffer.name(), "./".to_string());
     }
 
     #[test]
@@ -68,7 +67,7 @@ mod main_tests {
         let file_path = None;
         let buffer = Buffer::new(file_path);
         assert!(!buffer.is_directory);
-        assert_eq!(buffer.path, "Empty".to_string());
+        assert_eq!(buffer.path, None);
     }
 
     #[test]
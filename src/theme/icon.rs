@@ -1,9 +1,9 @@
 use std::path::PathBuf;
 
-pub fn get_icon(path: &String) -> &'static str {
+pub fn get_icon(path: &str) -> &'static str {
     match PathBuf::from(path).is_dir() {
         true => " \u{f115}",
-        false => match path.split('.').last() {
+        false => match path.split('.').next_back() {
             Some("txt") => " \u{f15c}",
             Some("md") => " \u{f48a}",
             Some("rs") => " \u{e7a8}",
@@ -2,7 +2,7 @@ pub mod color_highligther;
 pub mod icon;
 use mlua::{self, Lua, Table};
 
-use crate::{helper::lua_handler::get_home_file, log_message};
+use crate::helper::lua_handler::get_home_file;
 #[derive(Debug)]
 pub struct Theme {
     pub bg0: (u8, u8, u8),
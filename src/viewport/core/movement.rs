@@ -1,4 +1,4 @@
-use crate::{editor::TERMINAL_LINE_LEN_MINUS, viewport::Viewport};
+use crate::viewport::Viewport;
 
 impl Viewport {
     pub fn scroll_up(&mut self) {
@@ -37,7 +37,7 @@ impl Viewport {
             false => {
                 cursor.0 = self
                     .get_line_len(cursor)
-                    .wrapping_sub(TERMINAL_LINE_LEN_MINUS)
+                    .wrapping_sub(self.line_len_minus())
             }
         }
     }
@@ -46,7 +46,7 @@ impl Viewport {
         let max_vwidth = self.max_vwidth().saturating_sub(1);
         let without_line_len_minus = self
             .get_line_len(cursor)
-            .saturating_sub(TERMINAL_LINE_LEN_MINUS);
+            .saturating_sub(self.line_len_minus());
 
         cursor.0 = max_vwidth;
         self.left = without_line_len_minus.saturating_sub(max_vwidth);
@@ -89,7 +89,7 @@ impl Viewport {
         } else {
             // allow us to move at the end of the file if the cursor is under the number of
             // buffer_lines
-            let rest_of_file_len = (self.buffer.lines.len() as u16 - 1) - self.top;
+            let rest_of_file_len = (self.buffer.borrow().lines.len() as u16 - 1) - self.top;
             if rest_of_file_len > 0
                 && self.is_under_buffer_len(&(cursor.0, cursor.1 + rest_of_file_len - 1))
             {
@@ -98,12 +98,87 @@ impl Viewport {
         }
     }
 
+    // scroll of half a screen and move the cursor of what we couldnt scroll
+    pub fn half_page_down(&mut self, cursor: &mut (u16, u16)) {
+        let half = self.max_vheight() / 2;
+        let buffer_len = self.get_buffer_len() as u16;
+        let max_top = buffer_len.saturating_sub(self.max_vheight());
+        let scroll = half.min(max_top.saturating_sub(self.top));
+        self.top += scroll;
+
+        let last_line = buffer_len.saturating_sub(1).saturating_sub(self.top);
+        cursor.1 = (cursor.1 + half - scroll).min(last_line);
+        self.check_left_bound(cursor);
+    }
+
+    pub fn half_page_up(&mut self, cursor: &mut (u16, u16)) {
+        let half = self.max_vheight() / 2;
+        let scroll = half.min(self.top);
+        self.top -= scroll;
+        cursor.1 = cursor.1.saturating_sub(half - scroll);
+        self.check_left_bound(cursor);
+    }
+
+    // scroll the text of one line and keep the cursor on the same line if it stay visible
+    pub fn scroll_line_down(&mut self, cursor: &mut (u16, u16), scrolloff: u16) {
+        if (self.top as usize) + 1 >= self.get_buffer_len() {
+            return;
+        }
+        self.top += 1;
+        cursor.1 = cursor.1.saturating_sub(1).max(scrolloff);
+        let last_line = (self.get_buffer_len() as u16).saturating_sub(1 + self.top);
+        cursor.1 = cursor.1.min(last_line);
+        self.check_left_bound(cursor);
+    }
+
+    pub fn scroll_line_up(&mut self, cursor: &mut (u16, u16), scrolloff: u16) {
+        if self.top == 0 {
+            return;
+        }
+        self.scroll_up();
+        let max_y = self
+            .max_vheight()
+            .saturating_sub(1)
+            .saturating_sub(scrolloff);
+        cursor.1 = (cursor.1 + 1).min(max_y);
+        self.check_left_bound(cursor);
+    }
+
+    // return the screen line of H, M and L
+    pub fn screen_top(&self, scrolloff: u16) -> u16 {
+        match self.top > 0 {
+            true => scrolloff.min(self.visible_lines().saturating_sub(1)),
+            false => 0,
+        }
+    }
+
+    pub fn screen_middle(&self) -> u16 {
+        self.visible_lines().saturating_sub(1) / 2
+    }
+
+    pub fn screen_bottom(&self, scrolloff: u16) -> u16 {
+        let bottom = self.visible_lines().saturating_sub(1);
+        let is_end_of_file =
+            self.top as usize + self.visible_lines() as usize >= self.get_buffer_len();
+        match is_end_of_file {
+            true => bottom,
+            false => bottom.saturating_sub(scrolloff),
+        }
+    }
+
+    // number of buffer lines shown on the screen
+    fn visible_lines(&self) -> u16 {
+        let rest = self.get_buffer_len().saturating_sub(self.top as usize) as u16;
+        rest.min(self.max_vheight())
+    }
+
     pub fn move_to(&mut self, cursor: &(u16, u16)) -> (u16, u16) {
         // calculate the editor cursor position from an v_cursor
-        let quotient = cursor.1 / self.max_vheight();
-        let remain = cursor.1 % self.max_vheight();
+        let y = cursor.1.saturating_sub(self.min_vheight);
+        let quotient = y / self.max_vheight();
+        let remain = y % self.max_vheight();
         self.top = self.max_vheight() * quotient;
-        (cursor.0, remain.saturating_sub(self.min_vheight))
+        (cursor.0, remain)
     }
 
     pub fn center_line(&mut self, cursor: &mut (u16, u16)) {
@@ -2,11 +2,19 @@ pub mod core;
 mod ui;
 
 use crossterm::style::Color;
+use regex::Regex;
 use tree_sitter::Query;
 
 use crate::{
-    buff::Buffer, languages::Languages, theme::color_highligther::ColorHighligter,
-    LINE_NUMBERS_WIDTH, THEME,
+    buff::{Buffer, SharedBuffer},
+    editor::core::{
+        options::{OptionValue, Options, Scope},
+        quickfix::QuickfixList,
+    },
+    languages::{Languages, LANGUAGES},
+    theme::color_highligther::ColorHighligter,
+    viewports::layout::Rect,
+    THEME,
 };
 
 #[derive(Debug)]
@@ -40,7 +48,7 @@ impl BufferPosition {
 // to implement scrolling and showing text of the size of our current terminal
 #[derive(Debug)]
 pub struct Viewport {
-    pub buffer: Buffer,
+    pub buffer: SharedBuffer,
     pub left: u16,
     pub top: u16,
     pub min_vwidth: u16,
@@ -54,12 +62,18 @@ pub struct Viewport {
     pub vwidth: u16,
     pub vheight: u16,
     // pub query: Query,
-    pub languages: Languages,
+    pub languages: &'static Languages,
     pub bg_color: Color,
     pub is_popup: bool,
     // when we do some search it will store all position of match content
     pub search_pos: Vec<(u16, u16, u16)>, // x, y, len
     pub search_index: usize,              // to iter through search_pos;
+    // the bracket under the cursor and its match, drawn in normal and insert mode
+    pub matching_brackets: Option<((u16, u16), (u16, u16))>,
+    pub cursors: Vec<(u16, u16)>,  // the other cursors of the editor
+    pub current_line: Option<u16>, // buffer line drawn with another bg, like the quickfix entry
+    pub loclist: QuickfixList,     // the location list of the viewport
+    pub options: Options,          // numberwidth, popupmargin and onemore of the viewport
 
     pub cached_highlight: Option<Vec<ColorHighligter>>,
     pub last_highlighted_code: String,
@@ -75,10 +89,20 @@ impl Viewport {
     ) -> Viewport {
         // i am in obligation to put the Query::new in viewport or it will make lag the app
         // and make it unspossible to use tree_sitter without delay in the input
-        let min_vwidth = min_vwidth + LINE_NUMBERS_WIDTH;
+        buffer.set_query_language(&LANGUAGES);
+        Self::from_shared(buffer.shared(), vwidth, vheight, min_vwidth, modifiable)
+    }
 
-        let languages = Languages::new();
-        buffer.set_query_language(&languages);
+    // a viewport of a buffer which is already in the buffer list
+    pub fn from_shared(
+        buffer: SharedBuffer,
+        vwidth: u16,
+        vheight: u16,
+        min_vwidth: u16,
+        modifiable: bool,
+    ) -> Viewport {
+        let options = Options::from_config(Some(Scope::Viewport));
+        let min_vwidth = min_vwidth + options.number("numberwidth") as u16;
         Viewport {
             buffer,
             modifiable,
@@ -89,11 +113,44 @@ impl Viewport {
             left: 0,
             top: 0,
             buffer_position: BufferPosition::new(),
-            languages,
+            languages: &LANGUAGES,
             bg_color: Color::from(THEME.bg0),
             is_popup: false,
             search_pos: vec![],
             search_index: 0,
+            matching_brackets: None,
+            cursors: vec![],
+            current_line: None,
+            loclist: QuickfixList::default(),
+            options,
+            cached_highlight: None,
+            last_highlighted_code: String::new(),
+        }
+    }
+
+    // the viewport of a new window on the same buffer, the edits are shared but the search, the
+    // cursors and the options are its own
+    pub fn split(&self) -> Viewport {
+        Viewport {
+            buffer: self.buffer.clone(),
+            left: self.left,
+            top: self.top,
+            min_vwidth: self.min_vwidth,
+            min_vheight: self.min_vheight,
+            buffer_position: BufferPosition::new(),
+            modifiable: self.modifiable,
+            vwidth: self.vwidth,
+            vheight: self.vheight,
+            languages: self.languages,
+            bg_color: self.bg_color,
+            is_popup: false,
+            search_pos: vec![],
+            search_index: 0,
+            matching_brackets: None,
+            cursors: vec![],
+            current_line: self.current_line,
+            loclist: self.loclist.clone(),
+            options: self.options.clone(),
             cached_highlight: None,
             last_highlighted_code: String::new(),
         }
@@ -101,12 +158,12 @@ impl Viewport {
 
     // let us know if the viewport is the file_explorer.
     pub fn is_file_explorer(&self) -> bool {
-        self.buffer.is_directory
+        self.buffer.borrow().is_directory
     }
 
     // return a string with the size of the viewport
     fn viewport(&self) -> String {
-        if self.buffer.lines.is_empty() {
+        if self.buffer.borrow().lines.is_empty() {
             return String::new();
         }
 
@@ -114,10 +171,11 @@ impl Viewport {
             (self.top + self.max_vheight()) as usize,
             self.get_buffer_len(),
         );
-        let vec = &self.buffer.lines;
+        let vec = &self.buffer.borrow().lines;
 
         let mut chunk: Vec<String> = vec![];
-        for line in vec[self.top as usize..height].iter() {
+        // another window may have removed the lines under the top of this one
+        for line in vec[(self.top as usize).min(height)..height].iter() {
             let line = match line.len() >= self.left as usize {
                 true => line[self.left as usize..].to_string(),
                 false => "".to_string(),
@@ -133,14 +191,14 @@ impl Viewport {
     // retrieve the len of the line
     pub fn get_line_len_no_v_cursor(&self, cursor: &(u16, u16)) -> u16 {
         let (_, y) = self.viewport_cursor(cursor);
-        match self.buffer.get(y as usize) {
+        match self.buffer.borrow().get(y as usize) {
             Some(line) => line.len() as u16,
             None => 0,
         }
     }
     // retrieve the len of the line
     pub fn get_line_len(&self, cursor: &(u16, u16)) -> u16 {
-        match self.buffer.get(cursor.1 as usize) {
+        match self.buffer.borrow().get(cursor.1 as usize) {
             Some(line) => line.len() as u16,
             None => 0,
         }
@@ -153,11 +211,11 @@ impl Viewport {
 
     // let us know is the cursor is under the buffer max len
     pub fn is_under_buffer_len(&self, cursor: &(u16, u16)) -> bool {
-        if self.buffer.lines.is_empty() {
+        if self.buffer.borrow().lines.is_empty() {
             return false;
         }
         let (_, y) = self.viewport_cursor(cursor);
-        (y as usize) < (self.buffer.lines.len().saturating_sub(1))
+        (y as usize) < (self.buffer.borrow().lines.len().saturating_sub(1))
     }
 
     pub fn is_under_line_len(&self, cursor: &(u16, u16)) -> bool {
@@ -168,30 +226,103 @@ impl Viewport {
 
     // return the buffer len
     pub fn get_buffer_len(&self) -> usize {
-        match self.buffer.lines.is_empty() {
+        match self.buffer.borrow().lines.is_empty() {
             true => 0,
-            false => self.buffer.lines.len(),
+            false => self.buffer.borrow().lines.len(),
         }
     }
 
     // let us find all occurence of the search
-    pub fn find_occurence(&mut self, find: &str) {
-        let mut occurences: Vec<(u16, u16, u16)> = vec![];
-        if find.is_empty() {
-            self.search_pos = occurences;
-            return;
-        }
+    pub fn find_occurence(&mut self, regex: &Regex) {
+        self.search_pos = self
+            .buffer
+            .borrow()
+            .search(regex)
+            .into_iter()
+            .map(|(x, y, len)| (x, y + self.min_vheight, len))
+            .collect();
+    }
 
-        for (y, line) in self.buffer.lines.iter().enumerate() {
-            for (x, _) in line.match_indices(find) {
-                occurences.push((x as u16, y as u16 + self.min_vheight, find.len() as u16));
-            }
+    // index of the first match after `from` or the last one before it, the bool tell if the
+    // search wrapped around the buffer
+    pub fn search_match(
+        &self,
+        from: (u16, u16),
+        forward: bool,
+        inclusive: bool,
+    ) -> Option<(usize, bool)> {
+        if self.search_pos.is_empty() {
+            return None;
+        }
+        let from = (from.1 + self.min_vheight, from.0);
+        let key = |(x, y, _): &(u16, u16, u16)| (*y, *x);
+        let found = match forward {
+            true => self
+                .search_pos
+                .iter()
+                .position(|pos| key(pos) > from || (inclusive && key(pos) == from)),
+            false => self
+                .search_pos
+                .iter()
+                .rposition(|pos| key(pos) < from || (inclusive && key(pos) == from)),
+        };
+        match (found, forward) {
+            (Some(index), _) => Some((index, false)),
+            (None, true) => Some((0, true)),
+            (None, false) => Some((self.search_pos.len() - 1, true)),
         }
-        self.search_pos = occurences;
     }
 
     pub fn min_vwidth_without_line_number(&self) -> u16 {
-        self.min_vwidth - LINE_NUMBERS_WIDTH
+        self.min_vwidth - self.number_width()
+    }
+
+    pub fn number_width(&self) -> u16 {
+        self.options.number("numberwidth") as u16
+    }
+
+    // 1 when the cursor stops on the last char of the line, 0 with onemore
+    pub fn line_len_minus(&self) -> u16 {
+        !self.options.bool("onemore") as u16
+    }
+
+    pub fn set_option(&mut self, name: &'static str, value: OptionValue) {
+        let mut options = self.options.clone();
+        options.set(name, value);
+        self.set_options(options);
+    }
+
+    // the line numbers take the new width and the text moves with them
+    pub fn set_options(&mut self, options: Options) {
+        let without_line_number = self.min_vwidth_without_line_number();
+        self.options = options;
+        self.min_vwidth = without_line_number + self.number_width();
+    }
+
+    // the place of the window showing it, the search matches keep their line
+    pub fn set_rect(&mut self, rect: Rect) {
+        for (_, y, _) in self.search_pos.iter_mut() {
+            *y = *y - self.min_vheight + rect.y;
+        }
+        self.min_vwidth = rect.x + self.number_width();
+        self.min_vheight = rect.y;
+        self.vwidth = rect.x + rect.width;
+        self.vheight = rect.y + rect.height;
+    }
+
+    pub fn rect(&self) -> Rect {
+        let x = self.min_vwidth - self.number_width();
+        Rect::new(
+            x,
+            self.min_vheight,
+            self.vwidth - x,
+            self.vheight - self.min_vheight,
+        )
+    }
+
+    // the changes of a file which arent saved, the viewports like :grep results are never modified
+    pub fn is_modified(&self) -> bool {
+        self.modifiable && self.buffer.borrow().is_modified()
     }
 
     pub fn clear_search(&mut self) {
@@ -212,21 +343,27 @@ impl Default for Viewport {
             tree_sitter_rust::LANGUAGE.into(),
         ));
 
+        let options = Options::from_config(Some(Scope::Viewport));
         Viewport {
-            buffer,
+            buffer: buffer.shared(),
             modifiable: true,
             vwidth: 80,
             vheight: 20,
-            min_vwidth: LINE_NUMBERS_WIDTH,
+            min_vwidth: options.number("numberwidth") as u16,
             min_vheight: 1,
             left: 0,
             top: 0,
             buffer_position: BufferPosition::new(),
-            languages: Languages::new(),
+            languages: &LANGUAGES,
             bg_color: Color::from(THEME.bg0),
             is_popup: false,
             search_pos: vec![],
             search_index: 0,
+            matching_brackets: None,
+            cursors: vec![],
+            current_line: None,
+            loclist: QuickfixList::default(),
+            options,
             cached_highlight: None,
             last_highlighted_code: String::new(),
         }
@@ -17,16 +17,40 @@ fn draw_new_line<W: std::io::Write>(
     y: &mut u16,
 ) -> Result<()> {
     viewport.draw_line_number(stdout, *y)?;
-    stdout.queue(cursor::MoveTo(*x + viewport.min_vwidth, *y))?;
-    if *x < viewport.vwidth {
+    let max_vwidth = viewport.max_vwidth();
+    // a cursor after the end of the line
+    if *x < max_vwidth && viewport.is_cursor_at(*x, *y) {
+        stdout.queue(cursor::MoveTo(*x + viewport.min_vwidth, *y))?;
+        stdout.queue(PrintStyledContent(' '.on(Color::from(THEME.cursor))))?;
+        *x += 1;
+    }
+    // the line is filled until the end of its window
+    if *x < max_vwidth {
+        stdout.queue(cursor::MoveTo(*x + viewport.min_vwidth, *y))?;
         stdout.queue(PrintStyledContent(
-            " ".repeat(viewport.vwidth as usize - *x as usize)
-                .on(viewport.bg_color),
+            " ".repeat((max_vwidth - *x) as usize)
+                .on(viewport.line_bg(*y)),
         ))?;
     }
     Ok(())
 }
 
+impl Viewport {
+    // one of the other cursors is on this screen position
+    fn is_cursor_at(&self, x: u16, y: u16) -> bool {
+        let pos = (x + self.left, y.saturating_sub(self.min_vheight) + self.top);
+        self.cursors.contains(&pos)
+    }
+
+    // the bg of a screen line, the current line is drawn lighter
+    fn line_bg(&self, y: u16) -> Color {
+        match self.current_line == Some(y.saturating_sub(self.min_vheight) + self.top) {
+            true => Color::from(THEME.bg1),
+            false => self.bg_color,
+        }
+    }
+}
+
 pub fn draw_file<W: std::io::Write>(
     viewport: &mut Viewport,
     stdout: &mut W,
@@ -52,7 +76,6 @@ pub fn draw_file<W: std::io::Write>(
     let mut colorhighligter = None;
 
     let chars_len = viewport_buffer.len().saturating_sub(1);
-    let mut bg_color = viewport.bg_color;
 
     for (pos, c) in viewport_buffer.char_indices() {
         // tell us that we are at the end of the line
@@ -72,6 +95,7 @@ pub fn draw_file<W: std::io::Write>(
             colorhighligter = None
         }
 
+        let mut bg_color = viewport.line_bg(y);
         // allow us to change the bg_color to draw the visual_block
         if let Some(start_block) = start_v_mode {
             if let Some(end_block) = end_v_mode {
@@ -93,15 +117,31 @@ pub fn draw_file<W: std::io::Write>(
             }
         }
 
+        if let Some((start, end)) = viewport.matching_brackets {
+            let pos = (
+                x + viewport.left,
+                y.saturating_sub(viewport.min_vheight) + viewport.top,
+            );
+            if pos == start || pos == end {
+                bg_color = Color::from(THEME.bg1);
+            }
+        }
+
+        if viewport.is_cursor_at(x, y) {
+            bg_color = Color::from(THEME.cursor);
+        }
+
         let styled_char = match colorhighligter {
             Some(ch) => c.on(bg_color).with(ch.color),
             None => c.on(bg_color),
         };
 
-        // move cursor to draw the char
-        buffer
-            .queue(cursor::MoveTo(x + viewport.min_vwidth, y))?
-            .queue(PrintStyledContent(styled_char))?;
+        // move cursor to draw the char, the end of a long line is cut at the end of the window
+        if x < viewport.max_vwidth() {
+            buffer
+                .queue(cursor::MoveTo(x + viewport.min_vwidth, y))?
+                .queue(PrintStyledContent(styled_char))?;
+        }
         // stdout
         //     .queue(cursor::MoveTo(x + viewport.min_vwidth, y))?
         //     .queue(PrintStyledContent(styled_char))?;
@@ -123,10 +163,10 @@ pub fn draw_file<W: std::io::Write>(
 mod tests_draw_file {
     use tree_sitter::Query;
 
-    use crate::buff::Buffer;
+    use crate::{buff::Buffer, editor::core::options::Options};
 
     use super::*;
-    use std::io::Cursor;
+    use std::{cell::RefCell, collections::HashMap, io::Cursor};
 
     fn create_mock_stdout() -> Cursor<Vec<u8>> {
         Cursor::new(Vec::new()) // Create a new Cursor to capture the output
@@ -138,13 +178,18 @@ mod tests_draw_file {
         let buffer = Buffer {
             file: None,
             is_directory: false,
-            path: "".to_string(),
+            path: None,
             lines: vec![], // Empty buffer
             query_language: None,
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         };
 
         let mut viewport = Viewport {
-            buffer,
+            buffer: buffer.shared(),
             ..Viewport::default()
         };
 
@@ -163,7 +208,7 @@ mod tests_draw_file {
         let buffer = Buffer {
             file: None,
             is_directory: false,
-            path: "example.rs".to_string(),
+            path: Some("example.rs".to_string()),
             lines: vec![
                 "fn main() {".to_string(),
                 "    let x = 42;".to_string(),
@@ -178,10 +223,15 @@ mod tests_draw_file {
                 .expect("QueryErr"),
                 tree_sitter_rust::LANGUAGE.into(),
             )),
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         };
 
         let mut viewport = Viewport {
-            buffer,
+            buffer: buffer.shared(),
             ..Viewport::default()
         };
 
@@ -200,7 +250,7 @@ mod tests_draw_file {
         let buffer = crate::buff::Buffer {
             file: None,
             is_directory: false,
-            path: "example.rs".to_string(),
+            path: Some("example.rs".to_string()),
             lines: vec![
                 "fn main() {".to_string(),
                 "    let x = 42;".to_string(),
@@ -215,10 +265,15 @@ mod tests_draw_file {
                 .expect("QueryErr"),
                 tree_sitter_rust::LANGUAGE.into(),
             )),
+            options: Options::default(),
+            marks: HashMap::new(),
+            syntax: RefCell::default(),
+            changes: 0,
+            saved_changes: 0,
         };
 
         let mut viewport = Viewport {
-            buffer,
+            buffer: buffer.shared(),
             search_pos: vec![(5, 5, 3)], // Search result at "main"
             ..Viewport::default()
         };
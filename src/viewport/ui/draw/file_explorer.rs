@@ -12,19 +12,19 @@ pub fn draw_file_explorer<W: Write>(viewport: &Viewport, stdout: &mut W) -> anyh
     let mut y = viewport.min_vheight;
     let start = viewport.top as usize;
     let end = (viewport.top + viewport.max_vheight()) as usize;
-    let range = match end > viewport.buffer.lines.len() {
-        true => start..viewport.buffer.lines.len(),
+    let range = match end > viewport.buffer.borrow().lines.len() {
+        true => start..viewport.buffer.borrow().lines.len(),
         false => start..end,
     };
-    for (i, line) in viewport.buffer.lines[range].iter().enumerate() {
+    for (i, line) in viewport.buffer.borrow().lines[range].iter().enumerate() {
         viewport.draw_line_number(stdout, y)?;
 
         let icon = icon::get_icon(line);
         // we skip the ../ line
         //            // we skip the ../ line
         let line = match i > 0 || (i == 0 && line != "../") {
-            true if line.starts_with(viewport.buffer.path.as_str()) => {
-                let mut path = viewport.buffer.path.clone();
+            true if line.starts_with(viewport.buffer.borrow().name()) => {
+                let mut path = viewport.buffer.borrow().name().to_string();
                 if !path.ends_with("/") {
                     path.push('/');
                 }
@@ -62,7 +62,7 @@ mod test_draw_file_explorer {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR")); // Récupère la racine du projet
         path.push("tests_data/test_file_explorer_folder");
         Viewport {
-            buffer: Buffer::new(Some(path.display().to_string())),
+            buffer: Buffer::new(Some(path.display().to_string())).shared(),
             vwidth: 40,
             min_vwidth: 5,
             bg_color: Color::Black,
@@ -133,7 +133,7 @@ mod test_draw_file_explorer {
         path.push("tests_data/test_file_explorer_folder/user");
 
         let viewport = Viewport {
-            buffer: Buffer::new(Some(path.display().to_string())),
+            buffer: Buffer::new(Some(path.display().to_string())).shared(),
             vwidth: 40,
             min_vwidth: 5,
             bg_color: Color::Black,
@@ -7,7 +7,7 @@ use crossterm::{
 };
 
 use crate::viewport::Viewport;
-use crate::{viewport::LINE_NUMBERS_WIDTH, THEME};
+use crate::THEME;
 
 mod file;
 mod file_explorer;
@@ -22,7 +22,7 @@ impl Viewport {
         end_v_mode: Option<(u16, u16)>,
         is_file_explorer: bool,
     ) -> anyhow::Result<()> {
-        if self.buffer.lines.is_empty() {
+        if self.buffer.borrow().lines.is_empty() {
             return Ok(());
         }
 
@@ -43,13 +43,18 @@ impl Viewport {
         y: u16,
         stdout: &mut W,
     ) -> anyhow::Result<()> {
+        // a popup is as wide as its vwidth and a window ends at its vwidth
+        let width = match self.is_popup {
+            true => self.vwidth,
+            false => self.max_vwidth() + 1,
+        };
         if y < self.vheight {
             for i in y..self.vheight {
                 self.draw_line_number(stdout, i)?;
                 stdout
                     .queue(cursor::MoveTo(self.min_vwidth - 1, i))?
                     .queue(PrintStyledContent(
-                        " ".repeat(self.vwidth as usize).on(self.bg_color),
+                        " ".repeat(width as usize).on(self.bg_color),
                     ))?;
             }
         }
@@ -59,9 +64,9 @@ impl Viewport {
 
     fn draw_line_number<W: Write>(&self, stdout: &mut W, i: u16) -> anyhow::Result<()> {
         let pos = self.top as usize + i as usize;
-        let l_width = LINE_NUMBERS_WIDTH as usize - 1;
+        let l_width = self.number_width().saturating_sub(1) as usize;
         stdout
-            .queue(cursor::MoveTo(self.min_vwidth - LINE_NUMBERS_WIDTH, i))?
+            .queue(cursor::MoveTo(self.min_vwidth - self.number_width(), i))?
             .queue(PrintStyledContent(
                 format!("{pos:>width$}", width = l_width).on(self.bg_color),
             ))?;
@@ -69,26 +74,20 @@ impl Viewport {
         Ok(())
     }
 
+    // every match is highlighted and the current one has its own color
     fn draw_search(&self, x: u16, y: u16) -> Option<Color> {
-        if let Some(search_block) = self
-            .search_pos
+        let x = x + self.left;
+        let y = y + self.top;
+        self.search_pos
             .iter()
-            .find(|&&(_, search_y, _)| search_y.saturating_sub(self.top) == y)
-        {
-            return Some(visual_block::draw_block(
-                self,
-                x,
-                y,
-                (search_block.0, search_block.1.saturating_sub(self.top)),
-                (
-                    search_block.0 + search_block.2.saturating_sub(1),
-                    search_block.1.saturating_sub(self.top),
-                ),
-                Color::from(THEME.bright_orange),
-            ));
-        }
-
-        None
+            .enumerate()
+            .find(|(_, &(search_x, search_y, len))| {
+                search_y == y && x >= search_x && x < search_x + len.max(1)
+            })
+            .map(|(i, _)| match i == self.search_index {
+                true => Color::from(THEME.bright_orange),
+                false => Color::from(THEME.neutral_yellow),
+            })
     }
 }
 
@@ -103,7 +102,7 @@ mod tests_viewport_draw {
     #[test]
     fn test_draw_empty_buffer() {
         let mut viewport = Viewport {
-            buffer: crate::buff::Buffer::new(None),
+            buffer: crate::buff::Buffer::new(None).shared(),
             ..Viewport::default()
         };
 
@@ -118,7 +117,7 @@ mod tests_viewport_draw {
     #[test]
     fn test_draw_file_explorer() {
         let mut viewport = Viewport {
-            buffer: crate::buff::Buffer::new(Some("./".to_string())),
+            buffer: crate::buff::Buffer::new(Some("./".to_string())).shared(),
             ..Viewport::default()
         };
 
@@ -186,4 +185,24 @@ mod tests_viewport_draw {
             "draw_search() devrait retourner Some(Color) si une correspondance est trouvée"
         );
     }
+
+    #[test]
+    fn test_draw_search_every_match() {
+        let viewport = Viewport {
+            search_pos: vec![(1, 5, 2), (6, 5, 2)],
+            search_index: 1,
+            ..Viewport::default()
+        };
+
+        assert_eq!(viewport.draw_search(0, 5), None);
+        assert_eq!(
+            viewport.draw_search(2, 5),
+            Some(Color::from(THEME.neutral_yellow))
+        );
+        assert_eq!(
+            viewport.draw_search(7, 5),
+            Some(Color::from(THEME.bright_orange))
+        );
+        assert_eq!(viewport.draw_search(8, 5), None);
+    }
 }
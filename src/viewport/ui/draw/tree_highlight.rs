@@ -7,7 +7,8 @@ pub fn highlight(viewport: &Viewport, code: &String) -> anyhow::Result<Vec<Color
     let mut colors: Vec<ColorHighligter> = vec![];
     let mut parser = Parser::new();
 
-    let query = match &viewport.buffer.query_language {
+    let buffer = viewport.buffer.borrow();
+    let query = match &buffer.query_language {
         Some((query, language)) => {
             parser.set_language(language)?;
             query
@@ -27,7 +27,7 @@ pub fn draw_block(
                     false => viewport.bg_color,
                 }
             } else {
-                return color;
+                color
             }
         }
         false => viewport.bg_color,
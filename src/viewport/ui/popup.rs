@@ -1,9 +1,7 @@
-use crate::viewport::{BufferPosition, Viewport, LINE_NUMBERS_WIDTH};
+use crate::viewport::{BufferPosition, Viewport};
 use crate::THEME;
 use crossterm::style::Color;
 
-const POPUP_PERCENTAGE: u16 = 30;
-
 pub struct Popup {
     pub width: u16,
     pub height: u16,
@@ -12,19 +10,20 @@ pub struct Popup {
 }
 
 impl Popup {
-    fn percentage_of(n: u16) -> u16 {
-        (n * POPUP_PERCENTAGE) / 100
+    // percentage is the part of the screen left around the popup
+    fn percentage_of(n: u16, percentage: u16) -> u16 {
+        (n as u32 * percentage as u32 / 100) as u16
     }
 
-    fn wrapping_sub_by_percentage(n: u16) -> u16 {
-        n.wrapping_sub(Popup::percentage_of(n))
+    fn wrapping_sub_by_percentage(n: u16, percentage: u16) -> u16 {
+        n.wrapping_sub(Popup::percentage_of(n, percentage))
     }
 
-    pub fn new(width: u16, height: u16) -> Self {
-        let left = (Popup::percentage_of(width) / 2) + LINE_NUMBERS_WIDTH;
-        let top = Popup::percentage_of(height) / 2;
-        let width = Popup::wrapping_sub_by_percentage(width);
-        let height = Popup::wrapping_sub_by_percentage(height);
+    pub fn new(width: u16, height: u16, percentage: u16, number_width: u16) -> Self {
+        let left = (Popup::percentage_of(width, percentage) / 2) + number_width;
+        let top = Popup::percentage_of(height, percentage) / 2;
+        let width = Popup::wrapping_sub_by_percentage(width, percentage);
+        let height = Popup::wrapping_sub_by_percentage(height, percentage);
 
         Popup {
             width,
@@ -46,7 +45,8 @@ impl Viewport {
             return;
         }
 
-        let popup = Popup::new(self.vwidth, self.vheight);
+        let margin = self.options.number("popupmargin").min(100) as u16;
+        let popup = Popup::new(self.vwidth, self.vheight, margin, self.number_width());
         self.buffer_current_position();
 
         self.vwidth = popup.width;
@@ -77,14 +77,18 @@ mod test_popup {
 
     use crate::{
         buff::Buffer,
-        languages::Languages,
+        editor::core::{
+            options::{Options, Scope},
+            quickfix::QuickfixList,
+        },
+        languages::LANGUAGES,
         viewport::{BufferPosition, Viewport},
     };
     use crossterm::style::Color;
 
     fn create_test_viewport() -> Viewport {
         Viewport {
-            buffer: Buffer::new(None),
+            buffer: Buffer::new(None).shared(),
             vwidth: 50,
             vheight: 20,
             min_vwidth: 5,
@@ -93,13 +97,18 @@ mod test_popup {
             modifiable: true,
             left: 0,
             top: 0,
-            languages: Languages::new(),
+            languages: &LANGUAGES,
             // query: Query::new(&tree_sitter_rust::LANGUAGE.into(), HIGHLIGHTS_QUERY)
             // .expect("Query Error"),
             bg_color: Color::Black,
             is_popup: false,
             search_pos: vec![],
             search_index: 0,
+            matching_brackets: None,
+            cursors: vec![],
+            current_line: None,
+            loclist: QuickfixList::default(),
+            options: Options::from_config(Some(Scope::Viewport)),
             cached_highlight: None,
             last_highlighted_code: String::new(),
         }
@@ -7,26 +7,31 @@ use crossterm::{
     QueueableCommand,
 };
 
-use super::Viewports;
-use crate::{theme::icon, LINE_NUMBERS_WIDTH, THEME};
+use super::{layout::Window, Viewports};
+use crate::{theme::icon, THEME};
 
 impl Viewports {
     // draw the name of each viewports at the top /
     pub fn draw<W: Write>(&self, stdout: &mut W, width: u16) -> Result<()> {
-        let mut x = LINE_NUMBERS_WIDTH;
-        for (i, v) in self.values.iter().enumerate() {
-            let icon = icon::get_icon(&v.buffer.path);
-            let name = format!(" {} {}  ", icon, v.buffer.path);
+        let number_width = self.c_viewport().number_width();
+        let mut x = number_width;
+        let current = self.current_id();
+        for id in self.shown_buffers() {
+            let Some(buffer) = self.buffer(id) else {
+                continue;
+            };
+            let icon = icon::get_icon(buffer.borrow().name());
+            let name = format!(" {} {}  ", icon, buffer.borrow().name());
             let len = name.len() - icon.len() + 2; // icon is considered as 5 len but when renderer
                                                    // it will be 2 len so we need to remove the icon len
 
-            let name_color = match i == self.index {
+            let name_color = match Some(id) == current {
                 true => Color::from(THEME.bright_yellow),
                 false => Color::from(THEME.gray),
             };
 
             // stop printing viewport if the size is > to the width of the terminal
-            if x > width - LINE_NUMBERS_WIDTH - len as u16 {
+            if x > width.saturating_sub(number_width + len as u16) {
                 break;
             }
 
@@ -42,10 +47,54 @@ impl Viewports {
         stdout
             .queue(cursor::MoveTo(x, 0))?
             .queue(PrintStyledContent(
-                " ".repeat(width as usize - x as usize - LINE_NUMBERS_WIDTH as usize)
+                " ".repeat((width as usize).saturating_sub(x as usize + number_width as usize))
                     .on(Color::from(THEME.bg0)),
             ))?;
 
         Ok(())
     }
+
+    // the windows which are not focused with their own place and scroll, then the separators
+    pub fn draw_windows<W: Write>(&mut self, stdout: &mut W) -> Result<()> {
+        let windows: Vec<Window> = self
+            .layout
+            .windows()
+            .into_iter()
+            .filter(|w| w.id != self.window && w.viewport < self.values.len())
+            .cloned()
+            .collect();
+        for window in windows {
+            let viewport = &mut self.values[window.viewport];
+            let (rect, top, left) = (viewport.rect(), viewport.top, viewport.left);
+            let cursors = std::mem::take(&mut viewport.cursors);
+            let brackets = viewport.matching_brackets.take();
+            viewport.set_rect(window.rect);
+            viewport.top = window.top;
+            viewport.left = window.left;
+            let drawn = viewport.draw(stdout, None, None, false);
+            viewport.set_rect(rect);
+            viewport.top = top;
+            viewport.left = left;
+            viewport.cursors = cursors;
+            viewport.matching_brackets = brackets;
+            drawn?;
+        }
+
+        for split in self.layout.splits() {
+            let separator = split.separator();
+            for y in separator.y..separator.y + separator.height {
+                let line = match split.vertical {
+                    true => "│".to_string(),
+                    false => "─".repeat(separator.width as usize),
+                };
+                stdout
+                    .queue(cursor::MoveTo(separator.x, y))?
+                    .queue(PrintStyledContent(
+                        line.with(Color::from(THEME.gray))
+                            .on(Color::from(THEME.bg0)),
+                    ))?;
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,366 @@
+// the splits of the screen, each window shows a viewport with its own size and scroll
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn right(&self) -> u16 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u16 {
+        self.y + self.height
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Left,
+    Down,
+    Up,
+    Right,
+}
+
+// the focused window keeps its scroll in its viewport and its cursor in the editor, the others
+// keep them here
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    pub id: usize,
+    pub viewport: usize, // index in the values of viewports
+    pub rect: Rect,
+    pub top: u16,
+    pub left: u16,
+    pub cursor: (u16, u16),
+}
+
+impl Window {
+    pub fn new(id: usize, viewport: usize) -> Self {
+        Self {
+            id,
+            viewport,
+            rect: Rect::default(),
+            top: 0,
+            left: 0,
+            cursor: (0, 0),
+        }
+    }
+}
+
+// two windows side by side with a separator, vertical puts them left and right
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split {
+    pub vertical: bool,
+    pub ratio: f32, // the part of the split given to the first window
+    pub rect: Rect,
+    pub first: Box<Layout>,
+    pub second: Box<Layout>,
+}
+
+impl Split {
+    // the size of the first child without the separator
+    fn first_size(&self) -> u16 {
+        let available = self.available();
+        let size = (self.ratio * available as f32).round() as u16;
+        match available {
+            0 | 1 => available,
+            _ => size.clamp(1, available - 1),
+        }
+    }
+
+    fn available(&self) -> u16 {
+        match self.vertical {
+            true => self.rect.width.saturating_sub(1),
+            false => self.rect.height.saturating_sub(1),
+        }
+    }
+
+    // the separator is the column or the line after the first child
+    pub fn separator(&self) -> Rect {
+        let size = self.first_size();
+        match self.vertical {
+            true => Rect::new(self.rect.x + size, self.rect.y, 1, self.rect.height),
+            false => Rect::new(self.rect.x, self.rect.y + size, self.rect.width, 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    Window(Window),
+    Split(Split),
+}
+
+impl Layout {
+    // give a rect to every window of the layout
+    pub fn arrange(&mut self, rect: Rect) {
+        match self {
+            Layout::Window(window) => window.rect = rect,
+            Layout::Split(split) => {
+                split.rect = rect;
+                let size = split.first_size();
+                let (first, second) = match split.vertical {
+                    true => (
+                        Rect::new(rect.x, rect.y, size, rect.height),
+                        Rect::new(
+                            rect.x + size + 1,
+                            rect.y,
+                            rect.width.saturating_sub(size + 1),
+                            rect.height,
+                        ),
+                    ),
+                    false => (
+                        Rect::new(rect.x, rect.y, rect.width, size),
+                        Rect::new(
+                            rect.x,
+                            rect.y + size + 1,
+                            rect.width,
+                            rect.height.saturating_sub(size + 1),
+                        ),
+                    ),
+                };
+                split.first.arrange(first);
+                split.second.arrange(second);
+            }
+        }
+    }
+
+    // the windows from the top left to the bottom right
+    pub fn windows(&self) -> Vec<&Window> {
+        match self {
+            Layout::Window(window) => vec![window],
+            Layout::Split(split) => {
+                let mut windows = split.first.windows();
+                windows.extend(split.second.windows());
+                windows
+            }
+        }
+    }
+
+    pub fn windows_mut(&mut self) -> Vec<&mut Window> {
+        match self {
+            Layout::Window(window) => vec![window],
+            Layout::Split(split) => {
+                let mut windows = split.first.windows_mut();
+                windows.extend(split.second.windows_mut());
+                windows
+            }
+        }
+    }
+
+    pub fn splits(&self) -> Vec<&Split> {
+        match self {
+            Layout::Window(_) => vec![],
+            Layout::Split(split) => {
+                let mut splits = vec![split];
+                splits.extend(split.first.splits());
+                splits.extend(split.second.splits());
+                splits
+            }
+        }
+    }
+
+    pub fn window(&self, id: usize) -> Option<&Window> {
+        self.windows().into_iter().find(|w| w.id == id)
+    }
+
+    pub fn window_mut(&mut self, id: usize) -> Option<&mut Window> {
+        self.windows_mut().into_iter().find(|w| w.id == id)
+    }
+
+    fn contains(&self, id: usize) -> bool {
+        self.window(id).is_some()
+    }
+
+    // the new window takes the first half of the window like :split puts it above
+    pub fn split(&mut self, id: usize, vertical: bool, window: Window) {
+        match self {
+            Layout::Window(current) if current.id == id => {
+                let current = Layout::Window(current.clone());
+                *self = Layout::Split(Split {
+                    vertical,
+                    ratio: 0.5,
+                    rect: Rect::default(),
+                    first: Box::new(Layout::Window(window)),
+                    second: Box::new(current),
+                });
+            }
+            Layout::Window(_) => {}
+            Layout::Split(split) => match split.first.contains(id) {
+                true => split.first.split(id, vertical, window),
+                false => split.second.split(id, vertical, window),
+            },
+        }
+    }
+
+    // the other window of the split takes the place, the last window is never removed
+    pub fn remove(&mut self, id: usize) -> bool {
+        let Layout::Split(split) = self else {
+            return false;
+        };
+        let sibling = match (&*split.first, &*split.second) {
+            (Layout::Window(w), sibling) | (sibling, Layout::Window(w)) if w.id == id => {
+                sibling.clone()
+            }
+            _ => {
+                return match split.first.contains(id) {
+                    true => split.first.remove(id),
+                    false => split.second.remove(id),
+                }
+            }
+        };
+        *self = sibling;
+        true
+    }
+
+    // the number of windows next to each other in the direction of the split
+    fn count(&self, vertical: bool) -> usize {
+        match self {
+            Layout::Window(_) => 1,
+            Layout::Split(split) if split.vertical == vertical => {
+                split.first.count(vertical) + split.second.count(vertical)
+            }
+            Layout::Split(split) => split
+                .first
+                .count(vertical)
+                .max(split.second.count(vertical)),
+        }
+    }
+
+    // every window gets the same size
+    pub fn equalize(&mut self) {
+        if let Layout::Split(split) = self {
+            let first = split.first.count(split.vertical);
+            let second = split.second.count(split.vertical);
+            split.ratio = first as f32 / (first + second) as f32;
+            split.first.equalize();
+            split.second.equalize();
+        }
+    }
+
+    // grow the window by delta in the direction of the closest split which can change its size,
+    // it gives false when there is none
+    pub fn resize(&mut self, id: usize, vertical: bool, delta: i32) -> bool {
+        let Layout::Split(split) = self else {
+            return false;
+        };
+        let in_first = split.first.contains(id);
+        let child = match in_first {
+            true => &mut split.first,
+            false => &mut split.second,
+        };
+        if child.resize(id, vertical, delta) {
+            return true;
+        }
+        if split.vertical != vertical {
+            return false;
+        }
+        let available = split.available() as i32;
+        let size = split.first_size() as i32 + if in_first { delta } else { -delta };
+        split.ratio = size.clamp(1, (available - 1).max(1)) as f32 / available.max(1) as f32;
+        true
+    }
+
+    // the window next to the rect in the direction, the one at the row or column of the cursor
+    // is preferred
+    pub fn neighbor(&self, id: usize, direction: Direction, cursor: (u16, u16)) -> Option<usize> {
+        let current = self.window(id)?.rect;
+        let overlaps = |a: (u16, u16), b: (u16, u16)| a.0 < b.1 && b.0 < a.1;
+        let candidates: Vec<&Window> = self
+            .windows()
+            .into_iter()
+            .filter(|w| {
+                let rect = w.rect;
+                match direction {
+                    Direction::Left => rect.right() + 1 == current.x,
+                    Direction::Right => current.right() + 1 == rect.x,
+                    Direction::Up => rect.bottom() + 1 == current.y,
+                    Direction::Down => current.bottom() + 1 == rect.y,
+                }
+            })
+            .filter(|w| match direction {
+                Direction::Left | Direction::Right => {
+                    overlaps((w.rect.y, w.rect.bottom()), (current.y, current.bottom()))
+                }
+                Direction::Up | Direction::Down => {
+                    overlaps((w.rect.x, w.rect.right()), (current.x, current.right()))
+                }
+            })
+            .collect();
+        let at_cursor = candidates.iter().find(|w| match direction {
+            Direction::Left | Direction::Right => {
+                (w.rect.y..w.rect.bottom()).contains(&(current.y + cursor.1))
+            }
+            Direction::Up | Direction::Down => {
+                (w.rect.x..w.rect.right()).contains(&(current.x + cursor.0))
+            }
+        });
+        at_cursor.or(candidates.first()).map(|w| w.id)
+    }
+}
+
+#[cfg(test)]
+mod tests_layout {
+    use super::*;
+
+    fn ids(layout: &Layout) -> Vec<usize> {
+        layout.windows().iter().map(|w| w.id).collect()
+    }
+
+    fn rect(layout: &Layout, id: usize) -> Rect {
+        layout.window(id).unwrap().rect
+    }
+
+    #[test]
+    fn test_split_and_arrange() {
+        let mut layout = Layout::Window(Window::new(0, 0));
+        layout.split(0, true, Window::new(1, 0));
+        layout.split(0, false, Window::new(2, 0));
+        layout.arrange(Rect::new(0, 1, 81, 21));
+        assert_eq!(ids(&layout), vec![1, 2, 0]);
+        assert_eq!(rect(&layout, 1), Rect::new(0, 1, 40, 21));
+        assert_eq!(rect(&layout, 2), Rect::new(41, 1, 40, 10));
+        assert_eq!(rect(&layout, 0), Rect::new(41, 12, 40, 10));
+
+        assert_eq!(layout.neighbor(1, Direction::Right, (0, 15)), Some(0));
+        assert_eq!(layout.neighbor(1, Direction::Right, (0, 2)), Some(2));
+        assert_eq!(layout.neighbor(0, Direction::Up, (0, 0)), Some(2));
+        assert_eq!(layout.neighbor(0, Direction::Down, (0, 0)), None);
+
+        assert!(layout.remove(2));
+        layout.arrange(Rect::new(0, 1, 81, 21));
+        assert_eq!(ids(&layout), vec![1, 0]);
+        assert_eq!(rect(&layout, 0), Rect::new(41, 1, 40, 21));
+        assert!(layout.remove(1));
+        assert!(!layout.remove(0));
+    }
+
+    #[test]
+    fn test_resize_and_equalize() {
+        let mut layout = Layout::Window(Window::new(0, 0));
+        layout.split(0, false, Window::new(1, 0));
+        layout.split(1, false, Window::new(2, 0));
+        layout.arrange(Rect::new(0, 0, 80, 31));
+        assert!(layout.resize(0, false, 5));
+        assert!(!layout.resize(0, true, 5));
+        layout.arrange(Rect::new(0, 0, 80, 31));
+        assert_eq!(rect(&layout, 0).height, 20);
+
+        layout.equalize();
+        layout.arrange(Rect::new(0, 0, 80, 32));
+        let heights: Vec<u16> = layout.windows().iter().map(|w| w.rect.height).collect();
+        assert_eq!(heights, vec![10, 10, 10]);
+    }
+}
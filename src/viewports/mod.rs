@@ -1,11 +1,22 @@
-use crate::{buff::Buffer, viewport::Viewport};
+use std::rc::Rc;
+
+use crate::{
+    buff::{Buffer, SharedBuffer},
+    viewport::Viewport,
+};
+use layout::{Layout, Window};
 pub mod draw;
+pub mod layout;
 #[derive(Debug)]
 pub struct Viewports {
     pub explorer: Viewport,
     pub values: Vec<Viewport>,
-    pub index: usize,
+    pub buffers: Vec<(usize, SharedBuffer)>, // the buffer list of :ls with the ids
+    pub index: usize,                        // the viewport of the focused window
     pub is_explorer: bool,
+    pub layout: Layout,
+    pub window: usize, // the id of the focused window
+    next_id: usize,    // the buffer ids are never reused
 }
 
 impl Viewports {
@@ -13,26 +24,221 @@ impl Viewports {
         Viewports {
             explorer,
             values: vec![],
+            buffers: vec![],
             index: 0,
             is_explorer: false,
+            layout: Layout::Window(Window::new(0, 0)),
+            window: 0,
+            next_id: 1,
         }
     }
 
-    // let us know if some viewport are save
-    pub fn viewports_save_status(&mut self) -> anyhow::Result<bool> {
-        for viewport in &mut self.values {
-            if viewport.buffer.compare_file()? {
-                return Ok(false);
-            }
-        }
-        Ok(true)
+    // the hidden buffers count too, they are lost when the editor quits
+    pub fn first_modified(&self) -> Option<usize> {
+        self.buffers
+            .iter()
+            .map(|(id, _)| *id)
+            .find(|id| self.is_modified(*id))
     }
 
-    pub fn push(&mut self, viewport: Viewport) -> usize {
+    // a new viewport takes the options set on the current one and its buffer joins the list
+    pub fn push(&mut self, mut viewport: Viewport) -> usize {
+        if let Some(current) = self.values.get(self.index) {
+            viewport.set_options(current.options.clone());
+        }
+        if self.buffer_id(&viewport.buffer).is_none() {
+            self.buffers.push((self.next_id, viewport.buffer.clone()));
+            self.next_id += 1;
+        }
         self.values.push(viewport);
         self.values.len() - 1
     }
 
+    // the number of the buffer in :ls and :b
+    pub fn buffer_id(&self, buffer: &SharedBuffer) -> Option<usize> {
+        self.buffers
+            .iter()
+            .find(|(_, b)| Rc::ptr_eq(b, buffer))
+            .map(|(id, _)| *id)
+    }
+
+    pub fn buffer(&self, id: usize) -> Option<&SharedBuffer> {
+        self.buffers.iter().find(|(i, _)| *i == id).map(|(_, b)| b)
+    }
+
+    // the id of the buffer of an open file
+    pub fn find_buffer(&self, path: &str) -> Option<usize> {
+        self.buffers
+            .iter()
+            .find(|(_, b)| b.borrow().path.as_deref() == Some(path))
+            .map(|(id, _)| *id)
+    }
+
+    // the viewports showing the buffer, one for each window and the one of its tab
+    pub fn views(&self, id: usize) -> Vec<usize> {
+        let Some(buffer) = self.buffer(id) else {
+            return vec![];
+        };
+        (0..self.values.len())
+            .filter(|i| Rc::ptr_eq(&self.values[*i].buffer, buffer))
+            .collect()
+    }
+
+    // the changes which arent saved, the viewports like :grep results are never modified
+    pub fn is_modified(&self, id: usize) -> bool {
+        let views = self.views(id);
+        match views.first() {
+            Some(_) => views.iter().any(|index| self.values[*index].is_modified()),
+            None => self.buffer(id).is_some_and(|b| b.borrow().is_modified()),
+        }
+    }
+
+    // a listed buffer without viewport, it was closed while modified
+    pub fn is_hidden(&self, id: usize) -> bool {
+        self.views(id).is_empty()
+    }
+
+    // show the buffer in the focused window, with a viewport which no other window shows
+    pub fn open(&mut self, id: usize) {
+        let views = self.views(id);
+        let index = match views
+            .iter()
+            .find(|i| **i == self.index || self.refs(**i) == 0)
+        {
+            Some(index) => *index,
+            None => {
+                let Some(buffer) = self.buffer(id).cloned() else {
+                    return;
+                };
+                let viewport = match views.first() {
+                    Some(index) => self.values[*index].split(),
+                    None => {
+                        let current = &self.values[self.index];
+                        let mut viewport =
+                            Viewport::from_shared(buffer, current.vwidth, current.vheight, 0, true);
+                        viewport.set_options(current.options.clone());
+                        viewport
+                    }
+                };
+                self.values.push(viewport);
+                self.values.len() - 1
+            }
+        };
+        self.show(index);
+    }
+
+    // focus a viewport, the copy of a buffer made for a window is dropped when no window shows it
+    pub fn show(&mut self, index: usize) {
+        self.index = index;
+        self.is_explorer = false;
+        self.drop_copies();
+    }
+
+    // the number of windows showing the viewport
+    pub fn refs(&self, index: usize) -> usize {
+        self.layout
+            .windows()
+            .iter()
+            .map(|w| match w.id == self.window {
+                true => self.index,
+                false => w.viewport,
+            })
+            .filter(|viewport| *viewport == index)
+            .count()
+    }
+
+    // the viewports which no window shows and whose buffer has another viewport
+    pub fn drop_copies(&mut self) {
+        while let Some(index) = (0..self.values.len()).find(|i| {
+            self.refs(*i) == 0
+                && (0..self.values.len())
+                    .any(|j| j != *i && Rc::ptr_eq(&self.values[j].buffer, &self.values[*i].buffer))
+        }) {
+            self.remove_view(index);
+        }
+    }
+
+    // every window has its own viewport, the one showing the viewport of another window gets a
+    // copy of it
+    fn separate_windows(&mut self) {
+        let mut shown = vec![self.index];
+        let mut copies = vec![];
+        for window in self.layout.windows() {
+            match window.id != self.window && shown.contains(&window.viewport) {
+                true => copies.push(window.id),
+                false => shown.push(window.viewport),
+            }
+        }
+        for id in copies {
+            let Some(window) = self.layout.window(id) else {
+                continue;
+            };
+            let viewport = self.values[window.viewport].split();
+            self.values.push(viewport);
+            let index = self.values.len() - 1;
+            if let Some(window) = self.layout.window_mut(id) {
+                window.viewport = index;
+            }
+        }
+    }
+
+    // drop a viewport and keep the index on the same one, the windows showing it show the one of
+    // the focused window
+    fn remove_view(&mut self, index: usize) {
+        self.values.remove(index);
+        if self.index > index || self.index >= self.values.len() {
+            self.index = self.index.saturating_sub(1);
+        }
+        let focused = self.index;
+        for window in self.layout.windows_mut() {
+            window.viewport = match window.viewport.cmp(&index) {
+                std::cmp::Ordering::Less => window.viewport,
+                std::cmp::Ordering::Equal => focused,
+                std::cmp::Ordering::Greater => window.viewport - 1,
+            };
+        }
+    }
+
+    // remove the viewports of the buffer, a modified one stays hidden in the list, an empty
+    // viewport replaces the last one
+    pub fn remove_buffer(&mut self, id: usize, unlist: bool) {
+        let Some(&first) = self.views(id).first() else {
+            if unlist {
+                self.buffers.retain(|(i, _)| *i != id);
+            }
+            return;
+        };
+        let (vwidth, vheight) = (self.values[first].vwidth, self.values[first].vheight);
+        for index in self.views(id).into_iter().rev() {
+            self.remove_view(index);
+        }
+        if unlist {
+            self.buffers.retain(|(i, _)| *i != id);
+        }
+        if self.values.is_empty() {
+            let viewport = Viewport::new(Buffer::new(None), vwidth, vheight, 0, true);
+            self.index = self.push(viewport);
+        }
+        self.separate_windows();
+    }
+
+    // only keep the viewport of the focused window and hide the modified ones, every window shows
+    // the focused one
+    pub fn keep_current(&mut self) {
+        let current = self.values[self.index].buffer.clone();
+        for (id, buffer) in self.buffers.clone() {
+            if Rc::ptr_eq(&buffer, &current) {
+                continue;
+            }
+            self.remove_buffer(id, !self.is_modified(id));
+        }
+        for window in self.layout.windows_mut() {
+            window.viewport = self.index;
+        }
+        self.drop_copies();
+        self.separate_windows();
+    }
+
     pub fn c_viewport(&self) -> &Viewport {
         match self.is_explorer {
             true => &self.explorer,
@@ -47,30 +253,56 @@ impl Viewports {
         }
     }
 
+    // the buffers of the tabs, the hidden ones are skipped
+    pub fn shown_buffers(&self) -> Vec<usize> {
+        self.buffers
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| !self.is_hidden(*id))
+            .collect()
+    }
+
+    // the buffer of the focused window, the one shown again when the explorer is closed
+    pub fn current_id(&self) -> Option<usize> {
+        self.buffer_id(&self.values.get(self.index)?.buffer)
+    }
+
+    // the hidden buffers are reached too, like :bnext does
+    fn cycle(&mut self, forward: bool) {
+        let len = self.buffers.len();
+        let Some(position) = self
+            .current_id()
+            .and_then(|id| self.buffers.iter().position(|(i, _)| *i == id))
+        else {
+            return;
+        };
+        let position = match forward {
+            true => (position + 1) % len,
+            false => (position + len - 1) % len,
+        };
+        self.open(self.buffers[position].0);
+    }
+
     pub fn prev_viewport(&mut self) {
-        let prev_index = self.index.saturating_sub(1);
-        self.index = match self.index == 0 {
-            true => self.values.len() - 1,
-            false => prev_index,
-        }
+        self.cycle(false);
     }
     pub fn next_viewport(&mut self) {
-        let next_index = self.index + 1;
-        self.index = match self.values.get(next_index).is_some() {
-            true => next_index,
-            false => 0,
-        }
+        self.cycle(true);
     }
 }
 
 impl Default for Viewports {
     fn default() -> Self {
-        let values = vec![Viewport::new(Buffer::new(None), 80, 20, 0, true)];
+        let viewport = Viewport::new(Buffer::new(None), 80, 20, 0, true);
         Self {
             explorer: Viewport::new(Buffer::new(Some("./".to_string())), 80, 20, 0, true),
-            values,
+            buffers: vec![(1, viewport.buffer.clone())],
+            values: vec![viewport],
             index: 0,
             is_explorer: false,
+            layout: Layout::Window(Window::new(0, 0)),
+            window: 0,
+            next_id: 2,
         }
     }
 }